@@ -2346,4 +2346,34 @@ mod tests {
 
         assert_eq!(f1.bytes, f2.bytes)
     }
+
+    #[test]
+    fn code_section_raw_copies_bodies() {
+        use super::*;
+
+        let body = |value| {
+            let mut f = Function::new([(1, ValType::I64)]);
+            f.instruction(&Instruction::I32Const(value));
+            f.instruction(&Instruction::Drop);
+            f.instruction(&Instruction::End);
+            f
+        };
+        let mut original = CodeSection::new();
+        original.function(&body(1)).function(&body(2));
+        // The contents of the section, as a `wasmparser` reader expects them.
+        let mut bytes = vec![original.num_added as u8];
+        bytes.extend_from_slice(&original.bytes);
+
+        // Copy the first body unchanged and replace the second one.
+        let mut copied = CodeSection::new();
+        let mut reader = wasmparser::CodeSectionReader::new(&bytes, 0).unwrap();
+        let first = reader.read().unwrap().range();
+        copied.raw(&bytes[first.start..first.end]);
+        copied.function(&body(3));
+
+        let mut expected = CodeSection::new();
+        expected.function(&body(1)).function(&body(3));
+        assert_eq!(copied.bytes, expected.bytes);
+        assert_eq!(copied.num_added, 2);
+    }
 }
@@ -17,6 +17,7 @@ use crate::{encoders, Instruction, Section, SectionId};
 ///     minimum: 1,
 ///     maximum: None,
 ///     memory64: false,
+///     shared: false,
 /// });
 ///
 /// let mut data = DataSection::new();
@@ -80,6 +80,18 @@ pub enum ElementMode<'a> {
         /// The offset within the table to place this segment.
         offset: &'a Instruction<'a>,
     },
+    /// An active element segment whose offset is a constant expression made
+    /// of several instructions.
+    ///
+    /// Offsets made of more than one instruction are part of the extended
+    /// constant expressions proposal.
+    ActiveExpr {
+        /// The table index, as in [`ElementMode::Active`].
+        table: Option<u32>,
+        /// The instructions computing the offset within the table to place
+        /// this segment, without the trailing `end`.
+        offset: &'a [Instruction<'a>],
+    },
 }
 
 /// An element segment in the element section.
@@ -116,13 +128,14 @@ impl ElementSection {
             Elements::Functions(_) => 0b000,
         };
         match &segment.mode {
-            ElementMode::Active {
-                table: None,
-                offset,
-            } => {
-                self.bytes.extend(encoders::u32(0x00 | expr_bit));
-                offset.encode(&mut self.bytes);
-                Instruction::End.encode(&mut self.bytes);
+            ElementMode::Active { table, offset } => self.active_mode(
+                *table,
+                std::slice::from_ref(*offset),
+                expr_bit,
+                segment.element_type,
+            ),
+            ElementMode::ActiveExpr { table, offset } => {
+                self.active_mode(*table, offset, expr_bit, segment.element_type)
             }
             ElementMode::Passive => {
                 self.bytes.extend(encoders::u32(0x01 | expr_bit));
@@ -132,20 +145,6 @@ impl ElementSection {
                     self.bytes.push(segment.element_type.into());
                 }
             }
-            ElementMode::Active {
-                table: Some(i),
-                offset,
-            } => {
-                self.bytes.extend(encoders::u32(0x02 | expr_bit));
-                self.bytes.extend(encoders::u32(*i));
-                offset.encode(&mut self.bytes);
-                Instruction::End.encode(&mut self.bytes);
-                if expr_bit == 0 {
-                    self.bytes.push(0x00); // elemkind == funcref
-                } else {
-                    self.bytes.push(segment.element_type.into());
-                }
-            }
             ElementMode::Declared => {
                 self.bytes.extend(encoders::u32(0x03 | expr_bit));
                 if expr_bit == 0 {
@@ -182,6 +181,38 @@ impl ElementSection {
         self
     }
 
+    /// Encodes the flags, table and offset of an active element segment.
+    fn active_mode(
+        &mut self,
+        table: Option<u32>,
+        offset: &[Instruction<'_>],
+        expr_bit: u32,
+        element_type: ValType,
+    ) {
+        match table {
+            None => {
+                self.bytes.extend(encoders::u32(0x00 | expr_bit));
+                for instruction in offset {
+                    instruction.encode(&mut self.bytes);
+                }
+                Instruction::End.encode(&mut self.bytes);
+            }
+            Some(i) => {
+                self.bytes.extend(encoders::u32(0x02 | expr_bit));
+                self.bytes.extend(encoders::u32(i));
+                for instruction in offset {
+                    instruction.encode(&mut self.bytes);
+                }
+                Instruction::End.encode(&mut self.bytes);
+                if expr_bit == 0 {
+                    self.bytes.push(0x00); // elemkind == funcref
+                } else {
+                    self.bytes.push(element_type.into());
+                }
+            }
+        }
+    }
+
     /// Define an active element segment.
     ///
     /// Table `None` is implicitly table `0`. Non-`None` tables are part of the
@@ -203,6 +234,29 @@ impl ElementSection {
         })
     }
 
+    /// Define an active element segment whose offset is the constant
+    /// expression made of the `offset` instructions.
+    ///
+    /// Offsets made of more than one instruction, such as
+    /// `(i32.add (global.get 0) (i32.const 4))`, are part of the extended
+    /// constant expressions proposal.
+    pub fn active_expr(
+        &mut self,
+        table_index: Option<u32>,
+        offset: &[Instruction<'_>],
+        element_type: ValType,
+        elements: Elements<'_>,
+    ) -> &mut Self {
+        self.segment(ElementSegment {
+            mode: ElementMode::ActiveExpr {
+                table: table_index,
+                offset,
+            },
+            element_type,
+            elements,
+        })
+    }
+
     /// Encode a passive element segment.
     ///
     /// Passive segments are part of the bulk memory proposal.
@@ -251,3 +305,50 @@ impl Section for ElementSection {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EntityType, GlobalType, ImportSection, Module, TableSection, TableType};
+    use wasmparser::{Validator, WasmFeatures};
+
+    #[test]
+    fn extended_const_offset() {
+        let mut imports = ImportSection::new();
+        imports.import(
+            "",
+            "g",
+            EntityType::Global(GlobalType {
+                val_type: ValType::I32,
+                mutable: false,
+            }),
+        );
+        let mut tables = TableSection::new();
+        tables.table(TableType {
+            element_type: ValType::FuncRef,
+            minimum: 8,
+            maximum: None,
+        });
+        let mut elements = ElementSection::new();
+        elements.active_expr(
+            None,
+            &[
+                Instruction::GlobalGet(0),
+                Instruction::I32Const(4),
+                Instruction::I32Add,
+            ],
+            ValType::FuncRef,
+            Elements::Functions(&[]),
+        );
+        let mut module = Module::new();
+        module.section(&imports).section(&tables).section(&elements);
+        let wasm = module.finish();
+
+        let mut validator = Validator::new_with_features(WasmFeatures {
+            extended_const: true,
+            ..Default::default()
+        });
+        validator.validate_all(&wasm).unwrap();
+        assert!(Validator::new().validate_all(&wasm).is_err());
+    }
+}
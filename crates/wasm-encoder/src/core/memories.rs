@@ -14,6 +14,7 @@ use crate::{encoders, Section, SectionId};
 ///     minimum: 1,
 ///     maximum: None,
 ///     memory64: false,
+///     shared: false,
 /// });
 ///
 /// let mut module = Module::new();
@@ -71,6 +72,28 @@ impl Section for MemorySection {
 }
 
 /// A memory's type.
+///
+/// # Example
+///
+/// Shared memories are part of the threads proposal and must declare a
+/// maximum size.
+///
+/// ```
+/// use wasm_encoder::{Module, MemorySection, MemoryType};
+///
+/// let mut memories = MemorySection::new();
+/// memories.memory(MemoryType {
+///     minimum: 1,
+///     maximum: Some(16),
+///     memory64: false,
+///     shared: true,
+/// });
+///
+/// let mut module = Module::new();
+/// module.section(&memories);
+///
+/// let wasm_bytes = module.finish();
+/// ```
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct MemoryType {
     /// Minimum size, in pages, of this memory
@@ -79,14 +102,23 @@ pub struct MemoryType {
     pub maximum: Option<u64>,
     /// Whether or not this is a 64-bit memory.
     pub memory64: bool,
+    /// Whether or not this memory is shared between threads.
+    pub shared: bool,
 }
 
 impl MemoryType {
     pub(crate) fn encode(&self, bytes: &mut Vec<u8>) {
+        debug_assert!(
+            !self.shared || self.maximum.is_some(),
+            "shared memories must have a maximum size"
+        );
         let mut flags = 0;
         if self.maximum.is_some() {
             flags |= 0b001;
         }
+        if self.shared {
+            flags |= 0b010;
+        }
         if self.memory64 {
             flags |= 0b100;
         }
@@ -97,3 +129,55 @@ impl MemoryType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Module;
+    use wasmparser::{Parser, Payload};
+
+    #[test]
+    fn shared_memory_round_trips() {
+        let mut memories = MemorySection::new();
+        memories.memory(MemoryType {
+            minimum: 1,
+            maximum: Some(2),
+            memory64: false,
+            shared: true,
+        });
+        memories.memory(MemoryType {
+            minimum: 1,
+            maximum: Some(2),
+            memory64: false,
+            shared: false,
+        });
+        let mut module = Module::new();
+        module.section(&memories);
+        let wasm = module.finish();
+
+        let mut shared = Vec::new();
+        for payload in Parser::new(0).parse_all(&wasm) {
+            if let Payload::MemorySection(reader) = payload.unwrap() {
+                for memory in reader {
+                    let memory = memory.unwrap();
+                    assert_eq!(memory.initial, 1);
+                    assert_eq!(memory.maximum, Some(2));
+                    shared.push(memory.shared);
+                }
+            }
+        }
+        assert_eq!(shared, [true, false]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "shared memories must have a maximum size")]
+    fn shared_memory_requires_maximum() {
+        MemorySection::new().memory(MemoryType {
+            minimum: 1,
+            maximum: None,
+            memory64: false,
+            shared: true,
+        });
+    }
+}
@@ -47,6 +47,18 @@ impl TagSection {
         self.num_added += 1;
         self
     }
+
+    /// Define a tag with the given raw attribute byte instead of a
+    /// [`TagKind`].
+    ///
+    /// This allows encoding kinds of tags which this crate doesn't know
+    /// about yet. An attribute of 0 is the same as [`TagKind::Exception`].
+    pub fn tag_with_attribute(&mut self, attribute: u8, func_type_idx: u32) -> &mut Self {
+        self.bytes.push(attribute);
+        self.bytes.extend(encoders::u32(func_type_idx));
+        self.num_added += 1;
+        self
+    }
 }
 
 impl Section for TagSection {
@@ -91,3 +103,72 @@ impl TagType {
         bytes.extend(encoders::u32(self.func_type_idx));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Module, TypeSection};
+    use wasmparser::{Parser, Payload, Validator, WasmFeatures};
+
+    fn module(tags: &TagSection) -> Vec<u8> {
+        let mut types = TypeSection::new();
+        types.function([], []);
+        let mut module = Module::new();
+        module.section(&types);
+        module.section(tags);
+        module.finish()
+    }
+
+    #[test]
+    fn exception_attribute_round_trips() {
+        let mut tags = TagSection::new();
+        tags.tag(TagType {
+            kind: TagKind::Exception,
+            func_type_idx: 0,
+        });
+        tags.tag_with_attribute(0, 0);
+        let wasm = module(&tags);
+
+        let mut kinds = Vec::new();
+        for payload in Parser::new(0).parse_all(&wasm) {
+            if let Payload::TagSection(reader) = payload.unwrap() {
+                for tag in reader {
+                    let tag = tag.unwrap();
+                    assert_eq!(tag.func_type_idx, 0);
+                    kinds.push(tag.kind);
+                }
+            }
+        }
+        assert!(matches!(
+            kinds[..],
+            [
+                wasmparser::TagKind::Exception,
+                wasmparser::TagKind::Exception
+            ]
+        ));
+
+        let features = WasmFeatures {
+            exceptions: true,
+            ..WasmFeatures::default()
+        };
+        Validator::new_with_features(features)
+            .validate_all(&wasm)
+            .unwrap();
+    }
+
+    #[test]
+    fn unknown_attribute_is_rejected() {
+        let mut tags = TagSection::new();
+        tags.tag_with_attribute(1, 0);
+        let wasm = module(&tags);
+
+        let features = WasmFeatures {
+            exceptions: true,
+            ..WasmFeatures::default()
+        };
+        match Validator::new_with_features(features).validate_all(&wasm) {
+            Ok(_) => panic!("a tag attribute of 1 should be rejected"),
+            Err(e) => assert!(e.message().contains("invalid tag attributes")),
+        }
+    }
+}
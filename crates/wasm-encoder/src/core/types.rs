@@ -34,6 +34,48 @@ impl From<ValType> for u8 {
     }
 }
 
+/// The type stored in a struct field or array element.
+///
+/// Part of the GC proposal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StorageType {
+    /// The packed `i8` type.
+    I8,
+    /// The packed `i16` type.
+    I16,
+    /// A value type.
+    Val(ValType),
+}
+
+impl From<StorageType> for u8 {
+    #[inline]
+    fn from(t: StorageType) -> u8 {
+        match t {
+            StorageType::I8 => 0x7A,
+            StorageType::I16 => 0x79,
+            StorageType::Val(ty) => ty.into(),
+        }
+    }
+}
+
+/// The type of a struct field or of an array's elements.
+///
+/// Part of the GC proposal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FieldType {
+    /// The type stored in the field.
+    pub element_type: StorageType,
+    /// Whether or not the field is mutable.
+    pub mutable: bool,
+}
+
+impl FieldType {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.push(self.element_type.into());
+        bytes.push(self.mutable as u8);
+    }
+}
+
 pub(crate) fn encode_functype<P, R>(bytes: &mut Vec<u8>, params: P, results: R)
 where
     P: IntoIterator<Item = ValType>,
@@ -101,6 +143,35 @@ impl TypeSection {
         self.num_added += 1;
         self
     }
+
+    /// Define a struct type in this type section.
+    ///
+    /// Part of the GC proposal.
+    pub fn struct_<F>(&mut self, fields: F) -> &mut Self
+    where
+        F: IntoIterator<Item = FieldType>,
+        F::IntoIter: ExactSizeIterator,
+    {
+        let fields = fields.into_iter();
+        self.bytes.push(0x5f);
+        self.bytes
+            .extend(encoders::u32(u32::try_from(fields.len()).unwrap()));
+        for field in fields {
+            field.encode(&mut self.bytes);
+        }
+        self.num_added += 1;
+        self
+    }
+
+    /// Define an array type in this type section.
+    ///
+    /// Part of the GC proposal.
+    pub fn array(&mut self, ty: FieldType) -> &mut Self {
+        self.bytes.push(0x5e);
+        ty.encode(&mut self.bytes);
+        self.num_added += 1;
+        self
+    }
 }
 
 impl Section for TypeSection {
@@ -121,3 +192,45 @@ impl Section for TypeSection {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Module;
+    use wasmparser::{Validator, WasmFeatures};
+
+    #[test]
+    fn struct_type_round_trips() {
+        let mut types = TypeSection::new();
+        types.struct_([FieldType {
+            element_type: StorageType::Val(ValType::I32),
+            mutable: false,
+        }]);
+        let mut module = Module::new();
+        module.section(&types);
+        let wasm = module.finish();
+
+        let mut validator = Validator::new_with_features(WasmFeatures {
+            gc: true,
+            ..Default::default()
+        });
+        let types = validator.validate_all(&wasm).unwrap();
+        match types.type_at(0) {
+            Some(wasmparser::types::TypeDef::Struct(ty)) => {
+                assert_eq!(
+                    ty.fields.as_ref(),
+                    [wasmparser::FieldType {
+                        element_type: wasmparser::StorageType::Val(wasmparser::Type::I32),
+                        mutable: false,
+                    }]
+                );
+            }
+            _ => panic!("expected a struct type"),
+        }
+
+        match Validator::new().validate_all(&wasm) {
+            Ok(_) => panic!("struct types should require the gc feature"),
+            Err(e) => assert_eq!(e.missing_feature(), Some("gc")),
+        }
+    }
+}
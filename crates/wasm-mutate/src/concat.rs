@@ -0,0 +1,366 @@
+//! Merging two modules into one.
+
+use crate::info::ModuleInfo;
+use crate::mutators::{Item, Translator};
+use crate::{Error, Result};
+use std::collections::HashSet;
+use wasm_encoder::*;
+use wasmparser::{
+    BinaryReader, CodeSectionReader, DataSectionReader, ElementSectionReader, ExportSectionReader,
+    ExternalKind, FunctionSectionReader, GlobalSectionReader, ImportSectionReader,
+    MemorySectionReader, TableSectionReader, TagSectionReader, TypeSectionReader,
+};
+
+/// Merges the modules `a` and `b` into a single module defining the items of
+/// both.
+///
+/// In every index space the imports of `a` come first, followed by the
+/// imports of `b`, then the items defined by `a` and finally the items
+/// defined by `b`, and all references are renumbered accordingly.
+///
+/// Exports of `b` whose names are already exported by `a` are renamed by
+/// appending a numeric suffix. Merging fails if both modules have a start
+/// function. Custom sections, including the name section, are not carried
+/// over since their contents would no longer be accurate.
+///
+/// The merged module may need more features than either input, for example
+/// multiple memories if both modules have one.
+pub fn concat_modules(a: &[u8], b: &[u8]) -> Result<Vec<u8>> {
+    let a = ModuleInfo::new(a)?;
+    let b = ModuleInfo::new(b)?;
+    let mut translators = [
+        Renumber {
+            own: &a,
+            other: &b,
+            first: true,
+        },
+        Renumber {
+            own: &b,
+            other: &a,
+            first: false,
+        },
+    ];
+
+    let mut module = Module::new();
+    for id in [
+        SectionId::Type,
+        SectionId::Import,
+        SectionId::Function,
+        SectionId::Table,
+        SectionId::Memory,
+        SectionId::Tag,
+        SectionId::Global,
+        SectionId::Export,
+        SectionId::Start,
+        SectionId::Element,
+        SectionId::DataCount,
+        SectionId::Code,
+        SectionId::Data,
+    ] {
+        // Pairs each section of this kind with the index of the translator
+        // for the module it came from.
+        let mut sections = Vec::new();
+        for (i, t) in translators.iter().enumerate() {
+            for section in t.own.raw_sections.iter().filter(|s| s.id == id as u8) {
+                sections.push((i, section.data));
+            }
+        }
+        if sections.is_empty() {
+            continue;
+        }
+
+        match id {
+            SectionId::Type => {
+                let mut result = TypeSection::new();
+                for (i, data) in sections {
+                    let t = &mut translators[i];
+                    for ty in TypeSectionReader::new(data, 0)? {
+                        t.translate_type_def(ty?, &mut result)?;
+                    }
+                }
+                module.section(&result);
+            }
+            SectionId::Import => {
+                let mut result = ImportSection::new();
+                for (i, data) in sections {
+                    let t = &mut translators[i];
+                    for item in ImportSectionReader::new(data, 0)? {
+                        let item = item?;
+                        let ty: EntityType = match &item.ty {
+                            wasmparser::TypeRef::Func(ty) => {
+                                EntityType::Function(t.remap(Item::Type, *ty)?)
+                            }
+                            wasmparser::TypeRef::Table(ty) => t.translate_table_type(ty)?.into(),
+                            wasmparser::TypeRef::Memory(ty) => t.translate_memory_type(ty)?.into(),
+                            wasmparser::TypeRef::Global(ty) => t.translate_global_type(ty)?.into(),
+                            wasmparser::TypeRef::Tag(ty) => t.translate_tag_type(ty)?.into(),
+                        };
+                        result.import(item.module, item.name, ty);
+                    }
+                }
+                module.section(&result);
+            }
+            SectionId::Function => {
+                let mut result = FunctionSection::new();
+                for (i, data) in sections {
+                    let t = &mut translators[i];
+                    for ty in FunctionSectionReader::new(data, 0)? {
+                        result.function(t.remap(Item::Type, ty?)?);
+                    }
+                }
+                module.section(&result);
+            }
+            SectionId::Table => {
+                let mut result = TableSection::new();
+                for (i, data) in sections {
+                    let t = &mut translators[i];
+                    for ty in TableSectionReader::new(data, 0)? {
+                        result.table(t.translate_table_type(&ty?)?);
+                    }
+                }
+                module.section(&result);
+            }
+            SectionId::Memory => {
+                let mut result = MemorySection::new();
+                for (i, data) in sections {
+                    let t = &mut translators[i];
+                    for ty in MemorySectionReader::new(data, 0)? {
+                        result.memory(t.translate_memory_type(&ty?)?);
+                    }
+                }
+                module.section(&result);
+            }
+            SectionId::Tag => {
+                let mut result = TagSection::new();
+                for (i, data) in sections {
+                    let t = &mut translators[i];
+                    for ty in TagSectionReader::new(data, 0)? {
+                        result.tag(t.translate_tag_type(&ty?)?);
+                    }
+                }
+                module.section(&result);
+            }
+            SectionId::Global => {
+                let mut result = GlobalSection::new();
+                for (i, data) in sections {
+                    let t = &mut translators[i];
+                    for global in GlobalSectionReader::new(data, 0)? {
+                        t.translate_global(global?, &mut result)?;
+                    }
+                }
+                module.section(&result);
+            }
+            SectionId::Export => {
+                let mut result = ExportSection::new();
+                let mut names = HashSet::new();
+                for (i, data) in sections {
+                    let t = &mut translators[i];
+                    for item in ExportSectionReader::new(data, 0)? {
+                        let item = item?;
+                        let export = match &item.kind {
+                            ExternalKind::Func => {
+                                Export::Function(t.remap(Item::Function, item.index)?)
+                            }
+                            ExternalKind::Table => Export::Table(t.remap(Item::Table, item.index)?),
+                            ExternalKind::Memory => {
+                                Export::Memory(t.remap(Item::Memory, item.index)?)
+                            }
+                            ExternalKind::Tag => Export::Tag(t.remap(Item::Tag, item.index)?),
+                            ExternalKind::Global => {
+                                Export::Global(t.remap(Item::Global, item.index)?)
+                            }
+                        };
+                        let mut name = item.name.to_string();
+                        let mut suffix = 1;
+                        while names.contains(&name) {
+                            name = format!("{}_{}", item.name, suffix);
+                            suffix += 1;
+                        }
+                        result.export(&name, export);
+                        names.insert(name);
+                    }
+                }
+                module.section(&result);
+            }
+            SectionId::Start => {
+                if sections.len() > 1 {
+                    return Err(Error::unsupported("both modules have a start function"));
+                }
+                let (i, data) = sections[0];
+                let t = &mut translators[i];
+                let function_index = BinaryReader::new(data).read_var_u32()?;
+                let function_index = t.remap(Item::Function, function_index)?;
+                module.section(&StartSection { function_index });
+            }
+            SectionId::Element => {
+                let mut result = ElementSection::new();
+                for (i, data) in sections {
+                    let t = &mut translators[i];
+                    for element in ElementSectionReader::new(data, 0)? {
+                        t.translate_element(element?, &mut result)?;
+                    }
+                }
+                module.section(&result);
+            }
+            SectionId::DataCount => {
+                let count = a.num_data() + b.num_data();
+                module.section(&DataCountSection { count });
+            }
+            SectionId::Code => {
+                let mut result = CodeSection::new();
+                for (i, data) in sections {
+                    let t = &mut translators[i];
+                    for body in CodeSectionReader::new(data, 0)? {
+                        t.translate_code(body?, &mut result)?;
+                    }
+                }
+                module.section(&result);
+            }
+            SectionId::Data => {
+                let mut result = DataSection::new();
+                for (i, data) in sections {
+                    let t = &mut translators[i];
+                    for segment in DataSectionReader::new(data, 0)? {
+                        t.translate_data(segment?, &mut result)?;
+                    }
+                }
+                module.section(&result);
+            }
+            SectionId::Custom => unreachable!(),
+        }
+    }
+    Ok(module.finish())
+}
+
+/// Renumbers the items of one of the modules being merged.
+struct Renumber<'a> {
+    own: &'a ModuleInfo<'a>,
+    other: &'a ModuleInfo<'a>,
+    /// Whether `own` is the first of the two modules.
+    first: bool,
+}
+
+/// Returns the number of imported items and the total number of items of
+/// kind `item` in `info`.
+fn counts(info: &ModuleInfo, item: Item) -> (u32, u32) {
+    match item {
+        Item::Function => (info.num_imported_functions(), info.num_functions()),
+        Item::Table => (info.num_imported_tables(), info.num_tables()),
+        Item::Memory => (info.num_imported_memories(), info.num_memories()),
+        Item::Tag => (info.num_imported_tags(), info.num_tags()),
+        Item::Global => (info.num_imported_globals(), info.num_globals()),
+        Item::Type => (0, info.num_types()),
+        Item::Data => (0, info.num_data()),
+        Item::Element => (0, info.num_elements()),
+    }
+}
+
+impl Translator for Renumber<'_> {
+    fn as_obj(&mut self) -> &mut dyn Translator {
+        self
+    }
+
+    fn remap(&mut self, item: Item, idx: u32) -> Result<u32> {
+        let (own_imported, _) = counts(self.own, item);
+        let (other_imported, other_total) = counts(self.other, item);
+        let imported = idx < own_imported;
+        Ok(if self.first && imported {
+            idx
+        } else if self.first || imported {
+            // Either an item defined by the first module, which now follows
+            // the imports of the second, or an import of the second module,
+            // which now follows the imports of the first.
+            idx + other_imported
+        } else {
+            idx + other_total
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::concat_modules;
+
+    #[test]
+    fn test_concat_modules() {
+        let a = wat::parse_str(
+            r#"
+            (module
+                (import "env" "a" (func (param i32)))
+                (func (export "f") (result i32)
+                    i32.const 1
+                    call 0
+                    i32.const 2
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let b = wat::parse_str(
+            r#"
+            (module
+                (import "env" "b" (func (result i64)))
+                (func (export "f") (param i32) (result i32)
+                    call 0
+                    drop
+                    local.get 0
+                    call 1
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let merged = concat_modules(&a, &b).unwrap();
+        crate::validate(&merged);
+
+        let expected = wat::parse_str(
+            r#"
+            (module
+                (type (func (param i32)))
+                (type (func (result i32)))
+                (type (func (result i64)))
+                (type (func (param i32) (result i32)))
+                (import "env" "a" (func (type 0)))
+                (import "env" "b" (func (type 2)))
+                (func (type 1)
+                    i32.const 1
+                    call 0
+                    i32.const 2
+                )
+                (func (type 3)
+                    call 1
+                    drop
+                    local.get 0
+                    call 3
+                )
+                (export "f" (func 2))
+                (export "f_1" (func 3))
+            )
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            wasmprinter::print_bytes(&merged).unwrap(),
+            wasmprinter::print_bytes(&expected).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_concat_modules_single_functions() {
+        let a = wat::parse_str("(module (func))").unwrap();
+        let b = wat::parse_str("(module (func (param i32)))").unwrap();
+        let merged = concat_modules(&a, &b).unwrap();
+        crate::validate(&merged);
+        let expected = wat::parse_str("(module (func) (func (param i32)))").unwrap();
+        assert_eq!(
+            wasmprinter::print_bytes(&merged).unwrap(),
+            wasmprinter::print_bytes(&expected).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_concat_modules_two_starts() {
+        let a = wat::parse_str("(module (func) (start 0))").unwrap();
+        assert!(concat_modules(&a, &a).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+//! Section-by-section comparison of two modules.
+
+use crate::info::ModuleInfo;
+use crate::Result;
+use wasm_encoder::SectionId;
+
+/// How a module changed, as computed by [`byte_diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteDiff {
+    /// The number of bytes by which sections shrank, including the full size
+    /// of sections which were removed.
+    pub removed: usize,
+    /// The number of bytes by which sections grew, including the full size of
+    /// sections which were added.
+    pub added: usize,
+    /// The known sections whose contents differ, in the order they first
+    /// appear in the module before, followed by those only in the module
+    /// after.
+    pub sections_changed: Vec<SectionId>,
+}
+
+fn section_id(id: u8) -> Option<SectionId> {
+    Some(match id {
+        0 => SectionId::Custom,
+        1 => SectionId::Type,
+        2 => SectionId::Import,
+        3 => SectionId::Function,
+        4 => SectionId::Table,
+        5 => SectionId::Memory,
+        6 => SectionId::Global,
+        7 => SectionId::Export,
+        8 => SectionId::Start,
+        9 => SectionId::Element,
+        10 => SectionId::Code,
+        11 => SectionId::Data,
+        12 => SectionId::DataCount,
+        13 => SectionId::Tag,
+        _ => return None,
+    })
+}
+
+/// Compares the sections of the modules `before` and `after`.
+///
+/// Sections are paired up by id, and sections sharing an id, such as custom
+/// sections, are paired up in the order they appear. This is meant to report
+/// the progress of reducers, so only the sizes of sections are compared: a
+/// section which changed without changing size counts as changed without
+/// adding or removing any bytes.
+pub fn byte_diff(before: &[u8], after: &[u8]) -> Result<ByteDiff> {
+    let before = ModuleInfo::new(before)?.raw_sections;
+    let after = ModuleInfo::new(after)?.raw_sections;
+    let mut diff = ByteDiff {
+        removed: 0,
+        added: 0,
+        sections_changed: Vec::new(),
+    };
+
+    let mut ids = Vec::new();
+    for section in before.iter().chain(&after) {
+        if !ids.contains(&section.id) {
+            ids.push(section.id);
+        }
+    }
+    for id in ids {
+        let old = before.iter().filter(|s| s.id == id).map(|s| s.data);
+        let mut new = after.iter().filter(|s| s.id == id).map(|s| s.data);
+        let mut changed = false;
+        for old in old {
+            match new.next() {
+                Some(new) => {
+                    diff.removed += old.len().saturating_sub(new.len());
+                    diff.added += new.len().saturating_sub(old.len());
+                    changed |= old != new;
+                }
+                None => {
+                    diff.removed += old.len();
+                    changed = true;
+                }
+            }
+        }
+        for new in new {
+            diff.added += new.len();
+            changed = true;
+        }
+        if changed {
+            diff.sections_changed.extend(section_id(id));
+        }
+    }
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::byte_diff;
+    use wasm_encoder::SectionId;
+
+    #[test]
+    fn test_byte_diff_export_removal() {
+        let before = wat::parse_str(
+            r#"
+            (module
+                (func (export "a"))
+                (func (export "bb"))
+            )
+            "#,
+        )
+        .unwrap();
+        let after = wat::parse_str(
+            r#"
+            (module
+                (func (export "a"))
+                (func)
+            )
+            "#,
+        )
+        .unwrap();
+        let diff = byte_diff(&before, &after).unwrap();
+        // The export of "bb" took a 1-byte name length, 2 bytes of name, a
+        // kind and an index.
+        assert_eq!(diff.removed, 5);
+        assert_eq!(diff.added, 0);
+        assert_eq!(diff.sections_changed, [SectionId::Export]);
+
+        let diff = byte_diff(&before, &before).unwrap();
+        assert_eq!(diff.removed, 0);
+        assert!(diff.sections_changed.is_empty());
+    }
+
+    #[test]
+    fn test_byte_diff_keeps_module_order() {
+        let before = wat::parse_str(r#"(module (func) (@custom "c" "a"))"#).unwrap();
+        let after = wat::parse_str(r#"(module (func nop) (@custom "c" "ab"))"#).unwrap();
+        let diff = byte_diff(&before, &after).unwrap();
+        assert_eq!(diff.sections_changed, [SectionId::Code, SectionId::Custom]);
+    }
+}
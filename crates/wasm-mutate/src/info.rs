@@ -92,10 +92,8 @@ impl<'a> ModuleInfo<'a> {
 
                     // Save function types
                     for _ in 0..reader.get_count() {
-                        reader.read().map(|ty| {
-                            let typeinfo = TypeInfo::try_from(ty).unwrap();
-                            info.types_map.push(typeinfo);
-                        })?;
+                        let typeinfo = TypeInfo::try_from(reader.read()?)?;
+                        info.types_map.push(typeinfo);
                     }
                 }
                 Payload::ImportSection(mut reader) => {
@@ -174,6 +172,10 @@ impl<'a> ModuleInfo<'a> {
                         info.global_types.push(ty);
                     }
                 }
+                Payload::TagSection(reader) => {
+                    info.tag_count += reader.get_count();
+                    info.section(SectionId::Tag.into(), reader.range(), input_wasm);
+                }
                 Payload::ExportSection(mut reader) => {
                     info.exports = Some(info.raw_sections.len());
                     info.exports_count = reader.get_count();
@@ -263,6 +265,22 @@ impl<'a> ModuleInfo<'a> {
         self.raw_sections[self.code.unwrap()]
     }
 
+    /// Returns the body of every function defined in the code section, in
+    /// order.
+    ///
+    /// Byte ranges of the returned bodies are relative to the start of the
+    /// code section's contents.
+    pub fn code_bodies(&self) -> Result<Vec<wasmparser::FunctionBody<'a>>> {
+        let code_section = match self.code {
+            Some(section) => self.raw_sections[section],
+            None => return Ok(Vec::new()),
+        };
+        let mut reader = wasmparser::CodeSectionReader::new(code_section.data, 0)?;
+        (0..reader.get_count())
+            .map(|_| Ok(reader.read()?))
+            .collect()
+    }
+
     pub fn get_exports_section(&self) -> RawSection<'a> {
         self.raw_sections[self.exports.unwrap()]
     }
@@ -352,6 +370,61 @@ impl<'a> ModuleInfo<'a> {
         module
     }
 
+    /// Returns a new module in which every section listed in `ids` is written
+    /// by `write`, either in place of the original section with the same id
+    /// or at its proper position if the module doesn't have such a section.
+    ///
+    /// The `ids` must be listed in the order the sections appear in a module.
+    pub fn replace_or_insert_sections(
+        &self,
+        ids: &[SectionId],
+        mut write: impl FnMut(SectionId, &mut wasm_encoder::Module),
+    ) -> wasm_encoder::Module {
+        // The position of each section in a module, which doesn't follow the
+        // section ids for the tag and data count sections.
+        fn order(id: u8) -> usize {
+            const ORDER: [SectionId; 13] = [
+                SectionId::Type,
+                SectionId::Import,
+                SectionId::Function,
+                SectionId::Table,
+                SectionId::Memory,
+                SectionId::Tag,
+                SectionId::Global,
+                SectionId::Export,
+                SectionId::Start,
+                SectionId::Element,
+                SectionId::DataCount,
+                SectionId::Code,
+                SectionId::Data,
+            ];
+            ORDER
+                .iter()
+                .position(|section| *section as u8 == id)
+                .unwrap_or(ORDER.len())
+        }
+
+        let mut module = wasm_encoder::Module::new();
+        let mut next = 0;
+        for section in self.raw_sections.iter() {
+            if section.id == SectionId::Custom as u8 {
+                module.section(section);
+                continue;
+            }
+            while next < ids.len() && order(ids[next] as u8) <= order(section.id) {
+                write(ids[next], &mut module);
+                next += 1;
+            }
+            if !ids.iter().any(|id| *id as u8 == section.id) {
+                module.section(section);
+            }
+        }
+        for id in &ids[next..] {
+            write(*id, &mut module);
+        }
+        module
+    }
+
     pub fn num_functions(&self) -> u32 {
         self.function_map.len() as u32
     }
@@ -411,4 +484,79 @@ impl<'a> ModuleInfo<'a> {
     pub fn num_types(&self) -> u32 {
         self.types_map.len() as u32
     }
+
+    /// Returns the index of the start function, if any.
+    pub fn start_function(&self) -> Option<u32> {
+        self.start_function
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ModuleInfo;
+    use wasm_encoder::{
+        Export, ExportSection, GlobalSection, GlobalType, Instruction, SectionId, ValType,
+    };
+
+    #[test]
+    fn code_bodies() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "m" "f" (func))
+                (func)
+                (func (param i32) (result i32) local.get 0)
+            )
+            "#,
+        )
+        .unwrap();
+        let info = ModuleInfo::new(&wasm).unwrap();
+        let bodies = info.code_bodies().unwrap();
+        assert_eq!(bodies.len(), info.num_local_functions() as usize);
+        assert_eq!(bodies.len(), 2);
+
+        let wasm = wat::parse_str("(module)").unwrap();
+        let info = ModuleInfo::new(&wasm).unwrap();
+        assert!(info.code_bodies().unwrap().is_empty());
+    }
+
+    #[test]
+    fn replace_or_insert_sections_after_tags() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (tag (param i32))
+                (global i32 (i32.const 0))
+                (func)
+            )
+            "#,
+        )
+        .unwrap();
+        let info = ModuleInfo::new(&wasm).unwrap();
+        let module = info.replace_or_insert_sections(
+            &[SectionId::Global, SectionId::Export],
+            |id, module| {
+                if id == SectionId::Global {
+                    let mut globals = GlobalSection::new();
+                    globals.global(
+                        GlobalType {
+                            val_type: ValType::I64,
+                            mutable: false,
+                        },
+                        &Instruction::I64Const(1),
+                    );
+                    module.section(&globals);
+                } else {
+                    let mut exports = ExportSection::new();
+                    exports.export("g", Export::Global(0));
+                    module.section(&exports);
+                }
+            },
+        );
+        let mut validator = wasmparser::Validator::new_with_features(wasmparser::WasmFeatures {
+            exceptions: true,
+            ..Default::default()
+        });
+        validator.validate_all(&module.finish()).unwrap();
+    }
 }
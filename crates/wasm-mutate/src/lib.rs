@@ -9,19 +9,66 @@
 
 #![cfg_attr(not(feature = "clap"), deny(missing_docs))]
 
+mod concat;
+mod diff;
 mod error;
 mod info;
 mod module;
 mod mutators;
 
+pub use concat::concat_modules;
+pub use diff::{byte_diff, ByteDiff};
 pub use error::*;
 
 use crate::mutators::{
-    add_function::AddFunctionMutator, add_type::AddTypeMutator, codemotion::CodemotionMutator,
-    custom::RemoveCustomSection, function_body_unreachable::FunctionBodyUnreachable,
-    modify_data::ModifyDataMutator, modify_init_exprs::InitExpressionMutator,
-    peephole::PeepholeMutator, remove_export::RemoveExportMutator, remove_item::RemoveItemMutator,
-    rename_export::RenameExportMutator, snip_function::SnipMutator, Item,
+    add_function::AddFunctionMutator,
+    add_type::AddTypeMutator,
+    block_params::BlockParamsMutator,
+    call_import::CallImportMutator,
+    codemotion::CodemotionMutator,
+    collapse_nops::CollapseNopsMutator,
+    custom::{MoveCustomSection, RemoveCustomSection},
+    data_active_to_init::DataActiveToInitMutator,
+    dedupe_types::DedupeTypesMutator,
+    default_return::DefaultReturnMutator,
+    drop_unused_function_param::DropUnusedFunctionParamMutator,
+    element_active_to_init::ElementActiveToInitMutator,
+    eqz_to_eq::EqzToEqMutator,
+    extract_subexpression::ExtractSubexpressionMutator,
+    flip_load_signedness::FlipLoadSignednessMutator,
+    fold_constant_if::FoldConstantIfMutator,
+    function_body_unreachable::FunctionBodyUnreachable,
+    global_to_accessors::GlobalToAccessorsMutator,
+    guard_divide::GuardDivideMutator,
+    if_chain_to_br_table::IfChainToBrTableMutator,
+    instrument_entry::InstrumentEntryMutator,
+    local_to_param::LocalToParamMutator,
+    merge_blocks::MergeBlocksMutator,
+    modify_data::ModifyDataMutator,
+    modify_init_exprs::InitExpressionMutator,
+    narrow_memory64::Narrow64MemoryMutator,
+    non_minimal_leb::NonMinimalLebMutator,
+    pack_locals::PackLocalsMutator,
+    peephole::PeepholeMutator,
+    prepend_param::PrependParamMutator,
+    prune_name_section::PruneNameSectionMutator,
+    reduce_v128_const::ReduceV128ConstMutator,
+    remove_export::RemoveExportMutator,
+    remove_instruction::RemoveInstructionMutator,
+    remove_item::RemoveItemMutator,
+    rename_export::RenameExportMutator,
+    return_to_br::{BrToReturnMutator, ReturnToBrMutator},
+    select_to_if::SelectToIfMutator,
+    single_iteration_loop::SingleIterationLoopMutator,
+    snip_function::SnipMutator,
+    split_table::SplitTableMutator,
+    stores_to_fill::StoresToFillMutator,
+    swap_commutative_operands::SwapCommutativeOperandsMutator,
+    toggle_global_export::ToggleGlobalExportMutator,
+    unshare_memory::UnshareMemoryMutator,
+    wrap_body_in_block::WrapBodyInBlockMutator,
+    zero_memarg_offset::ZeroMemArgOffsetMutator,
+    Item,
 };
 use info::ModuleInfo;
 use mutators::Mutator;
@@ -176,6 +223,26 @@ pub struct WasmMutate<'wasm> {
     #[cfg_attr(feature = "clap", clap(long))]
     reduce: bool,
 
+    /// Don't emit multi-value constructs, such as blocks with parameters, in
+    /// the mutated Wasm module.
+    #[cfg_attr(feature = "clap", clap(long = "no-multi-value", parse(from_flag = std::ops::Not::not)))]
+    multi_value: bool,
+
+    /// Don't emit bulk memory instructions, such as `memory.fill`, in the
+    /// mutated Wasm module.
+    #[cfg_attr(feature = "clap", clap(long = "no-bulk-memory", parse(from_flag = std::ops::Not::not)))]
+    bulk_memory: bool,
+
+    /// Don't emit reference types constructs, such as multiple tables, in the
+    /// mutated Wasm module.
+    #[cfg_attr(feature = "clap", clap(long = "no-reference-types", parse(from_flag = std::ops::Not::not)))]
+    reference_types: bool,
+
+    /// Allow mutations producing modules which some decoders reject as
+    /// invalid, such as ones encoding integers with non-minimal LEB128s.
+    #[cfg_attr(feature = "clap", clap(long))]
+    allow_invalid: bool,
+
     // Note: this is only exposed via the programmatic interface, not via the
     // CLI.
     #[cfg_attr(feature = "clap", clap(skip = None))]
@@ -200,6 +267,10 @@ impl Default for WasmMutate<'_> {
             seed,
             preserve_semantics: false,
             reduce: false,
+            multi_value: true,
+            bulk_memory: true,
+            reference_types: true,
+            allow_invalid: false,
             raw_mutate_func: None,
             fuel: Cell::new(u64::MAX),
             rng: None,
@@ -241,6 +312,42 @@ impl<'wasm> WasmMutate<'wasm> {
         self
     }
 
+    /// Configure whether the multi-value proposal may be used by the mutated
+    /// Wasm module.
+    ///
+    /// This is enabled by default.
+    pub fn multi_value(&mut self, multi_value: bool) -> &mut Self {
+        self.multi_value = multi_value;
+        self
+    }
+
+    /// Configure whether the bulk memory proposal may be used by the mutated
+    /// Wasm module.
+    ///
+    /// This is enabled by default.
+    pub fn bulk_memory(&mut self, bulk_memory: bool) -> &mut Self {
+        self.bulk_memory = bulk_memory;
+        self
+    }
+
+    /// Configure whether the reference types proposal may be used by the
+    /// mutated Wasm module.
+    ///
+    /// This is enabled by default.
+    pub fn reference_types(&mut self, reference_types: bool) -> &mut Self {
+        self.reference_types = reference_types;
+        self
+    }
+
+    /// Configure whether mutations may produce modules which some decoders
+    /// reject as invalid, such as ones with padded LEB128 integers.
+    ///
+    /// This is disabled by default.
+    pub fn allow_invalid(&mut self, allow_invalid: bool) -> &mut Self {
+        self.allow_invalid = allow_invalid;
+        self
+    }
+
     /// Set a custom raw mutation function.
     ///
     /// This is used when we need some underlying raw bytes, for example when
@@ -313,6 +420,43 @@ impl<'wasm> WasmMutate<'wasm> {
                 ModifyDataMutator {
                     max_data_size: 10 << 20, // 10MB
                 },
+                CollapseNopsMutator,
+                MergeBlocksMutator,
+                FoldConstantIfMutator,
+                ZeroMemArgOffsetMutator,
+                SwapCommutativeOperandsMutator,
+                DefaultReturnMutator,
+                DataActiveToInitMutator,
+                GlobalToAccessorsMutator,
+                InstrumentEntryMutator,
+                BlockParamsMutator,
+                PrependParamMutator,
+                DropUnusedFunctionParamMutator,
+                EqzToEqMutator,
+                DedupeTypesMutator,
+                Narrow64MemoryMutator,
+                StoresToFillMutator,
+                IfChainToBrTableMutator,
+                UnshareMemoryMutator,
+                CallImportMutator,
+                ReturnToBrMutator,
+                BrToReturnMutator,
+                ReduceV128ConstMutator,
+                LocalToParamMutator,
+                SelectToIfMutator,
+                NonMinimalLebMutator,
+                SingleIterationLoopMutator,
+                PruneNameSectionMutator,
+                FlipLoadSignednessMutator,
+                WrapBodyInBlockMutator,
+                ExtractSubexpressionMutator,
+                ElementActiveToInitMutator,
+                PackLocalsMutator,
+                RemoveInstructionMutator,
+                GuardDivideMutator,
+                MoveCustomSection,
+                SplitTableMutator,
+                ToggleGlobalExportMutator,
             )
         );
 
@@ -58,6 +58,9 @@ impl TryFrom<TypeDef> for TypeInfo {
                     .map(|&t| PrimitiveTypeInfo::from(t))
                     .collect(),
             })),
+            TypeDef::Struct(_) | TypeDef::Array(_) => {
+                Err(Error::unsupported("GC types are not supported yet"))
+            }
         }
     }
 }
@@ -74,6 +77,19 @@ pub fn map_type(tpe: Type) -> Result<ValType> {
     }
 }
 
+pub fn map_primitive_type(ty: &PrimitiveTypeInfo) -> Result<ValType> {
+    match ty {
+        PrimitiveTypeInfo::I32 => Ok(ValType::I32),
+        PrimitiveTypeInfo::I64 => Ok(ValType::I64),
+        PrimitiveTypeInfo::F32 => Ok(ValType::F32),
+        PrimitiveTypeInfo::F64 => Ok(ValType::F64),
+        PrimitiveTypeInfo::V128 => Ok(ValType::V128),
+        PrimitiveTypeInfo::FuncRef => Ok(ValType::FuncRef),
+        PrimitiveTypeInfo::ExternRef => Ok(ValType::ExternRef),
+        PrimitiveTypeInfo::Empty => Err(Error::no_mutations_applicable()),
+    }
+}
+
 pub fn map_block_type(ty: wasmparser::BlockType) -> Result<BlockType> {
     match ty {
         wasmparser::BlockType::Empty => Ok(BlockType::Empty),
@@ -81,3 +97,50 @@ pub fn map_block_type(ty: wasmparser::BlockType) -> Result<BlockType> {
         wasmparser::BlockType::FuncType(f) => Ok(BlockType::FunctionType(f)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{map_primitive_type, PrimitiveTypeInfo, TypeInfo};
+    use crate::{ErrorKind, ModuleInfo};
+    use std::convert::TryFrom;
+    use wasm_encoder::ValType;
+    use wasmparser::{FieldType, StorageType, StructType, TypeDef};
+
+    #[test]
+    fn test_func_info_simd_and_reference_types() {
+        let wasm =
+            wat::parse_str("(module (func (param v128 externref) (result funcref) unreachable))")
+                .unwrap();
+        let info = ModuleInfo::new(&wasm).unwrap();
+        let TypeInfo::Func(ty) = info.get_functype_idx(0);
+        assert_eq!(
+            ty.params,
+            [PrimitiveTypeInfo::V128, PrimitiveTypeInfo::ExternRef]
+        );
+        assert_eq!(ty.returns, [PrimitiveTypeInfo::FuncRef]);
+
+        let params = ty
+            .params
+            .iter()
+            .map(|ty| map_primitive_type(ty).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(params, [ValType::V128, ValType::ExternRef]);
+        assert_eq!(
+            map_primitive_type(&ty.returns[0]).unwrap(),
+            ValType::FuncRef
+        );
+    }
+
+    #[test]
+    fn test_gc_types_are_unsupported() {
+        let ty = TypeDef::Struct(StructType {
+            fields: vec![FieldType {
+                element_type: StorageType::I8,
+                mutable: true,
+            }]
+            .into_boxed_slice(),
+        });
+        let err = TypeInfo::try_from(ty).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Unsupported(_)));
+    }
+}
@@ -22,21 +22,57 @@
 
 pub mod add_function;
 pub mod add_type;
+pub mod block_params;
+pub mod call_import;
 pub mod codemotion;
+pub mod collapse_nops;
 pub mod custom;
+pub mod data_active_to_init;
+pub mod dedupe_types;
+pub mod default_return;
+pub mod drop_unused_function_param;
+pub mod element_active_to_init;
+pub mod eqz_to_eq;
+pub mod extract_subexpression;
+pub mod flip_load_signedness;
+pub mod fold_constant_if;
 pub mod function_body_unreachable;
+pub mod global_to_accessors;
+pub mod guard_divide;
+pub mod if_chain_to_br_table;
+pub mod instrument_entry;
+pub mod local_to_param;
+pub mod merge_blocks;
 pub mod modify_data;
 pub mod modify_init_exprs;
+pub mod narrow_memory64;
+pub mod non_minimal_leb;
+pub mod pack_locals;
 pub mod peephole;
+pub mod prepend_param;
+pub mod prune_name_section;
+pub mod reduce_v128_const;
 pub mod remove_export;
+pub mod remove_instruction;
 pub mod remove_item;
 pub mod rename_export;
+pub mod return_to_br;
+pub mod select_to_if;
+pub mod single_iteration_loop;
 pub mod snip_function;
+pub mod split_table;
 pub mod start;
+pub mod stores_to_fill;
+pub mod swap_commutative_operands;
+pub mod toggle_global_export;
+pub mod unshare_memory;
+pub mod wrap_body_in_block;
+pub mod zero_memarg_offset;
 
 mod translate;
+use self::translate::DefaultTranslator;
 pub use self::translate::Item;
-use self::translate::{DefaultTranslator, Translator};
+pub(crate) use self::translate::Translator;
 
 use std::borrow::Cow;
 
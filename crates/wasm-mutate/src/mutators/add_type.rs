@@ -1,6 +1,6 @@
 //! A mutator to add a new type to a Wasm module.
 
-use super::Mutator;
+use super::{translate, DefaultTranslator, Mutator};
 use crate::Result;
 use rand::Rng;
 use std::iter;
@@ -70,6 +70,7 @@ impl Mutator for AddTypeMutator {
                             .collect::<Result<Vec<_>, _>>()?;
                         types.function(params, results);
                     }
+                    ty => translate::type_def(&mut DefaultTranslator, ty, &mut types)?,
                 }
             }
             // And then add our new type.
@@ -0,0 +1,260 @@
+//! Mutator that moves the first value a `block` pushes into its parameters.
+
+use super::{
+    global_to_accessors::find_or_add_type, translate, DefaultTranslator, Mutator, Translator,
+};
+use crate::module::{PrimitiveTypeInfo, TypeInfo};
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use wasm_encoder::{BlockType, CodeSection, Function, Instruction, Module, SectionId, TypeSection};
+use wasmparser::{FunctionBody, Operator, TypeSectionReader};
+
+/// Rewrites `block T (X ...) end`, where `X` pushes a single value of type `U`
+/// without popping anything, into `X block (param U) T (...) end`.
+///
+/// The block now takes the value produced by `X` as a parameter, which
+/// requires the multi-value proposal and a block type referring to a function
+/// type.
+#[derive(Clone, Copy)]
+pub struct BlockParamsMutator;
+
+/// A `block` whose first instruction can be hoisted in front of it.
+#[derive(Clone)]
+struct Candidate {
+    function: usize,
+    /// The index of the `block` operator.
+    block: usize,
+    param: PrimitiveTypeInfo,
+    results: Vec<PrimitiveTypeInfo>,
+}
+
+/// Returns the type of the `index`th local of the `function`th defined
+/// function.
+fn local_type(
+    config: &WasmMutate,
+    function: usize,
+    body: &FunctionBody,
+    index: u32,
+) -> Result<Option<PrimitiveTypeInfo>> {
+    let info = config.info();
+    let params = match info.get_functype_idx(info.num_imported_functions() + function as u32) {
+        TypeInfo::Func(ty) => &ty.params,
+    };
+    if let Some(param) = params.get(index as usize) {
+        return Ok(Some(param.clone()));
+    }
+    let mut start = params.len() as u32;
+    for local in body.get_locals_reader()? {
+        let (count, ty) = local?;
+        if index - start < count {
+            return Ok(Some(ty.into()));
+        }
+        start += count;
+    }
+    Ok(None)
+}
+
+fn candidates(
+    config: &WasmMutate,
+    function: usize,
+    body: &FunctionBody,
+    ops: &[Operator],
+) -> Result<Vec<Candidate>> {
+    let info = config.info();
+    let mut candidates = Vec::new();
+    for (i, pair) in ops.windows(2).enumerate() {
+        let results = match &pair[0] {
+            Operator::Block { ty } => match ty {
+                wasmparser::BlockType::Empty => vec![],
+                wasmparser::BlockType::Type(ty) => vec![(*ty).into()],
+                wasmparser::BlockType::FuncType(ty) => match &info.types_map[*ty as usize] {
+                    TypeInfo::Func(ty) if ty.params.is_empty() => ty.returns.clone(),
+                    _ => continue,
+                },
+            },
+            _ => continue,
+        };
+        let param = match &pair[1] {
+            Operator::I32Const { .. } => PrimitiveTypeInfo::I32,
+            Operator::I64Const { .. } => PrimitiveTypeInfo::I64,
+            Operator::F32Const { .. } => PrimitiveTypeInfo::F32,
+            Operator::F64Const { .. } => PrimitiveTypeInfo::F64,
+            Operator::GlobalGet { global_index } => {
+                match info.global_types.get(*global_index as usize) {
+                    Some(ty) => ty.clone(),
+                    None => continue,
+                }
+            }
+            Operator::LocalGet { local_index } => {
+                match local_type(config, function, body, *local_index)? {
+                    Some(ty) => ty,
+                    None => continue,
+                }
+            }
+            _ => continue,
+        };
+        candidates.push(Candidate {
+            function,
+            block: i,
+            param,
+            results,
+        });
+    }
+    Ok(candidates)
+}
+
+impl Mutator for BlockParamsMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let mut bodies = Vec::new();
+        let mut all_candidates = Vec::new();
+        for (i, body) in config.info().code_bodies()?.into_iter().enumerate() {
+            config.consume_fuel(1)?;
+            let mut ops = body.get_operators_reader()?;
+            ops.allow_memarg64(true);
+            let ops = ops.into_iter().collect::<wasmparser::Result<Vec<_>>>()?;
+            all_candidates.extend(candidates(config, i, &body, &ops)?);
+            bodies.push((body, ops));
+        }
+        let candidate = match all_candidates.choose(config.rng()) {
+            Some(candidate) => candidate.clone(),
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!(
+            "Moving a {:?} into the parameters of the block at operator {} of function {}",
+            candidate.param,
+            candidate.block,
+            candidate.function
+        );
+
+        let info = config.info();
+        let mut types = TypeSection::new();
+        if let Some(section) = info.get_type_section() {
+            let mut reader = TypeSectionReader::new(section.data, 0)?;
+            for _ in 0..reader.get_count() {
+                translate::type_def(&mut DefaultTranslator, reader.read()?, &mut types)?;
+            }
+        }
+        let ty = find_or_add_type(
+            config,
+            &mut types,
+            &mut Vec::new(),
+            vec![candidate.param],
+            candidate.results,
+        )?;
+
+        let code_section = info.get_code_section();
+        let mut codes = CodeSection::new();
+        for (i, (body, ops)) in bodies.iter().enumerate() {
+            if i != candidate.function {
+                codes.raw(&code_section.data[body.range().start..body.range().end]);
+                continue;
+            }
+            let mut func = Function::new(translate::locals(&mut DefaultTranslator, body)?);
+            for (j, op) in ops.iter().enumerate() {
+                if j == candidate.block {
+                    func.instruction(&DefaultTranslator.translate_op(&ops[j + 1])?);
+                    func.instruction(&Instruction::Block(BlockType::FunctionType(ty)));
+                } else if j != candidate.block + 1 {
+                    func.instruction(&DefaultTranslator.translate_op(op)?);
+                }
+            }
+            codes.function(&func);
+        }
+
+        let module =
+            info.replace_or_insert_sections(&[SectionId::Type, SectionId::Code], |id, module| {
+                match id {
+                    SectionId::Type => module.section(&types),
+                    _ => module.section(&codes),
+                };
+            });
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.reduce
+            && config.multi_value
+            && config.info().code_bodies().map_or(false, |bodies| {
+                bodies.iter().enumerate().any(|(i, body)| {
+                    body.get_operators_reader()
+                        .and_then(|mut ops| {
+                            ops.allow_memarg64(true);
+                            ops.into_iter().collect::<wasmparser::Result<Vec<_>>>()
+                        })
+                        .map_err(Error::from)
+                        .and_then(|ops| candidates(config, i, body, &ops))
+                        .map_or(false, |candidates| !candidates.is_empty())
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockParamsMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_block_params() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    block (result i32)
+                        local.get 0
+                        i32.const 1
+                        i32.add
+                    end
+                )
+            )
+            "#,
+            BlockParamsMutator,
+            r#"
+            (module
+                (type (func (param i32) (result i32)))
+                (func (type 0)
+                    local.get 0
+                    block (type 0)
+                        i32.const 1
+                        i32.add
+                    end
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_block_params_requires_multi_value() {
+        let wasm = wat::parse_str("(module (func block i32.const 1 drop end))").unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.multi_value(false);
+        config.setup(&wasm).unwrap();
+        assert!(!BlockParamsMutator.can_mutate(&config));
+    }
+
+    #[test]
+    fn test_block_params_memory64() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory i64 1)
+                (func (param i64) (result i32)
+                    block (result i32)
+                        local.get 0
+                        i32.load offset=0x100000000
+                    end
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(BlockParamsMutator.can_mutate(&config));
+    }
+}
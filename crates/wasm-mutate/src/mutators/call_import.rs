@@ -0,0 +1,131 @@
+//! Mutator that replaces the body of a function with a call to an import.
+
+use super::Mutator;
+use crate::module::TypeInfo;
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use wasm_encoder::{CodeSection, Function, Instruction, Module};
+
+/// Replaces the body of a defined function with a call to an imported function
+/// of the same signature, forwarding all parameters and returning the
+/// import's results.
+///
+/// This redirects the function's behavior to the host, so it is not
+/// semantics-preserving.
+#[derive(Clone, Copy)]
+pub struct CallImportMutator;
+
+/// Returns every pair of a defined function and an imported function with the
+/// same signature.
+fn candidates(config: &WasmMutate) -> Vec<(u32, u32)> {
+    let info = config.info();
+    let signature = |function: u32| match info.get_functype_idx(function) {
+        TypeInfo::Func(ty) => (&ty.params, &ty.returns),
+    };
+    let mut candidates = Vec::new();
+    for function in info.num_imported_functions()..info.num_functions() {
+        for import in 0..info.num_imported_functions() {
+            if signature(function) == signature(import) {
+                candidates.push((function, import));
+            }
+        }
+    }
+    candidates
+}
+
+impl Mutator for CallImportMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        config.consume_fuel(1)?;
+        let (function, import) = match candidates(config).choose(config.rng()) {
+            Some(candidate) => *candidate,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!(
+            "Replacing the body of function {} with a call to import {}",
+            function,
+            import
+        );
+
+        let num_params = match config.info().get_functype_idx(function) {
+            TypeInfo::Func(ty) => ty.params.len() as u32,
+        };
+        let code_section = config.info().get_code_section();
+        let mut codes = CodeSection::new();
+        for (i, body) in config.info().code_bodies()?.iter().enumerate() {
+            if i as u32 + config.info().num_imported_functions() != function {
+                codes.raw(&code_section.data[body.range().start..body.range().end]);
+                continue;
+            }
+            let mut f = Function::new(vec![]);
+            for param in 0..num_params {
+                f.instruction(&Instruction::LocalGet(param));
+            }
+            f.instruction(&Instruction::Call(import));
+            f.instruction(&Instruction::End);
+            codes.function(&f);
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.reduce && !config.preserve_semantics && !candidates(config).is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CallImportMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_call_import() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (import "env" "f" (func (param i64)))
+                (import "env" "g" (func (param i32 f32) (result i32)))
+                (func (param i32 f32) (result i32) (local i64)
+                    local.get 0
+                    i32.const 1
+                    i32.add
+                )
+            )
+            "#,
+            CallImportMutator,
+            r#"
+            (module
+                (import "env" "f" (func (param i64)))
+                (import "env" "g" (func (param i32 f32) (result i32)))
+                (func (param i32 f32) (result i32)
+                    local.get 0
+                    local.get 1
+                    call 1
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_call_import_requires_matching_signature() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "env" "f" (func (param i64)))
+                (func (param i32))
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!CallImportMutator.can_mutate(&config));
+    }
+}
@@ -0,0 +1,173 @@
+//! Mutator that collapses runs of consecutive `nop` instructions.
+
+use super::Mutator;
+use crate::{Error, Result, WasmMutate};
+
+use rand::{seq::SliceRandom, Rng};
+use std::ops::Range;
+use wasm_encoder::{CodeSection, Module};
+use wasmparser::{FunctionBody, Operator};
+
+/// Replaces a run of two or more consecutive `nop`s in a function body with a
+/// single `nop`, or removes the run entirely.
+///
+/// A `nop` never touches the operand stack, so dropping any number of them
+/// always leaves the body valid and semantically unchanged.
+#[derive(Clone, Copy)]
+pub struct CollapseNopsMutator;
+
+/// Returns the byte ranges, relative to the code section, of every run of at
+/// least two consecutive `nop`s in `body`.
+fn nop_runs(body: &FunctionBody) -> Result<Vec<Range<usize>>> {
+    let mut runs = Vec::new();
+    let mut run_start = None;
+    let mut run_len = 0;
+    let mut reader = body.get_operators_reader()?;
+    reader.allow_memarg64(true);
+    for op in reader.into_iter_with_offsets() {
+        let (op, offset) = op?;
+        if let Operator::Nop = op {
+            if run_start.is_none() {
+                run_start = Some(offset);
+            }
+            run_len += 1;
+            continue;
+        }
+        if let Some(start) = run_start.take() {
+            if run_len >= 2 {
+                runs.push(start..offset);
+            }
+        }
+        run_len = 0;
+    }
+    Ok(runs)
+}
+
+impl Mutator for CollapseNopsMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let code_section = config.info().get_code_section();
+        let bodies = config.info().code_bodies()?;
+        let mut candidates = Vec::new();
+        for (i, body) in bodies.iter().enumerate() {
+            config.consume_fuel(1)?;
+            for run in nop_runs(body)? {
+                candidates.push((i, run));
+            }
+        }
+
+        let (function_to_mutate, run) = match candidates.choose(config.rng()) {
+            Some(candidate) => candidate.clone(),
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        let keep_one = config.rng().gen_bool(0.5);
+        log::trace!(
+            "Collapsing nops at {:?} in function {} (keeping one: {})",
+            run,
+            function_to_mutate,
+            keep_one
+        );
+
+        let mut codes = CodeSection::new();
+        for (i, body) in bodies.iter().enumerate() {
+            let range = body.range();
+            if i != function_to_mutate {
+                codes.raw(&code_section.data[range.start..range.end]);
+                continue;
+            }
+            let mut body = Vec::with_capacity(range.end - range.start);
+            body.extend_from_slice(&code_section.data[range.start..run.start]);
+            if keep_one {
+                body.push(0x01);
+            }
+            body.extend_from_slice(&code_section.data[run.end..range.end]);
+            codes.raw(&body);
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        config.reduce
+            && config.info().code_bodies().map_or(false, |bodies| {
+                bodies
+                    .iter()
+                    .any(|body| nop_runs(body).map_or(false, |runs| !runs.is_empty()))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CollapseNopsMutator;
+    use crate::mutators::Mutator;
+
+    fn match_reduction(original: &str, expected: &str) {
+        crate::WasmMutate::default().reduce(true).match_mutation(
+            original,
+            CollapseNopsMutator,
+            expected,
+        )
+    }
+
+    #[test]
+    fn test_collapse_nops_removes_run() {
+        match_reduction(
+            r#"
+            (module
+                (func (result i32)
+                    nop
+                    nop
+                    nop
+                    i32.const 42
+                )
+            )
+            "#,
+            r#"
+            (module
+                (func (result i32)
+                    i32.const 42
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_collapse_nops_keeps_one() {
+        match_reduction(
+            r#"
+            (module
+                (func
+                    block
+                        nop
+                        nop
+                    end
+                )
+            )
+            "#,
+            r#"
+            (module
+                (func
+                    block
+                        nop
+                    end
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_collapse_nops_requires_run() {
+        let wasm = wat::parse_str("(module (func nop i32.const 0 drop nop))").unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.reduce(true);
+        config.setup(&wasm).unwrap();
+        assert!(!CollapseNopsMutator.can_mutate(&config));
+    }
+}
@@ -2,7 +2,7 @@
 
 use super::Mutator;
 use crate::{Result, WasmMutate};
-use rand::seq::SliceRandom;
+use rand::{seq::SliceRandom, Rng};
 use wasm_encoder::Module;
 
 /// A mutator that removes a custom section.
@@ -52,6 +52,73 @@ impl Mutator for RemoveCustomSection {
     }
 }
 
+/// A mutator that moves a custom section to another position in the module.
+///
+/// Custom sections may appear anywhere in a module, so this only changes how
+/// the module is laid out, keeping all other sections in their original order.
+#[derive(Clone, Copy)]
+pub struct MoveCustomSection;
+
+impl Mutator for MoveCustomSection {
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.reduce
+            && config.info().raw_sections.len() > 1
+            && config
+                .info()
+                .raw_sections
+                .iter()
+                .any(|s| s.id == wasm_encoder::SectionId::Custom as u8)
+    }
+
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>>
+    where
+        Self: Copy,
+    {
+        config.consume_fuel(1)?;
+        let custom_sections = config
+            .info()
+            .raw_sections
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| {
+                if s.id == wasm_encoder::SectionId::Custom as u8 {
+                    Some(i)
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let section_index_to_move = *custom_sections.choose(config.rng()).unwrap();
+        let num_sections = config.info().raw_sections.len();
+        // Pick any position other than the current one among the other
+        // sections.
+        let mut new_index = config.rng().gen_range(0..num_sections - 1);
+        if new_index >= section_index_to_move {
+            new_index += 1;
+        }
+        log::trace!(
+            "Moving custom section {} to position {}",
+            section_index_to_move,
+            new_index
+        );
+
+        let mut order = (0..num_sections)
+            .filter(|i| *i != section_index_to_move)
+            .collect::<Vec<_>>();
+        order.insert(new_index, section_index_to_move);
+
+        let mut module = Module::new();
+        for i in order {
+            module.section(&config.info().raw_sections[i]);
+        }
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -77,4 +144,62 @@ mod tests {
             "#,
         );
     }
+
+    #[test]
+    fn test_move_custom_section() {
+        let wasm = wat::parse_str(
+            r#"
+                (module
+                    (type (func))
+                    (@custom "foo" (after type) "abc")
+                    (func (type 0))
+                )
+            "#,
+        )
+        .unwrap();
+        let section_ids = |wasm: &[u8]| {
+            wasmparser::Parser::new(0)
+                .parse_all(wasm)
+                .filter_map(|payload| match payload.unwrap() {
+                    wasmparser::Payload::CustomSection { .. } => Some(0),
+                    wasmparser::Payload::TypeSection(_) => Some(1),
+                    wasmparser::Payload::FunctionSection(_) => Some(3),
+                    wasmparser::Payload::CodeSectionStart { .. } => Some(10),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(section_ids(&wasm), [1, 0, 3, 10]);
+
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(MoveCustomSection.can_mutate(&config));
+        let mut moved_after_code = false;
+        for _ in 0..100 {
+            let mutation = MoveCustomSection
+                .mutate(&mut config)
+                .unwrap()
+                .next()
+                .unwrap()
+                .unwrap()
+                .finish();
+            crate::validate(&mutation);
+            let ids = section_ids(&mutation);
+            assert_ne!(ids, [1, 0, 3, 10]);
+            assert_eq!(
+                ids.iter().filter(|id| **id != 0).collect::<Vec<_>>(),
+                [&1, &3, &10]
+            );
+            moved_after_code |= ids == [1, 3, 10, 0];
+        }
+        assert!(moved_after_code);
+    }
+
+    #[test]
+    fn test_move_custom_section_requires_custom_section() {
+        let wasm = wat::parse_str("(module (type (func)) (func (type 0)))").unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!MoveCustomSection.can_mutate(&config));
+    }
 }
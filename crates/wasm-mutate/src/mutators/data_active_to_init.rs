@@ -0,0 +1,284 @@
+//! Mutator that turns an active data segment into a passive one which is
+//! initialized by the start function.
+
+use super::translate::{self, InitExprKind};
+use super::{DefaultTranslator, Mutator, Translator};
+use crate::module::TypeInfo;
+use crate::{Error, ModuleInfo, Result, WasmMutate};
+
+use wasm_encoder::{
+    CodeSection, DataCountSection, DataSection, DataSegment, DataSegmentMode, Function,
+    FunctionSection, Instruction, Module, SectionId, StartSection, TypeSection,
+};
+use wasmparser::{DataKind, DataSectionReader, TypeSectionReader};
+
+/// Converts the last active data segment into a passive segment and copies it
+/// into memory with `memory.init` followed by `data.drop` from a new start
+/// function.
+///
+/// The new start function calls the previous start function, if any, after
+/// initializing the segment. Since active segments are initialized in order
+/// before the start function runs, always picking the last active segment
+/// keeps the order of all memory writes the same.
+#[derive(Clone, Copy)]
+pub struct DataActiveToInitMutator;
+
+impl Mutator for DataActiveToInitMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        config.consume_fuel(1)?;
+        let info = config.info();
+
+        let mut reader = DataSectionReader::new(info.get_data_section().data, 0)?;
+        let segments = (0..reader.get_count())
+            .map(|_| reader.read())
+            .collect::<wasmparser::Result<Vec<_>>>()?;
+        let index = match segments
+            .iter()
+            .rposition(|data| matches!(data.kind, DataKind::Active { .. }))
+        {
+            Some(index) => index,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!(
+            "Initializing data segment {} from the start function",
+            index
+        );
+
+        // Build the function which initializes the segment and then runs the
+        // previous start function.
+        let mut func = Function::new(Vec::new());
+        let segment = &segments[index];
+        if let DataKind::Active {
+            memory_index,
+            init_expr,
+        } = &segment.kind
+        {
+            func.instruction(&DefaultTranslator.translate_init_expr(
+                init_expr,
+                &wasmparser::Type::I32,
+                InitExprKind::DataOffset,
+            )?);
+            func.instruction(&Instruction::I32Const(0));
+            func.instruction(&Instruction::I32Const(segment.data.len() as i32));
+            func.instruction(&Instruction::MemoryInit {
+                mem: *memory_index,
+                data: index as u32,
+            });
+        }
+        func.instruction(&Instruction::DataDrop(index as u32));
+
+        let mut data = DataSection::new();
+        for (i, segment) in segments.into_iter().enumerate() {
+            if i == index {
+                data.segment(DataSegment {
+                    mode: DataSegmentMode::Passive,
+                    data: segment.data.iter().copied(),
+                });
+            } else {
+                translate::data(&mut DefaultTranslator, segment, &mut data)?;
+            }
+        }
+
+        let (types, functions, codes, start) = synthesize_start(info, func)?;
+        let data_count = DataCountSection {
+            count: info.num_data(),
+        };
+
+        let module = info.replace_or_insert_sections(
+            &[
+                SectionId::Type,
+                SectionId::Function,
+                SectionId::Start,
+                SectionId::DataCount,
+                SectionId::Code,
+                SectionId::Data,
+            ],
+            |id, module| {
+                match id {
+                    SectionId::Type => module.section(&types),
+                    SectionId::Function => module.section(&functions),
+                    SectionId::Start => module.section(&start),
+                    SectionId::DataCount => module.section(&data_count),
+                    SectionId::Code => module.section(&codes),
+                    _ => module.section(&data),
+                };
+            },
+        );
+
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.reduce
+            && config.bulk_memory
+            && config.info().num_data() > 0
+            && DataSectionReader::new(config.info().get_data_section().data, 0).map_or(
+                false,
+                |reader| {
+                    reader.into_iter().any(|data| {
+                        data.map_or(false, |data| matches!(data.kind, DataKind::Active { .. }))
+                    })
+                },
+            )
+    }
+}
+
+/// Builds the sections of a module with a new start function whose body is
+/// `func` followed by a call to the previous start function, if any.
+///
+/// The new start function is appended to the defined functions and its type
+/// is `[] -> []`, which is reused from the type section when possible.
+pub(super) fn synthesize_start(
+    info: &ModuleInfo,
+    mut func: Function,
+) -> Result<(TypeSection, FunctionSection, CodeSection, StartSection)> {
+    if let Some(start) = info.start_function() {
+        func.instruction(&Instruction::Call(start));
+    }
+    func.instruction(&Instruction::End);
+
+    // The new start function has type `[] -> []`, which the previous start
+    // function already uses if there is one.
+    let mut types = TypeSection::new();
+    if let Some(section) = info.get_type_section() {
+        let mut reader = TypeSectionReader::new(section.data, 0)?;
+        for _ in 0..reader.get_count() {
+            translate::type_def(&mut DefaultTranslator, reader.read()?, &mut types)?;
+        }
+    }
+    let ty = match info.start_function() {
+        Some(start) => info.function_map[start as usize],
+        None => match info.types_map.iter().position(|ty| match ty {
+            TypeInfo::Func(ty) => ty.params.is_empty() && ty.returns.is_empty(),
+        }) {
+            Some(ty) => ty as u32,
+            None => {
+                types.function([], []);
+                info.num_types()
+            }
+        },
+    };
+
+    let mut functions = FunctionSection::new();
+    for ty in &info.function_map[info.num_imported_functions() as usize..] {
+        functions.function(*ty);
+    }
+    functions.function(ty);
+
+    let mut codes = CodeSection::new();
+    if info.code.is_some() {
+        let data = info.get_code_section().data;
+        for body in info.code_bodies()? {
+            codes.raw(&data[body.range().start..body.range().end]);
+        }
+    }
+    codes.function(&func);
+
+    let start = StartSection {
+        function_index: info.num_functions(),
+    };
+    Ok((types, functions, codes, start))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DataActiveToInitMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_data_active_to_init() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (memory 1)
+                (data (i32.const 8) "abc")
+                (data (i32.const 16) "de")
+            )
+            "#,
+            DataActiveToInitMutator,
+            r#"
+            (module
+                (type (func))
+                (memory 1)
+                (func
+                    i32.const 16
+                    i32.const 0
+                    i32.const 2
+                    memory.init 1
+                    data.drop 1
+                )
+                (start 0)
+                (data (i32.const 8) "abc")
+                (data "de")
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_data_active_to_init_requires_active_segment() {
+        let wasm = wat::parse_str(r#"(module (memory 1) (data "abc"))"#).unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!DataActiveToInitMutator.can_mutate(&config));
+    }
+
+    #[test]
+    fn test_data_active_to_init_requires_bulk_memory() {
+        let wasm = wat::parse_str(r#"(module (memory 1) (data (i32.const 0) "abc"))"#).unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(DataActiveToInitMutator.can_mutate(&config));
+
+        config.bulk_memory(false);
+        assert!(!DataActiveToInitMutator.can_mutate(&config));
+    }
+
+    #[test]
+    fn test_data_active_to_init_calls_previous_start() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (type (func (param i32)))
+                (type (func))
+                (import "env" "f" (func (type 0)))
+                (memory 1)
+                (func (type 1)
+                    i32.const 1
+                    call 0
+                )
+                (start 1)
+                (data (i32.const 8) "abc")
+                (data "de")
+            )
+            "#,
+            DataActiveToInitMutator,
+            r#"
+            (module
+                (type (func (param i32)))
+                (type (func))
+                (import "env" "f" (func (type 0)))
+                (memory 1)
+                (func (type 1)
+                    i32.const 1
+                    call 0
+                )
+                (func (type 1)
+                    i32.const 8
+                    i32.const 0
+                    i32.const 3
+                    memory.init 0
+                    data.drop 0
+                    call 1
+                )
+                (start 2)
+                (data "abc")
+                (data "de")
+            )
+            "#,
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Mutator that merges two identical types.
+
+use super::{remove_item::RemoveItem, Item, Mutator};
+use crate::module::TypeInfo;
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use wasm_encoder::Module;
+
+/// Removes a type which is structurally identical to an earlier one,
+/// redirecting all references to the earlier type and renumbering the types
+/// which follow it.
+#[derive(Clone, Copy)]
+pub struct DedupeTypesMutator;
+
+/// Returns every pair of type indices `(a, b)` where `a < b` and both types
+/// are identical.
+fn duplicate_types(config: &WasmMutate) -> Vec<(u32, u32)> {
+    let types = &config.info().types_map;
+    let mut duplicates = Vec::new();
+    for (b, TypeInfo::Func(ty_b)) in types.iter().enumerate() {
+        let first = types.iter().position(|TypeInfo::Func(ty_a)| {
+            ty_a.params == ty_b.params && ty_a.returns == ty_b.returns
+        });
+        if let Some(a) = first.filter(|a| *a < b) {
+            duplicates.push((a as u32, b as u32));
+        }
+    }
+    duplicates
+}
+
+impl Mutator for DedupeTypesMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        config.consume_fuel(1)?;
+        let (survivor, duplicate) = match duplicate_types(config).choose(config.rng()) {
+            Some(pair) => *pair,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!("Merging type {} into type {}", duplicate, survivor);
+
+        let module = RemoveItem::new(Item::Type, duplicate, Some(survivor)).remove(config)?;
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        config.reduce && !duplicate_types(config).is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DedupeTypesMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_dedupe_types() {
+        crate::WasmMutate::default().reduce(true).match_mutation(
+            r#"
+            (module
+                (type (func (param i32)))
+                (type (func (result i64)))
+                (type (func (param i32)))
+                (type (func))
+                (import "env" "f" (func (type 2)))
+                (table 1 funcref)
+                (func (type 3)
+                    i32.const 1
+                    i32.const 0
+                    call_indirect (type 2)
+                    block (type 3)
+                    end
+                )
+            )
+            "#,
+            DedupeTypesMutator,
+            r#"
+            (module
+                (type (func (param i32)))
+                (type (func (result i64)))
+                (type (func))
+                (import "env" "f" (func (type 0)))
+                (table 1 funcref)
+                (func (type 2)
+                    i32.const 1
+                    i32.const 0
+                    call_indirect (type 0)
+                    block (type 2)
+                    end
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_dedupe_types_requires_duplicates() {
+        let wasm = wat::parse_str("(module (type (func)) (type (func (param i32))))").unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.reduce(true);
+        config.setup(&wasm).unwrap();
+        assert!(!DedupeTypesMutator.can_mutate(&config));
+    }
+}
@@ -0,0 +1,209 @@
+//! Mutator that replaces the result computed by a function with a default
+//! constant.
+
+use super::swap_commutative_operands::subexpression_start;
+use super::{translate, DefaultTranslator, Mutator, Translator};
+use crate::module::{PrimitiveTypeInfo, TypeInfo};
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use wasm_encoder::{CodeSection, Function, Instruction, Module, ValType};
+use wasmparser::{FunctionBody, Operator};
+
+/// Replaces the expression computing the result of a single-result function
+/// with a default constant of the result type, such as `i32.const 0`.
+///
+/// Only a side-effect free expression at the very end of the function body is
+/// replaced, so everything else the function does is kept.
+#[derive(Clone, Copy)]
+pub struct DefaultReturnMutator;
+
+fn default_value(ty: &PrimitiveTypeInfo) -> Option<Instruction<'static>> {
+    Some(match ty {
+        PrimitiveTypeInfo::I32 => Instruction::I32Const(0),
+        PrimitiveTypeInfo::I64 => Instruction::I64Const(0),
+        PrimitiveTypeInfo::F32 => Instruction::F32Const(0.0),
+        PrimitiveTypeInfo::F64 => Instruction::F64Const(0.0),
+        PrimitiveTypeInfo::V128 => Instruction::V128Const(0),
+        PrimitiveTypeInfo::FuncRef => Instruction::RefNull(ValType::FuncRef),
+        PrimitiveTypeInfo::ExternRef => Instruction::RefNull(ValType::ExternRef),
+        PrimitiveTypeInfo::Empty => return None,
+    })
+}
+
+/// Returns the index of the first operator of the expression computing the
+/// result at the end of `ops`, unless it's already a lone default value.
+fn result_expression_start(ops: &[(Operator, usize)]) -> Option<usize> {
+    // The last operator is always the `end` of the function.
+    let last = ops.len().checked_sub(2)?;
+    let start = subexpression_start(ops, last)?;
+    let is_default = start == last
+        && match &ops[last].0 {
+            Operator::I32Const { value } => *value == 0,
+            Operator::I64Const { value } => *value == 0,
+            Operator::F32Const { value } => value.bits() == 0,
+            Operator::F64Const { value } => value.bits() == 0,
+            Operator::V128Const { value } => value.i128() == 0,
+            Operator::RefNull { .. } => true,
+            _ => false,
+        };
+    if is_default {
+        None
+    } else {
+        Some(start)
+    }
+}
+
+fn replace_result(
+    body: &FunctionBody,
+    ops: &[(Operator, usize)],
+    start: usize,
+    value: &Instruction,
+) -> Result<Function> {
+    let mut func = Function::new(translate::locals(&mut DefaultTranslator, body)?);
+    for (op, _) in &ops[..start] {
+        func.instruction(&DefaultTranslator.translate_op(op)?);
+    }
+    func.instruction(value);
+    func.instruction(&Instruction::End);
+    Ok(func)
+}
+
+impl Mutator for DefaultReturnMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let code_section = config.info().get_code_section();
+        let bodies = config.info().code_bodies()?;
+        let mut candidates = Vec::new();
+        for (i, body) in bodies.iter().enumerate() {
+            config.consume_fuel(1)?;
+            let function = config.info().num_imported_functions() + i as u32;
+            let value = match config.info().get_functype_idx(function) {
+                TypeInfo::Func(ty) if ty.returns.len() == 1 => default_value(&ty.returns[0]),
+                _ => None,
+            };
+            let value = match value {
+                Some(value) => value,
+                None => continue,
+            };
+            let mut reader = body.get_operators_reader()?;
+            reader.allow_memarg64(true);
+            let ops = reader
+                .into_iter_with_offsets()
+                .collect::<wasmparser::Result<Vec<_>>>()?;
+            if let Some(start) = result_expression_start(&ops) {
+                candidates.push((i, ops, start, value));
+            }
+        }
+
+        let (function_to_mutate, ops, start, value) = match candidates.choose(config.rng()) {
+            Some(candidate) => candidate,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!(
+            "Replacing the result of function {} starting at operator {}",
+            function_to_mutate,
+            start
+        );
+
+        let mut codes = CodeSection::new();
+        for (i, body) in bodies.iter().enumerate() {
+            if i == *function_to_mutate {
+                codes.function(&replace_result(body, ops, *start, value)?);
+            } else {
+                codes.raw(&code_section.data[body.range().start..body.range().end]);
+            }
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.preserve_semantics && config.reduce && config.info().has_nonempty_code()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DefaultReturnMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_default_return() {
+        crate::WasmMutate::default().reduce(true).match_mutation(
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.const 1
+                    i32.add
+                )
+            )
+            "#,
+            DefaultReturnMutator,
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    i32.const 0
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_default_return_keeps_side_effects() {
+        crate::WasmMutate::default().reduce(true).match_mutation(
+            r#"
+            (module
+                (global (mut f64) (f64.const 0))
+                (func (param f64) (result f64)
+                    local.get 0
+                    global.set 0
+                    global.get 0
+                )
+            )
+            "#,
+            DefaultReturnMutator,
+            r#"
+            (module
+                (global (mut f64) (f64.const 0))
+                (func (param f64) (result f64)
+                    local.get 0
+                    global.set 0
+                    f64.const 0
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_default_return_skips_default_results() {
+        for wat in [
+            r#"(module (func (result f32) f32.const 0))"#,
+            r#"(module (func (result f64) f64.const 0))"#,
+            r#"(module (func (result v128) v128.const i64x2 0 0))"#,
+            r#"(module (func (result funcref) ref.null func))"#,
+        ] {
+            let wasm = wat::parse_str(wat).unwrap();
+            let mut config = crate::WasmMutate::default();
+            config.reduce(true);
+            config.setup(&wasm).unwrap();
+            assert!(DefaultReturnMutator.can_mutate(&config));
+            assert!(DefaultReturnMutator.mutate(&mut config).is_err());
+        }
+    }
+
+    #[test]
+    fn test_default_return_requires_reduce() {
+        let wasm = wat::parse_str(r#"(module (func (result i32) i32.const 1))"#).unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!DefaultReturnMutator.can_mutate(&config));
+    }
+}
@@ -0,0 +1,225 @@
+//! Mutator that removes the last parameter of a function which is never used.
+
+use super::{
+    global_to_accessors::find_or_add_type, prepend_param::indirectly_referenced_functions,
+    translate, DefaultTranslator, Mutator, Translator,
+};
+use crate::module::TypeInfo;
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use std::collections::HashSet;
+use wasm_encoder::{
+    CodeSection, Function, FunctionSection, Instruction, Module, SectionId, TypeSection,
+};
+use wasmparser::{FunctionBody, Operator, TypeSectionReader};
+
+/// Removes the last parameter of a function which is neither called nor
+/// otherwise referenced, as long as its body never accesses that parameter.
+///
+/// With no call sites to fix up, only the function's type and the indices of
+/// the locals following the parameter change.
+#[derive(Clone, Copy)]
+pub struct DropUnusedFunctionParamMutator;
+
+/// Returns whether `body` accesses the local at `index`.
+fn uses_local(body: &FunctionBody, index: u32) -> Result<bool> {
+    let mut reader = body.get_operators_reader()?;
+    reader.allow_memarg64(true);
+    for op in reader {
+        match op? {
+            Operator::LocalGet { local_index }
+            | Operator::LocalSet { local_index }
+            | Operator::LocalTee { local_index }
+                if local_index == index =>
+            {
+                return Ok(true)
+            }
+            _ => {}
+        }
+    }
+    Ok(false)
+}
+
+/// Returns the functions which are called or otherwise referenced.
+pub(super) fn referenced_functions(config: &WasmMutate) -> Result<HashSet<u32>> {
+    let mut referenced = indirectly_referenced_functions(config)?;
+    for body in config.info().code_bodies()? {
+        let mut reader = body.get_operators_reader()?;
+        reader.allow_memarg64(true);
+        for op in reader {
+            match op? {
+                Operator::Call { function_index } | Operator::ReturnCall { function_index } => {
+                    referenced.insert(function_index);
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(referenced)
+}
+
+/// Returns the defined functions whose last parameter can be removed.
+fn candidates(config: &WasmMutate) -> Result<Vec<u32>> {
+    let info = config.info();
+    let referenced = referenced_functions(config)?;
+    let bodies = info.code_bodies()?;
+    let mut candidates = Vec::new();
+    for (i, body) in bodies.iter().enumerate() {
+        let function = info.num_imported_functions() + i as u32;
+        let num_params = match info.get_functype_idx(function) {
+            TypeInfo::Func(ty) => ty.params.len() as u32,
+        };
+        if num_params > 0 && !referenced.contains(&function) && !uses_local(body, num_params - 1)? {
+            candidates.push(function);
+        }
+    }
+    Ok(candidates)
+}
+
+impl Mutator for DropUnusedFunctionParamMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        config.consume_fuel(1)?;
+        let function = match candidates(config)?.choose(config.rng()) {
+            Some(function) => *function,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!("Removing the last parameter of function {}", function);
+
+        let info = config.info();
+        let mut ty = match info.get_functype_idx(function) {
+            TypeInfo::Func(ty) => ty.clone(),
+        };
+        let param = ty.params.len() as u32 - 1;
+        ty.params.pop();
+
+        let mut types = TypeSection::new();
+        if let Some(section) = info.get_type_section() {
+            let mut reader = TypeSectionReader::new(section.data, 0)?;
+            for _ in 0..reader.get_count() {
+                translate::type_def(&mut DefaultTranslator, reader.read()?, &mut types)?;
+            }
+        }
+        let new_ty = find_or_add_type(config, &mut types, &mut Vec::new(), ty.params, ty.returns)?;
+
+        let mut functions = FunctionSection::new();
+        let first_defined = info.num_imported_functions();
+        for (i, ty) in info
+            .function_map
+            .iter()
+            .enumerate()
+            .skip(first_defined as usize)
+        {
+            functions.function(if i as u32 == function { new_ty } else { *ty });
+        }
+
+        let code_section = info.get_code_section();
+        let mut codes = CodeSection::new();
+        for (i, body) in info.code_bodies()?.iter().enumerate() {
+            if first_defined + i as u32 != function {
+                codes.raw(&code_section.data[body.range().start..body.range().end]);
+                continue;
+            }
+            let shift = |index: u32| if index > param { index - 1 } else { index };
+            let mut func = Function::new(translate::locals(&mut DefaultTranslator, body)?);
+            let mut reader = body.get_operators_reader()?;
+            reader.allow_memarg64(true);
+            for op in reader {
+                match op? {
+                    Operator::LocalGet { local_index } => {
+                        func.instruction(&Instruction::LocalGet(shift(local_index)))
+                    }
+                    Operator::LocalSet { local_index } => {
+                        func.instruction(&Instruction::LocalSet(shift(local_index)))
+                    }
+                    Operator::LocalTee { local_index } => {
+                        func.instruction(&Instruction::LocalTee(shift(local_index)))
+                    }
+                    op => func.instruction(&DefaultTranslator.translate_op(&op)?),
+                };
+            }
+            codes.function(&func);
+        }
+
+        let module = info.replace_or_insert_sections(
+            &[SectionId::Type, SectionId::Function, SectionId::Code],
+            |id, module| {
+                match id {
+                    SectionId::Type => module.section(&types),
+                    SectionId::Function => module.section(&functions),
+                    _ => module.section(&codes),
+                };
+            },
+        );
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        config.reduce && candidates(config).map_or(false, |candidates| !candidates.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DropUnusedFunctionParamMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_drop_unused_function_param() {
+        crate::WasmMutate::default().reduce(true).match_mutation(
+            r#"
+            (module
+                (type (func (param i32) (result i32)))
+                (func (type 0)
+                    i32.const 1
+                )
+                (func (param i32 f64) (result i32)
+                    (local i64)
+                    i64.const 2
+                    local.set 2
+                    local.get 0
+                )
+            )
+            "#,
+            DropUnusedFunctionParamMutator,
+            r#"
+            (module
+                (type (func (param i32) (result i32)))
+                (type (func (param i32 f64) (result i32)))
+                (func (type 0)
+                    i32.const 1
+                )
+                (func (type 0)
+                    (local i64)
+                    i64.const 2
+                    local.set 1
+                    local.get 0
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_drop_unused_function_param_requires_uncalled() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (func (param i32))
+                (func
+                    i32.const 0
+                    call 0
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.reduce(true);
+        config.setup(&wasm).unwrap();
+        assert!(!DropUnusedFunctionParamMutator.can_mutate(&config));
+    }
+}
@@ -0,0 +1,219 @@
+//! Mutator that turns an active element segment into a passive one which is
+//! initialized by the start function.
+
+use super::data_active_to_init::synthesize_start;
+use super::translate::{self, InitExprKind};
+use super::{DefaultTranslator, Mutator, Translator};
+use crate::{Error, Result, WasmMutate};
+
+use wasm_encoder::{ElementSection, Function, Instruction, Module, SectionId};
+use wasmparser::{ElementKind, ElementSectionReader};
+
+/// Converts the last active element segment into a passive segment and copies
+/// it into its table with `table.init` followed by `elem.drop` from a new start
+/// function.
+///
+/// The new start function calls the previous start function, if any, after
+/// initializing the segment. Always picking the last active element segment
+/// keeps the order of all table writes the same, but the segment is now
+/// initialized after all active data segments. If any of them are out of
+/// bounds, the trap happens at a different point, so this doesn't preserve
+/// semantics.
+#[derive(Clone, Copy)]
+pub struct ElementActiveToInitMutator;
+
+impl Mutator for ElementActiveToInitMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        config.consume_fuel(1)?;
+        let info = config.info();
+
+        let section = match info.elements {
+            Some(section) => section,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        let mut reader = ElementSectionReader::new(info.raw_sections[section].data, 0)?;
+        let segments = (0..reader.get_count())
+            .map(|_| reader.read())
+            .collect::<wasmparser::Result<Vec<_>>>()?;
+        let index = match segments
+            .iter()
+            .rposition(|elem| matches!(elem.kind, ElementKind::Active { .. }))
+        {
+            Some(index) => index,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!(
+            "Initializing element segment {} from the start function",
+            index
+        );
+
+        // Build the function which initializes the segment and then runs the
+        // previous start function.
+        let mut func = Function::new(Vec::new());
+        let segment = &segments[index];
+        if let ElementKind::Active {
+            table_index,
+            init_expr,
+        } = &segment.kind
+        {
+            let count = segment.items.get_items_reader()?.get_count();
+            func.instruction(&DefaultTranslator.translate_init_expr(
+                init_expr,
+                &wasmparser::Type::I32,
+                InitExprKind::ElementOffset,
+            )?);
+            func.instruction(&Instruction::I32Const(0));
+            func.instruction(&Instruction::I32Const(count as i32));
+            func.instruction(&Instruction::TableInit {
+                segment: index as u32,
+                table: *table_index,
+            });
+        }
+        func.instruction(&Instruction::ElemDrop {
+            segment: index as u32,
+        });
+
+        let mut elements = ElementSection::new();
+        for (i, segment) in segments.into_iter().enumerate() {
+            let segment = if i == index {
+                wasmparser::Element {
+                    kind: ElementKind::Passive,
+                    ..segment
+                }
+            } else {
+                segment
+            };
+            translate::element(&mut DefaultTranslator, segment, &mut elements)?;
+        }
+
+        let (types, functions, codes, start) = synthesize_start(info, func)?;
+
+        let module = info.replace_or_insert_sections(
+            &[
+                SectionId::Type,
+                SectionId::Function,
+                SectionId::Start,
+                SectionId::Element,
+                SectionId::Code,
+            ],
+            |id, module| {
+                match id {
+                    SectionId::Type => module.section(&types),
+                    SectionId::Function => module.section(&functions),
+                    SectionId::Start => module.section(&start),
+                    SectionId::Element => module.section(&elements),
+                    _ => module.section(&codes),
+                };
+            },
+        );
+
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.reduce
+            && !config.preserve_semantics
+            && config.bulk_memory
+            && config.info().elements.map_or(false, |section| {
+                ElementSectionReader::new(config.info().raw_sections[section].data, 0).map_or(
+                    false,
+                    |reader| {
+                        reader.into_iter().any(|elem| {
+                            elem.map_or(false, |elem| {
+                                matches!(elem.kind, ElementKind::Active { .. })
+                            })
+                        })
+                    },
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ElementActiveToInitMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_element_active_to_init() {
+        let original = r#"
+            (module
+                (type (func))
+                (table 4 funcref)
+                (func (type 0))
+                (elem (i32.const 0) func 0)
+                (elem (i32.const 2) func 0 0)
+            )
+        "#;
+        crate::mutators::match_mutation(
+            original,
+            ElementActiveToInitMutator,
+            r#"
+            (module
+                (type (func))
+                (table 4 funcref)
+                (func (type 0))
+                (func (type 0)
+                    i32.const 2
+                    i32.const 0
+                    i32.const 2
+                    table.init 1
+                    elem.drop 1
+                )
+                (start 1)
+                (elem (i32.const 0) func 0)
+                (elem func 0 0)
+            )
+            "#,
+        );
+
+        let wasm = wat::parse_str(original).unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        let mutation = ElementActiveToInitMutator
+            .mutate(&mut config)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .finish();
+        wasmparser::Validator::new()
+            .validate_all(&mutation)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_element_active_to_init_requires_active_segment() {
+        let wasm = wat::parse_str(r#"(module (func) (elem func 0))"#).unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!ElementActiveToInitMutator.can_mutate(&config));
+    }
+
+    #[test]
+    fn test_element_active_to_init_requires_bulk_memory() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (table 1 funcref)
+                (func)
+                (elem (i32.const 0) func 0)
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(ElementActiveToInitMutator.can_mutate(&config));
+
+        config.bulk_memory(false);
+        assert!(!ElementActiveToInitMutator.can_mutate(&config));
+
+        config.bulk_memory(true);
+        config.preserve_semantics(true);
+        assert!(!ElementActiveToInitMutator.can_mutate(&config));
+    }
+}
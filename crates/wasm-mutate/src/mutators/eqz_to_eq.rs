@@ -0,0 +1,122 @@
+//! Mutator that rewrites `i32.eqz` into a comparison with zero.
+
+use super::Mutator;
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use wasm_encoder::{CodeSection, Module};
+use wasmparser::{FunctionBody, Operator};
+
+/// Replaces an `i32.eqz` with the equivalent `i32.const 0; i32.eq`.
+#[derive(Clone, Copy)]
+pub struct EqzToEqMutator;
+
+/// Returns the offsets, relative to the code section, of every `i32.eqz` in
+/// `body`.
+fn eqz_offsets(body: &FunctionBody) -> Result<Vec<usize>> {
+    let mut offsets = Vec::new();
+    let mut reader = body.get_operators_reader()?;
+    reader.allow_memarg64(true);
+    for op in reader.into_iter_with_offsets() {
+        if let (Operator::I32Eqz, offset) = op? {
+            offsets.push(offset);
+        }
+    }
+    Ok(offsets)
+}
+
+impl Mutator for EqzToEqMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let code_section = config.info().get_code_section();
+        let bodies = config.info().code_bodies()?;
+        let mut candidates = Vec::new();
+        for (i, body) in bodies.iter().enumerate() {
+            config.consume_fuel(1)?;
+            for offset in eqz_offsets(body)? {
+                candidates.push((i, offset));
+            }
+        }
+
+        let (function_to_mutate, offset) = match candidates.choose(config.rng()) {
+            Some(candidate) => *candidate,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!(
+            "Rewriting the i32.eqz at {} in function {}",
+            offset,
+            function_to_mutate
+        );
+
+        let data = code_section.data;
+        let mut codes = CodeSection::new();
+        for (i, body) in bodies.iter().enumerate() {
+            let range = body.range();
+            if i != function_to_mutate {
+                codes.raw(&data[range.start..range.end]);
+                continue;
+            }
+            let mut body = Vec::with_capacity(range.end - range.start + 2);
+            body.extend_from_slice(&data[range.start..offset]);
+            // i32.const 0; i32.eq
+            body.extend_from_slice(&[0x41, 0x00, 0x46]);
+            body.extend_from_slice(&data[offset + 1..range.end]);
+            codes.raw(&body);
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.reduce
+            && config.info().code_bodies().map_or(false, |bodies| {
+                bodies
+                    .iter()
+                    .any(|body| eqz_offsets(body).map_or(false, |offsets| !offsets.is_empty()))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EqzToEqMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_eqz_to_eq() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    i32.eqz
+                )
+            )
+            "#,
+            EqzToEqMutator,
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.const 0
+                    i32.eq
+                    i32.eqz
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_eqz_to_eq_requires_eqz() {
+        let wasm = wat::parse_str("(module (func (result i32) i64.const 0 i64.eqz))").unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!EqzToEqMutator.can_mutate(&config));
+    }
+}
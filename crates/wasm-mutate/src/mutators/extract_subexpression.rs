@@ -0,0 +1,279 @@
+//! Mutator that extracts a repeated subexpression into a local.
+
+use super::{
+    select_to_if::{local_types, value_type},
+    swap_commutative_operands::{pure_arity, subexpression_start},
+    translate, DefaultTranslator, Mutator, Translator,
+};
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use std::collections::HashSet;
+use wasm_encoder::{CodeSection, Function, Instruction, Module, ValType};
+use wasmparser::Operator;
+
+/// Computes a pure subexpression which appears more than once in a function
+/// body only once, stashing its value in a fresh local with `local.tee`, and
+/// replaces the later repeats with a `local.get` of that local.
+///
+/// Only subexpressions built from constants, `local.get` and side-effect free,
+/// non-trapping operators are considered. A repeat is only replaced when the
+/// first occurrence is guaranteed to have been evaluated before it with the
+/// same inputs: it has to follow in the same or a nested control frame, with
+/// no `loop` nor write to any of the locals read in between.
+#[derive(Clone, Copy)]
+pub struct ExtractSubexpressionMutator;
+
+/// A subexpression of a function body along with its later repeats, each
+/// given as the indices of its first and last operator.
+struct Repeated {
+    first: (usize, usize),
+    repeats: Vec<(usize, usize)>,
+    ty: ValType,
+}
+
+/// Returns every subexpression of `ops` which is repeated later on in a way
+/// which allows the repeats to reuse its value.
+///
+/// `data` is the code section the operator offsets point into.
+fn repeated_subexpressions(ops: &[(Operator, usize)], data: &[u8]) -> Vec<Repeated> {
+    let bytes = |start: usize, end: usize| &data[ops[start].1..ops[end + 1].1];
+
+    // Returns the pure subexpression computed by a non-constant operator
+    // ending at `end`, if any.
+    let subexpression = |end: usize| {
+        if end + 1 >= ops.len() || pure_arity(&ops[end].0)? == 0 {
+            return None;
+        }
+        let start = subexpression_start(ops, end)?;
+        // Globals may be set by any call in between, so leave them be.
+        if ops[start..=end]
+            .iter()
+            .any(|(op, _)| matches!(op, Operator::GlobalGet { .. }))
+        {
+            return None;
+        }
+        Some(start)
+    };
+
+    let mut candidates = Vec::new();
+    for end in 0..ops.len() {
+        let start = match subexpression(end) {
+            Some(start) => start,
+            None => continue,
+        };
+        let ty = match value_type(&ops[end].0, &[], &[]) {
+            Some(ty) => ty,
+            None => continue,
+        };
+        let reads = ops[start..=end]
+            .iter()
+            .filter_map(|(op, _)| match op {
+                Operator::LocalGet { local_index } => Some(*local_index),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+
+        let mut repeats = Vec::new();
+        let mut depth = 0u32;
+        let mut next = end + 1;
+        for (i, (op, _)) in ops.iter().enumerate().skip(end + 1) {
+            match op {
+                Operator::Block { .. } | Operator::If { .. } | Operator::Try { .. } => depth += 1,
+                Operator::Else | Operator::Catch { .. } | Operator::CatchAll if depth == 0 => break,
+                Operator::End | Operator::Delegate { .. } => match depth.checked_sub(1) {
+                    Some(d) => depth = d,
+                    None => break,
+                },
+                Operator::Loop { .. } => break,
+                Operator::LocalSet { local_index } | Operator::LocalTee { local_index }
+                    if reads.contains(local_index) =>
+                {
+                    break
+                }
+                _ => {
+                    if let Some(repeat) = subexpression(i) {
+                        if repeat >= next && bytes(repeat, i) == bytes(start, end) {
+                            repeats.push((repeat, i));
+                            next = i + 1;
+                        }
+                    }
+                }
+            }
+        }
+        if !repeats.is_empty() {
+            candidates.push(Repeated {
+                first: (start, end),
+                repeats,
+                ty,
+            });
+        }
+    }
+    candidates
+}
+
+impl Mutator for ExtractSubexpressionMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let first_defined = config.info().num_imported_functions();
+        let code_section = config.info().get_code_section();
+        let bodies = config.info().code_bodies()?;
+        let mut candidates = Vec::new();
+        for (i, body) in bodies.iter().enumerate() {
+            config.consume_fuel(1)?;
+            let mut reader = body.get_operators_reader()?;
+            reader.allow_memarg64(true);
+            let ops = reader
+                .into_iter_with_offsets()
+                .collect::<wasmparser::Result<Vec<_>>>()?;
+            for repeated in repeated_subexpressions(&ops, code_section.data) {
+                candidates.push((i, repeated));
+            }
+        }
+
+        let (function_to_mutate, repeated) = match candidates.choose(config.rng()) {
+            Some(candidate) => candidate,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!(
+            "Extracting the subexpression at operators {:?} of function {} repeated {} times",
+            repeated.first,
+            function_to_mutate,
+            repeated.repeats.len()
+        );
+
+        let mut codes = CodeSection::new();
+        for (i, body) in bodies.iter().enumerate() {
+            if i != *function_to_mutate {
+                codes.raw(&code_section.data[body.range().start..body.range().end]);
+                continue;
+            }
+            let tmp = local_types(config, first_defined + i as u32, body)?.len() as u32;
+            let mut locals = translate::locals(&mut DefaultTranslator, body)?;
+            locals.push((1, repeated.ty));
+
+            let mut func = Function::new(locals);
+            let mut reader = body.get_operators_reader()?;
+            reader.allow_memarg64(true);
+            let mut repeats = repeated.repeats.iter().peekable();
+            for (j, op) in reader.into_iter().enumerate() {
+                let op = op?;
+                if let Some((start, end)) = repeats.peek() {
+                    if (*start..=*end).contains(&j) {
+                        if j == *end {
+                            func.instruction(&Instruction::LocalGet(tmp));
+                            repeats.next();
+                        }
+                        continue;
+                    }
+                }
+                func.instruction(&DefaultTranslator.translate_op(&op)?);
+                if j == repeated.first.1 {
+                    func.instruction(&Instruction::LocalTee(tmp));
+                }
+            }
+            codes.function(&func);
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        if config.reduce {
+            return false;
+        }
+        let data = config.info().get_code_section().data;
+        config.info().code_bodies().map_or(false, |bodies| {
+            bodies.iter().any(|body| {
+                body.get_operators_reader().map_or(false, |mut reader| {
+                    reader.allow_memarg64(true);
+                    reader
+                        .into_iter_with_offsets()
+                        .collect::<wasmparser::Result<Vec<_>>>()
+                        .map_or(false, |ops| !repeated_subexpressions(&ops, data).is_empty())
+                })
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExtractSubexpressionMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_extract_subexpression() {
+        let original = r#"
+            (module
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.const 4
+                    i32.mul
+                    local.get 0
+                    i32.const 4
+                    i32.mul
+                    i32.add
+                )
+            )
+        "#;
+        crate::mutators::match_mutation(
+            original,
+            ExtractSubexpressionMutator,
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    (local i32)
+                    local.get 0
+                    i32.const 4
+                    i32.mul
+                    local.tee 1
+                    local.get 1
+                    i32.add
+                )
+            )
+            "#,
+        );
+
+        let wasm = wat::parse_str(original).unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        let mutation = ExtractSubexpressionMutator
+            .mutate(&mut config)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .finish();
+        wasmparser::Validator::new()
+            .validate_all(&mutation)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_extract_subexpression_requires_unchanged_inputs() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.const 4
+                    i32.mul
+                    local.set 0
+                    local.get 0
+                    i32.const 4
+                    i32.mul
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!ExtractSubexpressionMutator.can_mutate(&config));
+    }
+}
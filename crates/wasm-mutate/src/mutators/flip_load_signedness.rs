@@ -0,0 +1,197 @@
+//! Mutator that flips the signedness of a sub-word load.
+
+use super::Mutator;
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use wasm_encoder::{CodeSection, Module};
+use wasmparser::{FunctionBody, Operator};
+
+/// Turns a sign-extending sub-word load such as `i32.load8_s` into its
+/// zero-extending counterpart `i32.load8_u`, or the other way around.
+///
+/// This changes the value loaded whenever the high bit of the loaded bytes is
+/// set, so it only runs when semantics don't have to be preserved.
+#[derive(Clone, Copy)]
+pub struct FlipLoadSignednessMutator;
+
+fn is_sub_word_load(op: &Operator) -> bool {
+    use Operator::*;
+    matches!(
+        op,
+        I32Load8S { .. }
+            | I32Load8U { .. }
+            | I32Load16S { .. }
+            | I32Load16U { .. }
+            | I64Load8S { .. }
+            | I64Load8U { .. }
+            | I64Load16S { .. }
+            | I64Load16U { .. }
+            | I64Load32S { .. }
+            | I64Load32U { .. }
+    )
+}
+
+/// Returns the offset of every sub-word load in `body`.
+fn sub_word_loads(body: &FunctionBody) -> Result<Vec<usize>> {
+    let mut reader = body.get_operators_reader()?;
+    reader.allow_memarg64(true);
+    let mut loads = Vec::new();
+    for op in reader.into_iter_with_offsets() {
+        let (op, offset) = op?;
+        if is_sub_word_load(&op) {
+            loads.push(offset);
+        }
+    }
+    Ok(loads)
+}
+
+impl Mutator for FlipLoadSignednessMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let code_section = config.info().get_code_section();
+        let bodies = config.info().code_bodies()?;
+        let mut candidates = Vec::new();
+        for (i, body) in bodies.iter().enumerate() {
+            config.consume_fuel(1)?;
+            for offset in sub_word_loads(body)? {
+                candidates.push((i, offset));
+            }
+        }
+
+        let (function_to_mutate, load) = match candidates.choose(config.rng()) {
+            Some(candidate) => *candidate,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!(
+            "Flipping the signedness of the load at {} in function {}",
+            load,
+            function_to_mutate
+        );
+
+        let data = code_section.data;
+        let mut codes = CodeSection::new();
+        for (i, body) in bodies.iter().enumerate() {
+            let range = body.range();
+            let mut body = data[range.start..range.end].to_vec();
+            if i == function_to_mutate {
+                // The signed and unsigned variants of each load have adjacent
+                // opcodes, with the signed one being even.
+                body[load - range.start] ^= 1;
+            }
+            codes.raw(&body);
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.preserve_semantics
+            && !config.reduce
+            && config.info().code_bodies().map_or(false, |bodies| {
+                bodies.iter().any(|body| {
+                    body.get_operators_reader().map_or(false, |mut ops| {
+                        ops.allow_memarg64(true);
+                        ops.into_iter()
+                            .any(|op| op.map_or(false, |op| is_sub_word_load(&op)))
+                    })
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FlipLoadSignednessMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_flip_load_signedness() {
+        let original = r#"
+            (module
+                (memory 1)
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.load8_s offset=4
+                )
+            )
+        "#;
+        crate::mutators::match_mutation(
+            original,
+            FlipLoadSignednessMutator,
+            r#"
+            (module
+                (memory 1)
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.load8_u offset=4
+                )
+            )
+            "#,
+        );
+
+        let wasm = wat::parse_str(original).unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        let mutation = FlipLoadSignednessMutator
+            .mutate(&mut config)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .finish();
+        wasmparser::Validator::new()
+            .validate_all(&mutation)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_flip_load_signedness_preserving_semantics() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory 1)
+                (func (param i32) (result i64)
+                    local.get 0
+                    i64.load16_u
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(FlipLoadSignednessMutator.can_mutate(&config));
+        config.preserve_semantics(true);
+        assert!(!FlipLoadSignednessMutator.can_mutate(&config));
+    }
+
+    #[test]
+    fn test_flip_load_signedness_memory64() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (memory i64 1)
+                (func (param i64) (result i32)
+                    local.get 0
+                    i32.load8_s offset=0x100000000
+                )
+            )
+            "#,
+            FlipLoadSignednessMutator,
+            r#"
+            (module
+                (memory i64 1)
+                (func (param i64) (result i32)
+                    local.get 0
+                    i32.load8_u offset=0x100000000
+                )
+            )
+            "#,
+        );
+    }
+}
@@ -0,0 +1,376 @@
+//! Mutator that folds an `if` whose condition is a constant.
+
+use super::{merge_blocks::unnest_op, translate, DefaultTranslator, Mutator, Translator};
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use wasm_encoder::{CodeSection, Function, Instruction, Module};
+use wasmparser::{FunctionBody, Operator};
+
+/// Replaces `i32.const C; if ... else ... end` with the arm that is always
+/// taken.
+///
+/// The taken arm is inlined in place of the `if` when nothing branches to the
+/// `if` itself, and is otherwise wrapped in a `block` of the same type. When
+/// `C` is zero and there is no `else` arm the whole `if` is removed.
+#[derive(Clone, Copy)]
+pub struct FoldConstantIfMutator;
+
+/// An `if` preceded by an `i32.const`, given as operator indices.
+#[derive(Clone, Copy)]
+struct ConstantIf {
+    function: usize,
+    /// The index of the `i32.const`, which is directly followed by the `if`.
+    condition: usize,
+    else_: Option<usize>,
+    end: usize,
+}
+
+fn constant_ifs(function: usize, ops: &[Operator]) -> Vec<ConstantIf> {
+    let mut ifs = Vec::new();
+    let mut frames = Vec::new();
+    for (i, op) in ops.iter().enumerate() {
+        match op {
+            Operator::If { .. } if i > 0 && matches!(ops[i - 1], Operator::I32Const { .. }) => {
+                frames.push(Some(ConstantIf {
+                    function,
+                    condition: i - 1,
+                    else_: None,
+                    end: 0,
+                }));
+            }
+            Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Try { .. } => frames.push(None),
+            Operator::Else => {
+                if let Some(Some(constant_if)) = frames.last_mut() {
+                    constant_if.else_ = Some(i);
+                }
+            }
+            Operator::End | Operator::Delegate { .. } => {
+                if let Some(Some(mut constant_if)) = frames.pop() {
+                    constant_if.end = i;
+                    ifs.push(constant_if);
+                }
+            }
+            _ => {}
+        }
+    }
+    ifs
+}
+
+/// Returns whether `arm` can't be inlined without its enclosing frame, either
+/// because something branches to that frame or because it contains a
+/// `delegate`, whose label isn't renumbered by [`unnest_op`].
+fn must_keep_frame(arm: &[Operator]) -> Result<bool> {
+    let mut depth = 0;
+    for op in arm {
+        let targets_frame = match op {
+            Operator::Br { relative_depth }
+            | Operator::BrIf { relative_depth }
+            | Operator::Rethrow { relative_depth } => *relative_depth == depth,
+            Operator::BrTable { table } => {
+                table.default() == depth
+                    || table
+                        .targets()
+                        .collect::<wasmparser::Result<Vec<_>>>()?
+                        .contains(&depth)
+            }
+            Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Try { .. } => {
+                depth += 1;
+                false
+            }
+            Operator::End => {
+                depth -= 1;
+                false
+            }
+            Operator::Delegate { .. } => true,
+            _ => false,
+        };
+        if targets_frame {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn fold(body: &FunctionBody, ops: &[Operator], constant_if: ConstantIf) -> Result<Function> {
+    let mut func = Function::new(translate::locals(&mut DefaultTranslator, body)?);
+
+    let ConstantIf {
+        condition,
+        else_,
+        end,
+        ..
+    } = constant_if;
+    let ty = match ops[condition + 1] {
+        Operator::If { ty } => ty,
+        _ => unreachable!(),
+    };
+    let arm = match ops[condition] {
+        Operator::I32Const { value: 0 } => match else_ {
+            Some(else_) => &ops[else_ + 1..end],
+            None => &[],
+        },
+        _ => &ops[condition + 2..else_.unwrap_or(end)],
+    };
+
+    for op in &ops[..condition] {
+        func.instruction(&DefaultTranslator.translate_op(op)?);
+    }
+    if must_keep_frame(arm)? {
+        func.instruction(&Instruction::Block(
+            DefaultTranslator.translate_block_type(&ty)?,
+        ));
+        for op in arm {
+            func.instruction(&DefaultTranslator.translate_op(op)?);
+        }
+        func.instruction(&Instruction::End);
+    } else {
+        let mut depth = 0;
+        for op in arm {
+            match op {
+                Operator::Block { .. }
+                | Operator::Loop { .. }
+                | Operator::If { .. }
+                | Operator::Try { .. } => depth += 1,
+                Operator::End => depth -= 1,
+                _ => {}
+            }
+            func.instruction(&unnest_op(op, depth)?);
+        }
+    }
+    for op in &ops[end + 1..] {
+        func.instruction(&DefaultTranslator.translate_op(op)?);
+    }
+    Ok(func)
+}
+
+impl Mutator for FoldConstantIfMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let code_section = config.info().get_code_section();
+        let mut bodies = Vec::new();
+        let mut candidates = Vec::new();
+        for (i, body) in config.info().code_bodies()?.into_iter().enumerate() {
+            config.consume_fuel(1)?;
+            let mut ops = body.get_operators_reader()?;
+            ops.allow_memarg64(true);
+            let ops = ops.into_iter().collect::<wasmparser::Result<Vec<_>>>()?;
+            candidates.extend(constant_ifs(i, &ops));
+            bodies.push((body, ops));
+        }
+
+        let constant_if = match candidates.choose(config.rng()) {
+            Some(candidate) => *candidate,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!(
+            "Folding the constant `if` at operator {} of function {}",
+            constant_if.condition + 1,
+            constant_if.function
+        );
+
+        let mut codes = CodeSection::new();
+        for (i, (body, ops)) in bodies.iter().enumerate() {
+            if i == constant_if.function {
+                codes.function(&fold(body, ops, constant_if)?);
+            } else {
+                codes.raw(&code_section.data[body.range().start..body.range().end]);
+            }
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        if config.reduce {
+            return false;
+        }
+        let bodies = match config.info().code_bodies() {
+            Ok(bodies) => bodies,
+            Err(_) => return false,
+        };
+        bodies.iter().any(|body| {
+            let mut ops = match body.get_operators_reader() {
+                Ok(ops) => ops,
+                Err(_) => return false,
+            };
+            ops.allow_memarg64(true);
+            let mut prev_is_const = false;
+            for op in ops {
+                match op {
+                    Ok(Operator::If { .. }) if prev_is_const => return true,
+                    Ok(op) => prev_is_const = matches!(op, Operator::I32Const { .. }),
+                    Err(_) => return false,
+                }
+            }
+            false
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FoldConstantIfMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_fold_constant_if_then() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func (result i32)
+                    i32.const 1
+                    if (result i32)
+                        i32.const 5
+                    else
+                        i32.const 6
+                    end
+                )
+            )
+            "#,
+            FoldConstantIfMutator,
+            r#"
+            (module
+                (func (result i32)
+                    i32.const 5
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_fold_constant_if_else() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func (result i32)
+                    i32.const 0
+                    if (result i32)
+                        i32.const 5
+                    else
+                        i32.const 6
+                    end
+                )
+            )
+            "#,
+            FoldConstantIfMutator,
+            r#"
+            (module
+                (func (result i32)
+                    i32.const 6
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_fold_constant_if_requires_constant_condition() {
+        let wasm = wat::parse_str("(module (func (param i32) local.get 0 if end))").unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!FoldConstantIfMutator.can_mutate(&config));
+    }
+
+    #[test]
+    fn test_fold_constant_if_skipped_when_reducing() {
+        let wasm = wat::parse_str("(module (func i32.const 1 if end))").unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(FoldConstantIfMutator.can_mutate(&config));
+
+        config.reduce(true);
+        assert!(!FoldConstantIfMutator.can_mutate(&config));
+    }
+
+    #[test]
+    fn test_fold_constant_if_keeps_branch_target() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func (param i32)
+                    i32.const 7
+                    if
+                        local.get 0
+                        br_if 0
+                        local.get 0
+                        br_if 1
+                    end
+                )
+            )
+            "#,
+            FoldConstantIfMutator,
+            r#"
+            (module
+                (func (param i32)
+                    block
+                        local.get 0
+                        br_if 0
+                        local.get 0
+                        br_if 1
+                    end
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_fold_constant_if_removes_untaken() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func
+                    i32.const 0
+                    if
+                        unreachable
+                    end
+                    nop
+                )
+            )
+            "#,
+            FoldConstantIfMutator,
+            r#"
+            (module
+                (func
+                    nop
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_fold_constant_if_memory64() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory i64 1)
+                (func (param i64)
+                    local.get 0
+                    i32.load offset=0x100000000
+                    drop
+                    i32.const 1
+                    if
+                    end
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(FoldConstantIfMutator.can_mutate(&config));
+    }
+}
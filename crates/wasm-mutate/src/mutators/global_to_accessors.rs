@@ -0,0 +1,264 @@
+//! Mutator that exposes an exported mutable global through accessor
+//! functions.
+
+use super::{translate, DefaultTranslator, Mutator};
+use crate::module::{map_primitive_type, PrimitiveTypeInfo, TypeInfo};
+use crate::{Error, Result, WasmMutate};
+
+use rand::{seq::SliceRandom, Rng};
+use wasm_encoder::{
+    CodeSection, Export, ExportSection, Function, FunctionSection, Instruction, Module, SectionId,
+    TypeSection,
+};
+use wasmparser::{
+    ExportSectionReader, ExternalKind, GlobalSectionReader, GlobalType, ImportSectionReader,
+    TypeRef, TypeSectionReader,
+};
+
+/// Adds `get_<name>` and `set_<name>` functions for an exported mutable
+/// global named `<name>` and exports them, optionally removing the export of
+/// the global itself.
+///
+/// This changes the module's interface, so it is not semantics-preserving.
+#[derive(Clone, Copy)]
+pub struct GlobalToAccessorsMutator;
+
+/// Returns the types of all globals, imported ones first.
+pub(super) fn global_types(config: &WasmMutate) -> Result<Vec<GlobalType>> {
+    let info = config.info();
+    let mut types = Vec::new();
+    if let Some(imports) = info.imports {
+        let mut reader = ImportSectionReader::new(info.raw_sections[imports].data, 0)?;
+        for _ in 0..reader.get_count() {
+            if let TypeRef::Global(ty) = reader.read()?.ty {
+                types.push(ty);
+            }
+        }
+    }
+    if let Some(globals) = info.globals {
+        let mut reader = GlobalSectionReader::new(info.raw_sections[globals].data, 0)?;
+        for _ in 0..reader.get_count() {
+            types.push(reader.read()?.ty);
+        }
+    }
+    Ok(types)
+}
+
+/// Returns the index of the function type `params -> results`, adding it to
+/// `types` if the module doesn't define it yet.
+pub(super) fn find_or_add_type(
+    config: &WasmMutate,
+    types: &mut TypeSection,
+    added: &mut Vec<(Vec<PrimitiveTypeInfo>, Vec<PrimitiveTypeInfo>)>,
+    params: Vec<PrimitiveTypeInfo>,
+    returns: Vec<PrimitiveTypeInfo>,
+) -> Result<u32> {
+    let existing = config.info().types_map.iter().position(|ty| match ty {
+        TypeInfo::Func(ty) => ty.params == params && ty.returns == returns,
+    });
+    if let Some(index) = existing {
+        return Ok(index as u32);
+    }
+    let ty = |tys: &[PrimitiveTypeInfo]| {
+        tys.iter()
+            .map(map_primitive_type)
+            .collect::<Result<Vec<_>>>()
+    };
+    types.function(ty(&params)?, ty(&returns)?);
+    added.push((params, returns));
+    Ok(config.info().num_types() + added.len() as u32 - 1)
+}
+
+impl Mutator for GlobalToAccessorsMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let globals = global_types(config)?;
+        let mut reader = ExportSectionReader::new(config.info().get_exports_section().data, 0)?;
+        let exports = (0..reader.get_count())
+            .map(|_| reader.read())
+            .collect::<wasmparser::Result<Vec<_>>>()?;
+
+        let export_names = &config.info().export_names;
+        let candidates = exports
+            .iter()
+            .enumerate()
+            .filter(|(_, export)| {
+                matches!(export.kind, ExternalKind::Global)
+                    && globals
+                        .get(export.index as usize)
+                        .map_or(false, |ty| ty.mutable)
+                    && !export_names.contains(&format!("get_{}", export.name))
+                    && !export_names.contains(&format!("set_{}", export.name))
+            })
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let chosen = match candidates.choose(config.rng()) {
+            Some(chosen) => *chosen,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        let drop_global_export = config.rng().gen_bool(0.5);
+        config.consume_fuel(1)?;
+
+        let export = &exports[chosen];
+        let global = export.index;
+        let ty = globals[global as usize].content_type;
+        log::trace!(
+            "Adding accessors for global {} exported as {:?} (dropping its export: {})",
+            global,
+            export.name,
+            drop_global_export
+        );
+
+        let info = config.info();
+        let mut types = TypeSection::new();
+        if let Some(section) = info.get_type_section() {
+            let mut reader = TypeSectionReader::new(section.data, 0)?;
+            for _ in 0..reader.get_count() {
+                translate::type_def(&mut DefaultTranslator, reader.read()?, &mut types)?;
+            }
+        }
+        let mut added = Vec::new();
+        let getter_ty = find_or_add_type(config, &mut types, &mut added, vec![], vec![ty.into()])?;
+        let setter_ty = find_or_add_type(config, &mut types, &mut added, vec![ty.into()], vec![])?;
+
+        let mut functions = FunctionSection::new();
+        for ty in &info.function_map[info.num_imported_functions() as usize..] {
+            functions.function(*ty);
+        }
+        functions.function(getter_ty);
+        functions.function(setter_ty);
+
+        let mut codes = CodeSection::new();
+        if info.code.is_some() {
+            let data = info.get_code_section().data;
+            for body in info.code_bodies()? {
+                codes.raw(&data[body.range().start..body.range().end]);
+            }
+        }
+        let mut getter = Function::new(Vec::new());
+        getter.instruction(&Instruction::GlobalGet(global));
+        getter.instruction(&Instruction::End);
+        codes.function(&getter);
+        let mut setter = Function::new(Vec::new());
+        setter.instruction(&Instruction::LocalGet(0));
+        setter.instruction(&Instruction::GlobalSet(global));
+        setter.instruction(&Instruction::End);
+        codes.function(&setter);
+
+        let mut new_exports = ExportSection::new();
+        for (i, export) in exports.iter().enumerate() {
+            if i == chosen && drop_global_export {
+                continue;
+            }
+            let item = match export.kind {
+                ExternalKind::Func => Export::Function(export.index),
+                ExternalKind::Table => Export::Table(export.index),
+                ExternalKind::Memory => Export::Memory(export.index),
+                ExternalKind::Global => Export::Global(export.index),
+                ExternalKind::Tag => Export::Tag(export.index),
+            };
+            new_exports.export(export.name, item);
+        }
+        let getter_index = info.num_functions();
+        new_exports.export(
+            &format!("get_{}", export.name),
+            Export::Function(getter_index),
+        );
+        new_exports.export(
+            &format!("set_{}", export.name),
+            Export::Function(getter_index + 1),
+        );
+
+        let module = info.replace_or_insert_sections(
+            &[
+                SectionId::Type,
+                SectionId::Function,
+                SectionId::Export,
+                SectionId::Code,
+            ],
+            |id, module| {
+                match id {
+                    SectionId::Type => module.section(&types),
+                    SectionId::Function => module.section(&functions),
+                    SectionId::Export => module.section(&new_exports),
+                    _ => module.section(&codes),
+                };
+            },
+        );
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.preserve_semantics && config.info().has_exports() && config.info().num_globals() > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GlobalToAccessorsMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_global_to_accessors() {
+        let original = r#"
+            (module
+                (type (func (result i32)))
+                (global (export "g") (mut i32) (i32.const 7))
+                (func (type 0)
+                    global.get 0
+                )
+            )
+        "#;
+        let with_export = r#"
+            (module
+                (type (func (result i32)))
+                (type (func (param i32)))
+                (global (mut i32) (i32.const 7))
+                (func (type 0)
+                    global.get 0
+                )
+                (func (type 0)
+                    global.get 0
+                )
+                (func (type 1)
+                    local.get 0
+                    global.set 0
+                )
+                (export "g" (global 0))
+                (export "get_g" (func 1))
+                (export "set_g" (func 2))
+            )
+        "#;
+        let without_export = r#"
+            (module
+                (type (func (result i32)))
+                (type (func (param i32)))
+                (global (mut i32) (i32.const 7))
+                (func (type 0)
+                    global.get 0
+                )
+                (func (type 0)
+                    global.get 0
+                )
+                (func (type 1)
+                    local.get 0
+                    global.set 0
+                )
+                (export "get_g" (func 1))
+                (export "set_g" (func 2))
+            )
+        "#;
+        crate::mutators::match_mutation(original, GlobalToAccessorsMutator, with_export);
+        crate::mutators::match_mutation(original, GlobalToAccessorsMutator, without_export);
+    }
+
+    #[test]
+    fn test_global_to_accessors_requires_mutable_global() {
+        let wasm = wat::parse_str(r#"(module (global (export "g") i32 (i32.const 0)))"#).unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(GlobalToAccessorsMutator.mutate(&mut config).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+//! Mutator that guards an integer division against a zero divisor.
+
+use super::{select_to_if::local_types, translate, DefaultTranslator, Mutator, Translator};
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use wasm_encoder::{BlockType, CodeSection, Function, Instruction, Module, ValType};
+use wasmparser::Operator;
+
+/// Inserts an explicit check before an integer division or remainder which
+/// runs `unreachable` when the divisor is zero.
+///
+/// The divisor is stashed in a fresh local so it can be tested and then pushed
+/// back for the division. Since the division would trap on a zero divisor
+/// anyway, this doesn't change the behavior of the module.
+#[derive(Clone, Copy)]
+pub struct GuardDivideMutator;
+
+/// Returns the type of the operands of `op` if it is an integer division or
+/// remainder.
+fn divisor_type(op: &Operator) -> Option<ValType> {
+    use Operator::*;
+    match op {
+        I32DivS | I32DivU | I32RemS | I32RemU => Some(ValType::I32),
+        I64DivS | I64DivU | I64RemS | I64RemU => Some(ValType::I64),
+        _ => None,
+    }
+}
+
+impl Mutator for GuardDivideMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let first_defined = config.info().num_imported_functions();
+        let code_section = config.info().get_code_section();
+        let bodies = config.info().code_bodies()?;
+        let mut candidates = Vec::new();
+        for (i, body) in bodies.iter().enumerate() {
+            config.consume_fuel(1)?;
+            let mut reader = body.get_operators_reader()?;
+            reader.allow_memarg64(true);
+            for (j, op) in reader.into_iter().enumerate() {
+                if let Some(ty) = divisor_type(&op?) {
+                    candidates.push((i, j, ty));
+                }
+            }
+        }
+
+        let (function_to_mutate, target, ty) = match candidates.choose(config.rng()) {
+            Some(candidate) => *candidate,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!(
+            "Guarding the division at operator {} of function {}",
+            target,
+            function_to_mutate
+        );
+
+        let mut codes = CodeSection::new();
+        for (i, body) in bodies.iter().enumerate() {
+            if i != function_to_mutate {
+                codes.raw(&code_section.data[body.range().start..body.range().end]);
+                continue;
+            }
+            let divisor = local_types(config, first_defined + i as u32, body)?.len() as u32;
+            let mut locals = translate::locals(&mut DefaultTranslator, body)?;
+            locals.push((1, ty));
+
+            let mut func = Function::new(locals);
+            let mut reader = body.get_operators_reader()?;
+            reader.allow_memarg64(true);
+            for (j, op) in reader.into_iter().enumerate() {
+                let op = op?;
+                if j == target {
+                    func.instruction(&Instruction::LocalTee(divisor));
+                    func.instruction(&if ty == ValType::I32 {
+                        Instruction::I32Eqz
+                    } else {
+                        Instruction::I64Eqz
+                    });
+                    func.instruction(&Instruction::If(BlockType::Empty));
+                    func.instruction(&Instruction::Unreachable);
+                    func.instruction(&Instruction::End);
+                    func.instruction(&Instruction::LocalGet(divisor));
+                }
+                func.instruction(&DefaultTranslator.translate_op(&op)?);
+            }
+            codes.function(&func);
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.reduce
+            && config.info().code_bodies().map_or(false, |bodies| {
+                bodies.iter().any(|body| {
+                    body.get_operators_reader().map_or(false, |mut ops| {
+                        ops.allow_memarg64(true);
+                        ops.into_iter()
+                            .any(|op| op.map_or(false, |op| divisor_type(&op).is_some()))
+                    })
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GuardDivideMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_guard_divide() {
+        let original = r#"
+            (module
+                (func (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.div_s
+                )
+            )
+        "#;
+        crate::mutators::match_mutation(
+            original,
+            GuardDivideMutator,
+            r#"
+            (module
+                (func (param i32 i32) (result i32)
+                    (local i32)
+                    local.get 0
+                    local.get 1
+                    local.tee 2
+                    i32.eqz
+                    if
+                        unreachable
+                    end
+                    local.get 2
+                    i32.div_s
+                )
+            )
+            "#,
+        );
+
+        let wasm = wat::parse_str(original).unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.preserve_semantics(true);
+        config.setup(&wasm).unwrap();
+        assert!(GuardDivideMutator.can_mutate(&config));
+        let mutation = GuardDivideMutator
+            .mutate(&mut config)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .finish();
+        wasmparser::Validator::new()
+            .validate_all(&mutation)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_guard_divide_requires_division() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (func (param f32) (result f32)
+                    local.get 0
+                    local.get 0
+                    f32.div
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!GuardDivideMutator.can_mutate(&config));
+    }
+
+    #[test]
+    fn test_guard_divide_memory64() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (memory i64 1)
+                (func (param i64 i32) (result i32)
+                    local.get 0
+                    i32.load offset=0x100000000
+                    local.get 1
+                    i32.div_u
+                )
+            )
+            "#,
+            GuardDivideMutator,
+            r#"
+            (module
+                (memory i64 1)
+                (func (param i64 i32) (result i32)
+                    (local i32)
+                    local.get 0
+                    i32.load offset=0x100000000
+                    local.get 1
+                    local.tee 2
+                    i32.eqz
+                    if
+                        unreachable
+                    end
+                    local.get 2
+                    i32.div_u
+                )
+            )
+            "#,
+        );
+    }
+}
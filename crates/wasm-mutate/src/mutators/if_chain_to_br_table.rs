@@ -0,0 +1,466 @@
+//! Mutator that lowers a chain of `if`s comparing a local against dense
+//! constants into a `br_table`.
+
+use super::{translate, DefaultTranslator, Mutator, Translator};
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use std::convert::TryFrom;
+use std::ops::Range;
+use wasm_encoder::{BlockType, CodeSection, Function, Instruction, Module};
+use wasmparser::{FunctionBody, Operator};
+
+/// Replaces a chain of `if`/`else if` comparisons of a single local against
+/// dense constants with a `br_table` dispatching to one block per case.
+///
+/// The only supported shape is, for `n >= 2` links:
+///
+/// ```text
+/// local.get $x
+/// i32.const C0
+/// i32.eq
+/// if
+///   A0
+/// else
+///   local.get $x
+///   i32.const C1
+///   i32.eq
+///   if
+///     A1
+///   else
+///     ...
+///       local.get $x
+///       i32.const Cn-1
+///       i32.eq
+///       if
+///         An-1
+///       else   ;; optional
+///         D
+///       end
+///     ...
+///   end
+/// end
+/// ```
+///
+/// where every `if` has an empty block type, every `else` arm but the last
+/// consists solely of the next link of the chain, and `C0` to `Cn-1` are the
+/// distinct values `K`, `K + 1`, ..., `K + n - 1` in any order. Arms
+/// containing a `delegate` are not supported. The chain becomes:
+///
+/// ```text
+/// block              ;; exit
+///   block            ;; default
+///     block          ;; case n-1
+///       ...
+///         block      ;; case 0
+///           local.get $x
+///           i32.const K
+///           i32.sub
+///           br_table ...
+///         end
+///         A0
+///         br (exit)
+///       ...
+///     end
+///     An-1
+///     br (exit)
+///   end
+///   D
+/// end
+/// ```
+///
+/// with the branches in the arms relabeled to account for the new nesting.
+/// Since nothing but the comparisons runs before an arm is picked, `$x` is
+/// the same in every comparison and the chain is semantics-preserving.
+#[derive(Clone, Copy)]
+pub struct IfChainToBrTableMutator;
+
+#[derive(Clone)]
+struct IfChain {
+    function: usize,
+    /// The index of the first `local.get`.
+    start: usize,
+    /// The index of the `end` of the outermost `if`.
+    end: usize,
+    local: u32,
+    /// The constant compared against and the operator range of the arm of
+    /// each link.
+    cases: Vec<(i32, Range<usize>)>,
+    /// The operator range of the final `else` arm, if any.
+    default: Option<Range<usize>>,
+}
+
+impl IfChain {
+    /// The smallest constant compared against.
+    fn base(&self) -> i32 {
+        self.cases.iter().map(|(value, _)| *value).min().unwrap()
+    }
+}
+
+/// Returns the local and constant compared by the link of a chain starting at
+/// operator `i`, if any.
+fn link(ops: &[Operator], i: usize) -> Option<(u32, i32)> {
+    match ops.get(i..i + 4)? {
+        [Operator::LocalGet { local_index }, Operator::I32Const { value }, Operator::I32Eq, Operator::If {
+            ty: wasmparser::BlockType::Empty,
+        }] => Some((*local_index, *value)),
+        _ => None,
+    }
+}
+
+fn if_chains(function: usize, ops: &[Operator]) -> Vec<IfChain> {
+    // The `else` and `end` of every `if`.
+    let mut elses = vec![None; ops.len()];
+    let mut ends = vec![0; ops.len()];
+    let mut frames = Vec::new();
+    for (i, op) in ops.iter().enumerate() {
+        match op {
+            Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Try { .. } => frames.push(i),
+            Operator::Else => {
+                if let Some(start) = frames.last() {
+                    elses[*start] = Some(i);
+                }
+            }
+            Operator::End | Operator::Delegate { .. } => {
+                if let Some(start) = frames.pop() {
+                    ends[start] = i;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut chains = Vec::new();
+    for start in 0..ops.len() {
+        let (local, _) = match link(ops, start) {
+            Some(link) => link,
+            None => continue,
+        };
+        let mut chain = IfChain {
+            function,
+            start,
+            end: ends[start + 3],
+            local,
+            cases: Vec::new(),
+            default: None,
+        };
+        let mut i = start;
+        loop {
+            let value = link(ops, i).unwrap().1;
+            let if_ = i + 3;
+            let else_ = match elses[if_] {
+                Some(else_) => else_,
+                None => {
+                    chain.cases.push((value, if_ + 1..ends[if_]));
+                    break;
+                }
+            };
+            chain.cases.push((value, if_ + 1..else_));
+            match link(ops, else_ + 1) {
+                Some((next, _)) if next == local && ends[else_ + 4] + 1 == ends[if_] => {
+                    i = else_ + 1;
+                }
+                _ => {
+                    chain.default = Some(else_ + 1..ends[if_]);
+                    break;
+                }
+            }
+        }
+
+        if chain.cases.len() < 2
+            || ops[start..chain.end]
+                .iter()
+                .any(|op| matches!(op, Operator::Delegate { .. }))
+        {
+            continue;
+        }
+        let base = chain.base();
+        let mut seen = vec![false; chain.cases.len()];
+        let dense = chain.cases.iter().all(|(value, _)| {
+            match usize::try_from(i64::from(*value) - i64::from(base)) {
+                Ok(index) if index < seen.len() && !seen[index] => {
+                    seen[index] = true;
+                    true
+                }
+                _ => false,
+            }
+        });
+        if dense {
+            chains.push(chain);
+        }
+    }
+    chains
+}
+
+/// Translates the operators of an arm which was nested `levels` deep in the
+/// chain and now sits `exit` labels inside of the exit block, such that
+/// branches to the chain target the exit block and branches beyond it keep
+/// their destination.
+fn relabel_arm(func: &mut Function, arm: &[Operator], levels: u32, exit: u32) -> Result<()> {
+    let mut depth = 0;
+    for op in arm {
+        match op {
+            Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Try { .. } => depth += 1,
+            Operator::End => depth -= 1,
+            _ => {}
+        }
+        let relabel = |label: u32| {
+            if label < depth {
+                label
+            } else if label - depth < levels {
+                exit + depth
+            } else {
+                label - levels + exit + 1
+            }
+        };
+        func.instruction(&match op {
+            Operator::Br { relative_depth } => Instruction::Br(relabel(*relative_depth)),
+            Operator::BrIf { relative_depth } => Instruction::BrIf(relabel(*relative_depth)),
+            Operator::Rethrow { relative_depth } => Instruction::Rethrow(relabel(*relative_depth)),
+            Operator::BrTable { table } => Instruction::BrTable(
+                table
+                    .targets()
+                    .map(|target| Ok(relabel(target?)))
+                    .collect::<Result<Vec<_>>>()?
+                    .into(),
+                relabel(table.default()),
+            ),
+            _ => DefaultTranslator.translate_op(op)?,
+        });
+    }
+    Ok(())
+}
+
+fn lower(body: &FunctionBody, ops: &[Operator], chain: &IfChain) -> Result<Function> {
+    let mut func = Function::new(translate::locals(&mut DefaultTranslator, body)?);
+    for op in &ops[..chain.start] {
+        func.instruction(&DefaultTranslator.translate_op(op)?);
+    }
+
+    let n = chain.cases.len() as u32;
+    let base = chain.base();
+    // The exit block, the default block and one block per case.
+    for _ in 0..n + 2 {
+        func.instruction(&Instruction::Block(BlockType::Empty));
+    }
+    func.instruction(&Instruction::LocalGet(chain.local));
+    if base != 0 {
+        func.instruction(&Instruction::I32Const(base));
+        func.instruction(&Instruction::I32Sub);
+    }
+    let mut targets = vec![0; chain.cases.len()];
+    for (case, (value, _)) in chain.cases.iter().enumerate() {
+        targets[(i64::from(*value) - i64::from(base)) as usize] = case as u32;
+    }
+    func.instruction(&Instruction::BrTable(targets.into(), n));
+
+    for (case, (_, arm)) in chain.cases.iter().enumerate() {
+        let case = case as u32;
+        func.instruction(&Instruction::End);
+        relabel_arm(&mut func, &ops[arm.clone()], case + 1, n - case)?;
+        func.instruction(&Instruction::Br(n - case));
+    }
+    func.instruction(&Instruction::End);
+    if let Some(default) = &chain.default {
+        relabel_arm(&mut func, &ops[default.clone()], n, 0)?;
+    }
+    func.instruction(&Instruction::End);
+
+    for op in &ops[chain.end + 1..] {
+        func.instruction(&DefaultTranslator.translate_op(op)?);
+    }
+    Ok(func)
+}
+
+impl Mutator for IfChainToBrTableMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let code_section = config.info().get_code_section();
+        let mut bodies = Vec::new();
+        let mut candidates = Vec::new();
+        for (i, body) in config.info().code_bodies()?.into_iter().enumerate() {
+            config.consume_fuel(1)?;
+            let mut ops = body.get_operators_reader()?;
+            ops.allow_memarg64(true);
+            let ops = ops.into_iter().collect::<wasmparser::Result<Vec<_>>>()?;
+            candidates.extend(if_chains(i, &ops));
+            bodies.push((body, ops));
+        }
+
+        let chain = match candidates.choose(config.rng()) {
+            Some(candidate) => candidate.clone(),
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!(
+            "Lowering the chain of {} `if`s at operator {} of function {} into a `br_table`",
+            chain.cases.len(),
+            chain.start,
+            chain.function
+        );
+
+        let mut codes = CodeSection::new();
+        for (i, (body, ops)) in bodies.iter().enumerate() {
+            if i == chain.function {
+                codes.function(&lower(body, ops, &chain)?);
+            } else {
+                codes.raw(&code_section.data[body.range().start..body.range().end]);
+            }
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.reduce
+            && config.info().code_bodies().map_or(false, |bodies| {
+                bodies.iter().any(|body| {
+                    body.get_operators_reader().map_or(false, |mut ops| {
+                        ops.allow_memarg64(true);
+                        ops.into_iter()
+                            .collect::<wasmparser::Result<Vec<_>>>()
+                            .map_or(false, |ops| !if_chains(0, &ops).is_empty())
+                    })
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IfChainToBrTableMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_if_chain_to_br_table() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func (param i32) (result i32) (local i32)
+                    local.get 0
+                    i32.const 2
+                    i32.eq
+                    if
+                        i32.const 20
+                        local.set 1
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.eq
+                        if
+                            i32.const 10
+                            local.set 1
+                            br 1
+                        else
+                            i32.const 30
+                            local.set 1
+                        end
+                    end
+                    local.get 1
+                )
+            )
+            "#,
+            IfChainToBrTableMutator,
+            r#"
+            (module
+                (func (param i32) (result i32) (local i32)
+                    block
+                        block
+                            block
+                                block
+                                    local.get 0
+                                    i32.const 1
+                                    i32.sub
+                                    br_table 1 0 2
+                                end
+                                i32.const 20
+                                local.set 1
+                                br 2
+                            end
+                            i32.const 10
+                            local.set 1
+                            br 1
+                            br 1
+                        end
+                        i32.const 30
+                        local.set 1
+                    end
+                    local.get 1
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_if_chain_requires_dense_constants() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (func (param i32)
+                    local.get 0
+                    i32.const 0
+                    i32.eq
+                    if
+                        nop
+                    else
+                        local.get 0
+                        i32.const 5
+                        i32.eq
+                        if
+                            nop
+                        end
+                    end
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!IfChainToBrTableMutator.can_mutate(&config));
+    }
+
+    #[test]
+    fn test_if_chain_to_br_table_memory64() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory i64 1)
+                (func (param i32) (result i32)
+                    i64.const 0
+                    i32.load offset=0x100000000
+                    drop
+                    local.get 0
+                    i32.const 1
+                    i32.eq
+                    if
+                        nop
+                    else
+                        local.get 0
+                        i32.const 0
+                        i32.eq
+                        if
+                            nop
+                        end
+                    end
+                    i32.const 0
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(IfChainToBrTableMutator.can_mutate(&config));
+    }
+}
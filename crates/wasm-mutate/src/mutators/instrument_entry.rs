@@ -0,0 +1,281 @@
+//! Mutator that calls an imported logging function on function entry.
+
+use super::{translate, Item, Mutator, Translator};
+use crate::module::TypeInfo;
+use crate::{Error, Result, WasmMutate};
+
+use rand::Rng;
+use wasm_encoder::{
+    CodeSection, ElementSection, EntityType, Export, ExportSection, Function, GlobalSection,
+    ImportSection, Instruction, Module, SectionId, StartSection, TypeSection,
+};
+use wasmparser::{
+    CodeSectionReader, ElementSectionReader, ExportSectionReader, ExternalKind,
+    GlobalSectionReader, ImportSectionReader, TypeRef, TypeSectionReader,
+};
+
+/// Prepends a `call` to an imported `env.log` function of type `[] -> []` to
+/// a random function body.
+///
+/// The import is added if the module doesn't have it yet, in which case all
+/// defined functions are shifted up by one index.
+#[derive(Clone, Copy)]
+pub struct InstrumentEntryMutator;
+
+/// Translator shifting the indices of defined functions to make room for a new
+/// imported function.
+struct ShiftFunctions {
+    first_defined: u32,
+    shift: u32,
+}
+
+impl Translator for ShiftFunctions {
+    fn as_obj(&mut self) -> &mut dyn Translator {
+        self
+    }
+
+    fn remap(&mut self, item: Item, idx: u32) -> Result<u32> {
+        Ok(match item {
+            Item::Function if idx >= self.first_defined => idx + self.shift,
+            _ => idx,
+        })
+    }
+}
+
+fn is_empty_func_type(ty: &TypeInfo) -> bool {
+    match ty {
+        TypeInfo::Func(ty) => ty.params.is_empty() && ty.returns.is_empty(),
+    }
+}
+
+impl Mutator for InstrumentEntryMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        config.consume_fuel(1)?;
+        let num_local = config.info().num_local_functions();
+        if num_local == 0 {
+            return Err(Error::no_mutations_applicable());
+        }
+        let function_to_mutate = config.rng().gen_range(0..num_local);
+        let info = config.info();
+
+        // Look for an existing `env.log` import, otherwise it is added after
+        // all other imported functions.
+        let mut existing_log = None;
+        if let Some(imports) = info.imports {
+            let mut function = 0;
+            for import in ImportSectionReader::new(info.raw_sections[imports].data, 0)? {
+                let import = import?;
+                if let TypeRef::Func(ty) = import.ty {
+                    if import.module == "env"
+                        && import.name == "log"
+                        && is_empty_func_type(&info.types_map[ty as usize])
+                    {
+                        existing_log = Some(function);
+                    }
+                    function += 1;
+                }
+            }
+        }
+        let first_defined = info.num_imported_functions();
+        let (log, shift) = match existing_log {
+            Some(log) => (log, 0),
+            None => (first_defined, 1),
+        };
+        log::trace!(
+            "Calling function {} on entry of function {}",
+            log,
+            first_defined + function_to_mutate
+        );
+        let mut t = ShiftFunctions {
+            first_defined,
+            shift,
+        };
+
+        let mut types = TypeSection::new();
+        if let Some(section) = info.get_type_section() {
+            let mut reader = TypeSectionReader::new(section.data, 0)?;
+            for _ in 0..reader.get_count() {
+                translate::type_def(&mut t, reader.read()?, &mut types)?;
+            }
+        }
+        let mut imports = ImportSection::new();
+        if let Some(section) = info.imports {
+            for import in ImportSectionReader::new(info.raw_sections[section].data, 0)? {
+                let import = import?;
+                let ty = match &import.ty {
+                    TypeRef::Func(ty) => EntityType::Function(*ty),
+                    TypeRef::Table(ty) => EntityType::Table(t.translate_table_type(ty)?),
+                    TypeRef::Memory(ty) => EntityType::Memory(t.translate_memory_type(ty)?),
+                    TypeRef::Global(ty) => EntityType::Global(t.translate_global_type(ty)?),
+                    TypeRef::Tag(ty) => EntityType::Tag(t.translate_tag_type(ty)?),
+                };
+                imports.import(import.module, import.name, ty);
+            }
+        }
+        if existing_log.is_none() {
+            let ty = match info.types_map.iter().position(is_empty_func_type) {
+                Some(ty) => ty as u32,
+                None => {
+                    types.function([], []);
+                    info.num_types()
+                }
+            };
+            imports.import("env", "log", EntityType::Function(ty));
+        }
+
+        let mut globals = GlobalSection::new();
+        if let Some(section) = info.globals {
+            for global in GlobalSectionReader::new(info.raw_sections[section].data, 0)? {
+                t.translate_global(global?, &mut globals)?;
+            }
+        }
+        let mut exports = ExportSection::new();
+        if let Some(section) = info.exports {
+            for export in ExportSectionReader::new(info.raw_sections[section].data, 0)? {
+                let export = export?;
+                let item = match export.kind {
+                    ExternalKind::Func => Export::Function(t.remap(Item::Function, export.index)?),
+                    ExternalKind::Table => Export::Table(export.index),
+                    ExternalKind::Memory => Export::Memory(export.index),
+                    ExternalKind::Global => Export::Global(export.index),
+                    ExternalKind::Tag => Export::Tag(export.index),
+                };
+                exports.export(export.name, item);
+            }
+        }
+        let start = match info.start_function() {
+            Some(start) => Some(StartSection {
+                function_index: t.remap(Item::Function, start)?,
+            }),
+            None => None,
+        };
+        let mut elements = ElementSection::new();
+        if let Some(section) = info.elements {
+            for element in ElementSectionReader::new(info.raw_sections[section].data, 0)? {
+                t.translate_element(element?, &mut elements)?;
+            }
+        }
+        let mut codes = CodeSection::new();
+        let reader = CodeSectionReader::new(info.get_code_section().data, 0)?;
+        for (i, body) in reader.into_iter().enumerate() {
+            let body = body?;
+            if i as u32 != function_to_mutate {
+                t.translate_code(body, &mut codes)?;
+                continue;
+            }
+            let mut func = Function::new(translate::locals(&mut t, &body)?);
+            func.instruction(&Instruction::Call(log));
+            let mut reader = body.get_operators_reader()?;
+            reader.allow_memarg64(true);
+            for op in reader {
+                func.instruction(&t.translate_op(&op?)?);
+            }
+            codes.function(&func);
+        }
+
+        let mut ids = vec![SectionId::Type, SectionId::Import];
+        if info.globals.is_some() {
+            ids.push(SectionId::Global);
+        }
+        if info.exports.is_some() {
+            ids.push(SectionId::Export);
+        }
+        if start.is_some() {
+            ids.push(SectionId::Start);
+        }
+        if info.elements.is_some() {
+            ids.push(SectionId::Element);
+        }
+        ids.push(SectionId::Code);
+        let module = info.replace_or_insert_sections(&ids, |id, module| {
+            match id {
+                SectionId::Type => module.section(&types),
+                SectionId::Import => module.section(&imports),
+                SectionId::Global => module.section(&globals),
+                SectionId::Export => module.section(&exports),
+                SectionId::Start => module.section(start.as_ref().unwrap()),
+                SectionId::Element => module.section(&elements),
+                _ => module.section(&codes),
+            };
+        });
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.preserve_semantics && config.info().has_nonempty_code()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InstrumentEntryMutator;
+
+    #[test]
+    fn test_instrument_entry_adds_import() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (type (func (param i32) (result i32)))
+                (func (export "f") (type 0)
+                    local.get 0
+                    call 0
+                )
+                (elem declare func 0)
+                (start 1)
+                (func
+                    ref.func 0
+                    drop
+                )
+            )
+            "#,
+            InstrumentEntryMutator,
+            r#"
+            (module
+                (type (func (param i32) (result i32)))
+                (type (func))
+                (import "env" "log" (func (type 1)))
+                (func (export "f") (type 0)
+                    call 0
+                    local.get 0
+                    call 1
+                )
+                (elem declare func 1)
+                (start 2)
+                (func
+                    ref.func 1
+                    drop
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_instrument_entry_reuses_import() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (import "env" "log" (func))
+                (func
+                    i32.const 1
+                    drop
+                )
+            )
+            "#,
+            InstrumentEntryMutator,
+            r#"
+            (module
+                (import "env" "log" (func))
+                (func
+                    call 0
+                    i32.const 1
+                    drop
+                )
+            )
+            "#,
+        );
+    }
+}
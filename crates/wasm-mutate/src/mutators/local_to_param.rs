@@ -0,0 +1,276 @@
+//! Mutator that turns a local of an uncalled function into a parameter.
+
+use super::{
+    drop_unused_function_param::referenced_functions, global_to_accessors::find_or_add_type,
+    translate, DefaultTranslator, Mutator, Translator,
+};
+use crate::module::{map_type, PrimitiveTypeInfo, TypeInfo};
+use crate::{Error, Result, WasmMutate};
+
+use rand::{seq::SliceRandom, Rng};
+use wasm_encoder::{
+    CodeSection, Function, FunctionSection, Instruction, Module, SectionId, TypeSection,
+};
+use wasmparser::{Operator, TypeSectionReader};
+
+/// Removes a local of a function which is neither called nor otherwise
+/// referenced and appends a parameter of the same type to the function's
+/// signature instead.
+///
+/// The new parameter takes the index right after the existing parameters, so
+/// the locals declared before the moved one are shifted up by one. Since the
+/// function can never run, the local losing its zero initialization doesn't
+/// change the behavior of the module.
+#[derive(Clone, Copy)]
+pub struct LocalToParamMutator;
+
+/// Returns the defined functions which are unreferenced and declare at least
+/// one local, along with the number of locals they declare.
+fn candidates(config: &WasmMutate) -> Result<Vec<(u32, u32)>> {
+    let info = config.info();
+    let referenced = referenced_functions(config)?;
+    let mut candidates = Vec::new();
+    for (i, body) in info.code_bodies()?.iter().enumerate() {
+        let function = info.num_imported_functions() + i as u32;
+        if referenced.contains(&function) {
+            continue;
+        }
+        let mut num_locals = 0;
+        for local in body.get_locals_reader()? {
+            num_locals += local?.0;
+        }
+        if num_locals > 0 {
+            candidates.push((function, num_locals));
+        }
+    }
+    Ok(candidates)
+}
+
+impl Mutator for LocalToParamMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        config.consume_fuel(1)?;
+        let (function, num_locals) = match candidates(config)?.choose(config.rng()) {
+            Some(candidate) => *candidate,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        // The index of the local among the declared locals.
+        let local = config.rng().gen_range(0..num_locals);
+
+        let info = config.info();
+        let first_defined = info.num_imported_functions();
+        let body = &info.code_bodies()?[(function - first_defined) as usize];
+        let mut ty = match info.get_functype_idx(function) {
+            TypeInfo::Func(ty) => ty.clone(),
+        };
+        let num_params = ty.params.len() as u32;
+        log::trace!(
+            "Moving local {} of function {} into its parameters",
+            num_params + local,
+            function
+        );
+
+        let mut locals = Vec::new();
+        let mut seen = 0;
+        for group in body.get_locals_reader()? {
+            let (count, local_ty) = group?;
+            let mut remaining = count;
+            if (seen..seen + count).contains(&local) {
+                ty.params.push(PrimitiveTypeInfo::from(local_ty));
+                remaining -= 1;
+            }
+            seen += count;
+            if remaining > 0 {
+                locals.push((remaining, map_type(local_ty)?));
+            }
+        }
+
+        let mut types = TypeSection::new();
+        if let Some(section) = info.get_type_section() {
+            let mut reader = TypeSectionReader::new(section.data, 0)?;
+            for _ in 0..reader.get_count() {
+                translate::type_def(&mut DefaultTranslator, reader.read()?, &mut types)?;
+            }
+        }
+        let new_ty = find_or_add_type(config, &mut types, &mut Vec::new(), ty.params, ty.returns)?;
+
+        let mut functions = FunctionSection::new();
+        for (i, ty) in info
+            .function_map
+            .iter()
+            .enumerate()
+            .skip(first_defined as usize)
+        {
+            functions.function(if i as u32 == function { new_ty } else { *ty });
+        }
+
+        let moved = num_params + local;
+        let renumber = |index: u32| {
+            if index == moved {
+                num_params
+            } else if index >= num_params && index < moved {
+                index + 1
+            } else {
+                index
+            }
+        };
+        let mut func = Function::new(locals);
+        let mut reader = body.get_operators_reader()?;
+        reader.allow_memarg64(true);
+        for op in reader {
+            match op? {
+                Operator::LocalGet { local_index } => {
+                    func.instruction(&Instruction::LocalGet(renumber(local_index)))
+                }
+                Operator::LocalSet { local_index } => {
+                    func.instruction(&Instruction::LocalSet(renumber(local_index)))
+                }
+                Operator::LocalTee { local_index } => {
+                    func.instruction(&Instruction::LocalTee(renumber(local_index)))
+                }
+                op => func.instruction(&DefaultTranslator.translate_op(&op)?),
+            };
+        }
+
+        let code_section = info.get_code_section();
+        let mut codes = CodeSection::new();
+        for (i, body) in info.code_bodies()?.iter().enumerate() {
+            if first_defined + i as u32 == function {
+                codes.function(&func);
+            } else {
+                codes.raw(&code_section.data[body.range().start..body.range().end]);
+            }
+        }
+
+        let module = info.replace_or_insert_sections(
+            &[SectionId::Type, SectionId::Function, SectionId::Code],
+            |id, module| {
+                match id {
+                    SectionId::Type => module.section(&types),
+                    SectionId::Function => module.section(&functions),
+                    _ => module.section(&codes),
+                };
+            },
+        );
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.reduce && candidates(config).map_or(false, |candidates| !candidates.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LocalToParamMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_local_to_param() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    (local i64)
+                    i64.const 2
+                    local.set 1
+                    local.get 0
+                )
+            )
+            "#,
+            LocalToParamMutator,
+            r#"
+            (module
+                (type (func (param i32) (result i32)))
+                (type (func (param i32 i64) (result i32)))
+                (func (type 1)
+                    i64.const 2
+                    local.set 1
+                    local.get 0
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_local_to_param_renumbers_locals() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func (local i32 f32)
+                    f32.const 1
+                    local.set 1
+                    local.get 0
+                    drop
+                )
+            )
+            "#,
+            LocalToParamMutator,
+            r#"
+            (module
+                (type (func))
+                (type (func (param f32)))
+                (func (type 1)
+                    (local i32)
+                    f32.const 1
+                    local.set 0
+                    local.get 1
+                    drop
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_local_to_param_multiple_groups() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func (local i32 i32 f32)
+                    i32.const 1
+                    local.set 1
+                    local.get 0
+                    drop
+                    f32.const 2
+                    local.set 2
+                )
+            )
+            "#,
+            LocalToParamMutator,
+            r#"
+            (module
+                (type (func))
+                (type (func (param i32)))
+                (func (type 1)
+                    (local i32 f32)
+                    i32.const 1
+                    local.set 0
+                    local.get 1
+                    drop
+                    f32.const 2
+                    local.set 2
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_local_to_param_requires_uncalled() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (func (export "f") (local i32))
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!LocalToParamMutator.can_mutate(&config));
+    }
+}
@@ -0,0 +1,246 @@
+//! Mutator that merges a `block` with a nested `block` of the same type.
+
+use super::{translate, DefaultTranslator, Mutator, Translator};
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use wasm_encoder::{CodeSection, Function, Instruction, Module};
+use wasmparser::{FunctionBody, Operator};
+
+/// Merges `block T (block T ... end) end` into a single `block T ... end`.
+///
+/// When the inner block is the sole content of the outer block, both blocks
+/// take and produce the same values and end at the same point, so branching
+/// to either of them is equivalent. Dropping the inner block then only
+/// requires renumbering the branches which target the outer block or any
+/// enclosing label.
+#[derive(Clone, Copy)]
+pub struct MergeBlocksMutator;
+
+/// Returns the operator indices of the `block` and `end` of every inner block
+/// which can be merged into its parent.
+fn mergeable_blocks(ops: &[Operator]) -> Vec<(usize, usize)> {
+    let mut ends = vec![None; ops.len()];
+    let mut frames = Vec::new();
+    for (i, op) in ops.iter().enumerate() {
+        match op {
+            Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Try { .. } => frames.push(i),
+            Operator::End | Operator::Delegate { .. } => {
+                if let Some(start) = frames.pop() {
+                    ends[start] = Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for i in 1..ops.len() {
+        let (outer_ty, inner_ty) = match (&ops[i - 1], &ops[i]) {
+            (Operator::Block { ty: outer }, Operator::Block { ty: inner }) => (outer, inner),
+            _ => continue,
+        };
+        let (outer_end, inner_end) = match (ends[i - 1], ends[i]) {
+            (Some(outer), Some(inner)) => (outer, inner),
+            _ => continue,
+        };
+        // `delegate` labels are resolved outside of their own `try`, so keep
+        // things simple and don't renumber them.
+        if outer_ty == inner_ty
+            && inner_end + 1 == outer_end
+            && !ops[i..inner_end]
+                .iter()
+                .any(|op| matches!(op, Operator::Delegate { .. }))
+        {
+            candidates.push((i, inner_end));
+        }
+    }
+    candidates
+}
+
+/// Translates `op`, found `depth` frames deep inside of a frame which is being
+/// removed, such that labels up to and including that frame keep their index
+/// while labels beyond it move one frame closer.
+pub(super) fn unnest_op(op: &Operator, depth: u32) -> Result<Instruction<'static>> {
+    let relabel = |label: u32| if label > depth { label - 1 } else { label };
+    Ok(match op {
+        Operator::Br { relative_depth } => Instruction::Br(relabel(*relative_depth)),
+        Operator::BrIf { relative_depth } => Instruction::BrIf(relabel(*relative_depth)),
+        Operator::Rethrow { relative_depth } => Instruction::Rethrow(relabel(*relative_depth)),
+        Operator::BrTable { table } => Instruction::BrTable(
+            table
+                .targets()
+                .map(|target| Ok(relabel(target?)))
+                .collect::<Result<Vec<_>>>()?
+                .into(),
+            relabel(table.default()),
+        ),
+        _ => DefaultTranslator.translate_op(op)?,
+    })
+}
+
+/// Re-encodes `body` without the `block` and `end` at the `inner_block` and
+/// `inner_end` operator indices.
+fn merge_block(body: &FunctionBody, inner_block: usize, inner_end: usize) -> Result<Function> {
+    let mut func = Function::new(translate::locals(&mut DefaultTranslator, body)?);
+
+    let mut reader = body.get_operators_reader()?;
+    reader.allow_memarg64(true);
+    // How many frames deep we are inside of the inner block.
+    let mut depth = 0;
+    for (i, op) in reader.into_iter().enumerate() {
+        let op = op?;
+        if i == inner_block || i == inner_end {
+            continue;
+        }
+        if i < inner_block || i > inner_end {
+            func.instruction(&DefaultTranslator.translate_op(&op)?);
+            continue;
+        }
+
+        match op {
+            Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Try { .. } => depth += 1,
+            Operator::End => depth -= 1,
+            _ => {}
+        }
+        func.instruction(&unnest_op(&op, depth)?);
+    }
+    Ok(func)
+}
+
+impl Mutator for MergeBlocksMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let code_section = config.info().get_code_section();
+        let mut bodies = Vec::new();
+        let mut candidates = Vec::new();
+        for (i, body) in config.info().code_bodies()?.into_iter().enumerate() {
+            config.consume_fuel(1)?;
+            let mut reader = body.get_operators_reader()?;
+            reader.allow_memarg64(true);
+            let ops = reader.into_iter().collect::<wasmparser::Result<Vec<_>>>()?;
+            for (inner_block, inner_end) in mergeable_blocks(&ops) {
+                candidates.push((i, inner_block, inner_end));
+            }
+            bodies.push(body);
+        }
+
+        let (function_to_mutate, inner_block, inner_end) = match candidates.choose(config.rng()) {
+            Some(candidate) => *candidate,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!(
+            "Merging the block at operator {} of function {} into its parent",
+            inner_block,
+            function_to_mutate
+        );
+
+        let mut codes = CodeSection::new();
+        for (i, body) in bodies.iter().enumerate() {
+            if i == function_to_mutate {
+                codes.function(&merge_block(body, inner_block, inner_end)?);
+            } else {
+                codes.raw(&code_section.data[body.range().start..body.range().end]);
+            }
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        config.info().has_nonempty_code()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MergeBlocksMutator;
+
+    #[test]
+    fn test_merge_empty_blocks() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func
+                    block
+                        block
+                            nop
+                        end
+                    end
+                )
+            )
+            "#,
+            MergeBlocksMutator,
+            r#"
+            (module
+                (func
+                    block
+                        nop
+                    end
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_merge_blocks_relabels_branches() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    block (result i32)
+                        block (result i32)
+                            i32.const 1
+                            local.get 0
+                            br_if 0
+                            loop
+                                i32.const 3
+                                local.get 0
+                                br_if 2
+                                drop
+                                br 0
+                            end
+                            drop
+                            i32.const 2
+                            br 1
+                        end
+                    end
+                )
+            )
+            "#,
+            MergeBlocksMutator,
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    block (result i32)
+                        i32.const 1
+                        local.get 0
+                        br_if 0
+                        loop
+                            i32.const 3
+                            local.get 0
+                            br_if 1
+                            drop
+                            br 0
+                        end
+                        drop
+                        i32.const 2
+                        br 0
+                    end
+                )
+            )
+            "#,
+        );
+    }
+}
@@ -1,13 +1,25 @@
 //! This mutator modifies the constant initializer expressions between various valid forms in
 //! entities which require constant initializers.
 
+use crate::mutators::global_to_accessors::global_types;
 use crate::mutators::translate::{self, InitExprKind, Item, Translator};
-use crate::{Error, Mutator, Result};
+use crate::{Error, Mutator, Result, WasmMutate};
 
-use rand::Rng;
+use rand::{seq::SliceRandom, Rng};
+use std::collections::HashSet;
 use wasm_encoder::{ElementSection, GlobalSection, Instruction};
-use wasmparser::{ElementSectionReader, GlobalSectionReader, InitExpr, Operator, Type};
+use wasmparser::{
+    ElementKind, ElementSectionReader, GlobalSectionReader, InitExpr, Operator, Type,
+};
 
+/// When not reducing, `ElementOffset` switches the offset of an active element
+/// segment between an `i32.const` and a `global.get` of an immutable imported
+/// `i32` global, while `Global` and `ElementFunc` replace an initializer with a
+/// different arbitrary constant of the same type.
+///
+/// Functions referenced by `ref.func` in a function body must be declared
+/// outside of the code section, so references to them in element segments and
+/// global initializers are never replaced.
 #[derive(Copy, Clone)]
 pub enum InitExpressionMutator {
     Global,
@@ -15,10 +27,68 @@ pub enum InitExpressionMutator {
     ElementFunc,
 }
 
+/// Returns the immutable imported `i32` globals which an element segment
+/// offset can read.
+fn offset_globals(config: &WasmMutate) -> Result<Vec<u32>> {
+    let num_imported = config.info().num_imported_globals() as usize;
+    Ok(global_types(config)?
+        .iter()
+        .take(num_imported)
+        .enumerate()
+        .filter(|(_, ty)| ty.content_type == Type::I32 && !ty.mutable)
+        .map(|(i, _)| i as u32)
+        .collect())
+}
+
+/// Returns whether the offset of some active element segment can be switched
+/// between a constant and a global.
+fn can_convert_element_offset(config: &WasmMutate) -> Result<bool> {
+    let section = match config.info().elements {
+        Some(section) => section,
+        None => return Ok(false),
+    };
+    let has_globals = !offset_globals(config)?.is_empty();
+    let mut reader = ElementSectionReader::new(config.info().raw_sections[section].data, 0)?;
+    for _ in 0..reader.get_count() {
+        if let ElementKind::Active { init_expr, .. } = reader.read()?.kind {
+            match init_expr.get_operators_reader().read()? {
+                Operator::I32Const { .. } if has_globals => return Ok(true),
+                Operator::GlobalGet { .. } => return Ok(true),
+                _ => {}
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Returns the functions referenced by `ref.func` in function bodies.
+fn ref_func_targets(config: &WasmMutate) -> Result<HashSet<u32>> {
+    let mut targets = HashSet::new();
+    if config.info().code.is_none() {
+        return Ok(targets);
+    }
+    for body in config.info().code_bodies()? {
+        config.consume_fuel(1)?;
+        let mut reader = body.get_operators_reader()?;
+        reader.allow_memarg64(true);
+        for op in reader {
+            if let Operator::RefFunc { function_index } = op? {
+                targets.insert(function_index);
+            }
+        }
+    }
+    Ok(targets)
+}
+
 struct InitTranslator<'cfg, 'wasm> {
     config: &'cfg mut crate::WasmMutate<'wasm>,
     skip_inits: u32,
     kind: InitExprKind,
+    /// Size of the module's function index space, used to pick replacement
+    /// function indices.
+    num_functions: u32,
+    /// Functions whose references must be kept, see `ref_func_targets`.
+    keep_functions: HashSet<u32>,
 }
 
 impl<'cfg, 'wasm> InitTranslator<'cfg, 'wasm> {
@@ -46,9 +116,17 @@ impl<'cfg, 'wasm> Translator for InitTranslator<'cfg, 'wasm> {
     fn remap(&mut self, item: Item, idx: u32) -> Result<u32> {
         Ok(match (self.kind, item) {
             (InitExprKind::ElementFunction, Item::Function) if self.should_process() => {
-                log::trace!("... replacing referenced function index with 0");
-                // FIXME: generate random function indices when `!config.reduce`.
-                0
+                if self.keep_functions.contains(&idx) {
+                    return Err(Error::no_mutations_applicable());
+                }
+                if self.config.reduce {
+                    log::trace!("... replacing referenced function index with 0");
+                    0
+                } else {
+                    let new_idx = self.config.rng().gen_range(0..self.num_functions);
+                    log::trace!("... replacing referenced function index with {}", new_idx);
+                    new_idx
+                }
             }
             _ => idx,
         })
@@ -69,6 +147,11 @@ impl<'cfg, 'wasm> Translator for InitTranslator<'cfg, 'wasm> {
         }
         let mut reader = e.get_operators_reader();
         let op = reader.read()?;
+        if let O::RefFunc { function_index } = op {
+            if self.keep_functions.contains(&function_index) {
+                return Err(Error::no_mutations_applicable());
+            }
+        }
         // Don't mutate further if the expressions are already their most reduced form.
         let is_simplest = match op {
             O::RefNull { .. } | O::I32Const { value: 0 | 1 } | O::I64Const { value: 0 | 1 } => true,
@@ -134,9 +217,69 @@ impl<'cfg, 'wasm> Translator for InitTranslator<'cfg, 'wasm> {
                 T::FuncRef => I::RefNull(wasm_encoder::ValType::FuncRef),
                 T::ExternRef => I::RefNull(wasm_encoder::ValType::ExternRef),
             }
+        } else if kind == InitExprKind::ElementOffset {
+            match op {
+                O::I32Const { .. } => {
+                    let globals = offset_globals(self.config)?;
+                    match globals.choose(self.config.rng()) {
+                        Some(global) => I::GlobalGet(*global),
+                        None => return Err(Error::no_mutations_applicable()),
+                    }
+                }
+                O::GlobalGet { .. } => I::I32Const(0),
+                _ => return Err(Error::no_mutations_applicable()),
+            }
         } else {
-            // FIXME: implement non-reducing mutations for constant expressions.
-            return Err(Error::no_mutations_applicable());
+            // Flip some bits of the original constant so the new one always
+            // differs from it.
+            let rng = self.config.rng();
+            match ty {
+                T::I32 => I::I32Const(match op {
+                    O::I32Const { value } => value ^ rng.gen_range(1..=u32::MAX) as i32,
+                    _ => rng.gen(),
+                }),
+                T::I64 => I::I64Const(match op {
+                    O::I64Const { value } => value ^ rng.gen_range(1..=u64::MAX) as i64,
+                    _ => rng.gen(),
+                }),
+                T::F32 => I::F32Const(f32::from_bits(match op {
+                    O::F32Const { value } => value.bits() ^ rng.gen_range(1..=u32::MAX),
+                    _ => rng.gen(),
+                })),
+                T::F64 => I::F64Const(f64::from_bits(match op {
+                    O::F64Const { value } => value.bits() ^ rng.gen_range(1..=u64::MAX),
+                    _ => rng.gen(),
+                })),
+                T::V128 => I::V128Const(match op {
+                    O::V128Const { value } => value.i128() ^ rng.gen_range(1..=u128::MAX) as i128,
+                    _ => rng.gen(),
+                }),
+                T::FuncRef => {
+                    let num_functions = self.config.info().num_functions();
+                    let function = match op {
+                        O::RefFunc { function_index } => Some(function_index),
+                        _ => None,
+                    };
+                    // Either pick another function or switch to a null
+                    // reference.
+                    let new_function = (0..num_functions)
+                        .filter(|f| Some(*f) != function)
+                        .collect::<Vec<_>>()
+                        .choose(self.config.rng())
+                        .copied();
+                    match (new_function, function) {
+                        (Some(f), None) => I::RefFunc(f),
+                        (Some(f), Some(_)) if self.config.rng().gen() => I::RefFunc(f),
+                        (_, Some(_)) => I::RefNull(wasm_encoder::ValType::FuncRef),
+                        (None, None) => return Err(Error::no_mutations_applicable()),
+                    }
+                }
+                // A null reference is the only constant `externref`.
+                T::ExternRef => match op {
+                    O::RefNull { .. } => return Err(Error::no_mutations_applicable()),
+                    _ => I::RefNull(wasm_encoder::ValType::ExternRef),
+                },
+            }
         };
 
         log::trace!("... replacing original expression with {:?}", new_op);
@@ -163,10 +306,14 @@ impl Mutator for InitExpressionMutator {
                 let mut new_section = GlobalSection::new();
                 let mut reader =
                     GlobalSectionReader::new(config.info().raw_sections[section].data, 0)?;
+                let num_functions = config.info().num_functions();
+                let keep_functions = ref_func_targets(config)?;
                 let mut translator = InitTranslator {
                     config,
                     skip_inits: 0,
                     kind: translator_kind,
+                    num_functions,
+                    keep_functions,
                 };
                 for idx in 0..reader.get_count() {
                     translator.config.consume_fuel(1)?;
@@ -191,10 +338,14 @@ impl Mutator for InitExpressionMutator {
                 let mut new_section = ElementSection::new();
                 let mut reader =
                     ElementSectionReader::new(config.info().raw_sections[section].data, 0)?;
+                let num_functions = config.info().num_functions();
+                let keep_functions = ref_func_targets(config)?;
                 let mut translator = InitTranslator {
                     config,
                     skip_inits: 0,
                     kind: translator_kind,
+                    num_functions,
+                    keep_functions,
                 };
                 for idx in 0..reader.get_count() {
                     translator.config.consume_fuel(1)?;
@@ -232,17 +383,21 @@ impl Mutator for InitExpressionMutator {
     }
 
     fn can_mutate(&self, config: &crate::WasmMutate) -> bool {
-        // the implementation here can only reduce for now,
-        // but could be extended to mutate arbitrarily.
-        if !config.reduce {
+        if config.preserve_semantics {
             return false;
         }
+        // Besides reducing, element offsets are only switched between
+        // constants and globals, since arbitrary offsets are likely out of
+        // bounds of the table.
+        if !config.reduce && matches!(self, Self::ElementOffset) {
+            return can_convert_element_offset(config).unwrap_or(false);
+        }
 
         let any_data = match self {
             Self::Global => config.info().num_local_globals() > 0,
             Self::ElementOffset | Self::ElementFunc => config.info().num_elements() > 0,
         };
-        !config.preserve_semantics && any_data
+        any_data
     }
 }
 
@@ -312,6 +467,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn elem_offset_to_global() {
+        crate::mutators::match_mutation(
+            r#"(module
+                (import "m" "g" (global i32))
+                (table 1 funcref)
+                (func $f)
+                (elem (offset (i32.const 0)) $f))"#,
+            super::InitExpressionMutator::ElementOffset,
+            r#"(module
+                (import "m" "g" (global i32))
+                (table 1 funcref)
+                (func $f)
+                (elem (offset (global.get 0)) $f))"#,
+        );
+    }
+
+    #[test]
+    fn elem_offset_requires_immutable_i32_import() {
+        use crate::Mutator;
+
+        let wasm = wat::parse_str(
+            r#"(module
+                (import "m" "g" (global (mut i32)))
+                (global i32 (i32.const 1))
+                (table 1 funcref)
+                (func $f)
+                (elem (offset (i32.const 0)) $f))"#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!super::InitExpressionMutator::ElementOffset.can_mutate(&config));
+    }
+
     #[test]
     fn reduce_elem_base() {
         match_reduction(
@@ -328,4 +518,153 @@ mod tests {
                 (elem (offset (i32.const 0)) $f))"#,
         );
     }
+
+    /// Applies `mutator` with `seed` without reducing and returns the
+    /// resulting module after checking that it's valid.
+    fn mutate_once(original: &str, mutator: super::InitExpressionMutator, seed: u64) -> Vec<u8> {
+        use crate::Mutator;
+
+        let wasm = wat::parse_str(original).unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.seed(seed);
+        config.setup(&wasm).unwrap();
+        assert!(mutator.can_mutate(&config));
+        let mutation = mutator
+            .mutate(&mut config)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .finish();
+        crate::validate(&mutation);
+        mutation
+    }
+
+    /// Returns the operator initializing the only global of `wasm`.
+    fn global_init(wasm: &[u8]) -> wasmparser::Operator<'_> {
+        for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+            if let wasmparser::Payload::GlobalSection(mut reader) = payload.unwrap() {
+                let global = reader.read().unwrap();
+                return global.init_expr.get_operators_reader().read().unwrap();
+            }
+        }
+        panic!("no global section")
+    }
+
+    #[test]
+    fn mutate_global_const() {
+        use wasmparser::Operator;
+
+        for seed in 0..10 {
+            let wasm = mutate_once(
+                "(module (global i32 (i32.const 42)))",
+                super::InitExpressionMutator::Global,
+                seed,
+            );
+            match global_init(&wasm) {
+                Operator::I32Const { value } => assert_ne!(value, 42),
+                op => panic!("unexpected initializer {:?}", op),
+            }
+
+            let wasm = mutate_once(
+                "(module (global f64 (f64.const 1.5)))",
+                super::InitExpressionMutator::Global,
+                seed,
+            );
+            match global_init(&wasm) {
+                Operator::F64Const { value } => assert_ne!(value.bits(), 1.5f64.to_bits()),
+                op => panic!("unexpected initializer {:?}", op),
+            }
+        }
+    }
+
+    #[test]
+    fn mutate_global_ref() {
+        crate::mutators::match_mutation(
+            "(module (global funcref (ref.null func)) (func))",
+            super::InitExpressionMutator::Global,
+            "(module (global funcref (ref.func 0)) (func))",
+        );
+        crate::mutators::match_mutation(
+            "(module (global funcref (ref.func 0)) (func))",
+            super::InitExpressionMutator::Global,
+            "(module (global funcref (ref.null func)) (func))",
+        );
+    }
+
+    #[test]
+    fn mutate_elem_func() {
+        crate::mutators::match_mutation(
+            r#"(module (table 0 funcref) (elem $a $a) (func $a) (func $b))"#,
+            super::InitExpressionMutator::ElementFunc,
+            r#"(module (table 0 funcref) (elem $a $b) (func $a) (func $b))"#,
+        );
+    }
+
+    #[test]
+    fn mutate_elem_func_random_index() {
+        use crate::Mutator;
+
+        let wasm = wat::parse_str(
+            r#"(module (table 0 funcref) (elem func $a) (func $a) (func $b) (func $c) (func $d))"#,
+        )
+        .unwrap();
+        let mut saw_nonzero = false;
+        for seed in 0..32 {
+            let mut config = crate::WasmMutate::default();
+            config.seed(seed);
+            config.setup(&wasm).unwrap();
+            let mutation = super::InitExpressionMutator::ElementFunc
+                .mutate(&mut config)
+                .unwrap()
+                .next()
+                .unwrap()
+                .unwrap()
+                .finish();
+            crate::validate(&mutation);
+            for payload in wasmparser::Parser::new(0).parse_all(&mutation) {
+                if let wasmparser::Payload::ElementSection(mut reader) = payload.unwrap() {
+                    let element = reader.read().unwrap();
+                    let mut items = element.items.get_items_reader().unwrap();
+                    match items.read().unwrap() {
+                        wasmparser::ElementItem::Func(idx) => {
+                            assert!(idx < 4);
+                            saw_nonzero |= idx != 0;
+                        }
+                        item => panic!("unexpected element item {:?}", item),
+                    }
+                }
+            }
+        }
+        assert!(saw_nonzero);
+    }
+
+    #[test]
+    fn mutate_elem_func_keeps_ref_func_declarations() {
+        use crate::Mutator;
+
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (elem declare func $a)
+                (global funcref (ref.func $a))
+                (func $a (result funcref) ref.func $a)
+                (func $b)
+            )
+            "#,
+        )
+        .unwrap();
+        for mutator in [
+            super::InitExpressionMutator::ElementFunc,
+            super::InitExpressionMutator::Global,
+        ] {
+            for reduce in [false, true] {
+                let mut config = crate::WasmMutate::default();
+                config.reduce(reduce);
+                config.setup(&wasm).unwrap();
+                assert!(mutator.can_mutate(&config));
+                assert!(mutator.mutate(&mut config).is_err());
+            }
+        }
+    }
 }
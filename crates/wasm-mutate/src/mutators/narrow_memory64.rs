@@ -0,0 +1,350 @@
+//! Mutator that turns a 64-bit memory into a 32-bit one.
+
+use super::{
+    swap_commutative_operands::subexpression_start, translate, DefaultTranslator, Mutator,
+    Translator,
+};
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use wasm_encoder::{
+    CodeSection, DataSection, DataSegment, DataSegmentMode, Function, Instruction, MemArg,
+    MemorySection, Module, SectionId,
+};
+use wasmparser::{
+    DataKind, DataSectionReader, FunctionBody, InitExpr, MemoryImmediate, MemorySectionReader,
+    Operator,
+};
+
+/// Rewrites a defined 64-bit memory whose limits fit in the 32-bit page range
+/// into a 32-bit memory, removing the dependency on the memory64 proposal.
+///
+/// Only memories whose addresses are trivially narrowable are handled: every
+/// active data segment offset and every address given to a plain load or store
+/// must be an `i64.const` below 2<sup>32</sup>, which is then turned into an
+/// `i32.const`. Any other use of the memory, such as `memory.size`, makes the
+/// mutation inapplicable.
+#[derive(Clone, Copy)]
+pub struct Narrow64MemoryMutator;
+
+/// Returns the offset of a data segment narrowed to 32 bits if it's a lone
+/// `i64.const` below 2<sup>32</sup>.
+fn narrow_offset(expr: &InitExpr<'_>) -> Result<Option<u32>> {
+    let mut reader = expr.get_operators_reader();
+    let value = match reader.read()? {
+        Operator::I64Const { value } => value,
+        _ => return Ok(None),
+    };
+    if !matches!(reader.read()?, Operator::End) || !reader.eof() {
+        return Ok(None);
+    }
+    Ok(u32::try_from(value).ok())
+}
+
+/// Translator recording the memory immediate of the translated operator, if
+/// any.
+#[derive(Default)]
+pub(super) struct MemargProbe(pub(super) Option<MemoryImmediate>);
+
+impl Translator for MemargProbe {
+    fn as_obj(&mut self) -> &mut dyn Translator {
+        self
+    }
+
+    fn translate_memarg(&mut self, arg: &MemoryImmediate) -> Result<MemArg> {
+        self.0 = Some(*arg);
+        translate::memarg(self, arg)
+    }
+}
+
+fn is_load(op: &Operator) -> bool {
+    use Operator::*;
+    matches!(
+        op,
+        I32Load { .. }
+            | I64Load { .. }
+            | F32Load { .. }
+            | F64Load { .. }
+            | I32Load8S { .. }
+            | I32Load8U { .. }
+            | I32Load16S { .. }
+            | I32Load16U { .. }
+            | I64Load8S { .. }
+            | I64Load8U { .. }
+            | I64Load16S { .. }
+            | I64Load16U { .. }
+            | I64Load32S { .. }
+            | I64Load32U { .. }
+            | V128Load { .. }
+    )
+}
+
+fn is_store(op: &Operator) -> bool {
+    use Operator::*;
+    matches!(
+        op,
+        I32Store { .. }
+            | I64Store { .. }
+            | F32Store { .. }
+            | F64Store { .. }
+            | I32Store8 { .. }
+            | I32Store16 { .. }
+            | I64Store8 { .. }
+            | I64Store16 { .. }
+            | I64Store32 { .. }
+            | V128Store { .. }
+    )
+}
+
+/// Returns whether `op` is an `i64.const` whose value is a valid 32-bit
+/// address.
+fn is_narrowable_address(op: &Operator) -> bool {
+    matches!(op, Operator::I64Const { value } if u32::try_from(*value).is_ok())
+}
+
+/// Returns the indices of the `i64.const` operators in `ops` which compute an
+/// address into `memory`, or `None` if `memory` is used in any other way.
+fn narrowable_addresses(ops: &[(Operator, usize)], memory: u32) -> Result<Option<Vec<usize>>> {
+    let mut addresses = Vec::new();
+    for (i, (op, _)) in ops.iter().enumerate() {
+        match op {
+            Operator::MemorySize { mem, .. }
+            | Operator::MemoryGrow { mem, .. }
+            | Operator::MemoryFill { mem }
+            | Operator::MemoryInit { mem, .. }
+                if *mem == memory =>
+            {
+                return Ok(None)
+            }
+            Operator::MemoryCopy { src, dst } if *src == memory || *dst == memory => {
+                return Ok(None)
+            }
+            _ => {}
+        }
+
+        let mut probe = MemargProbe::default();
+        probe.translate_op(op)?;
+        let memarg = match probe.0 {
+            Some(memarg) if memarg.memory == memory => memarg,
+            _ => continue,
+        };
+        if u32::try_from(memarg.offset).is_err() {
+            return Ok(None);
+        }
+        let address = if is_load(op) {
+            i.checked_sub(1)
+        } else if is_store(op) {
+            i.checked_sub(1)
+                .and_then(|value| subexpression_start(ops, value))
+                .and_then(|value| value.checked_sub(1))
+        } else {
+            None
+        };
+        match address {
+            Some(address) if is_narrowable_address(&ops[address].0) => addresses.push(address),
+            _ => return Ok(None),
+        }
+    }
+    Ok(Some(addresses))
+}
+
+fn narrow_body(
+    body: &FunctionBody,
+    ops: &[(Operator, usize)],
+    addresses: &[usize],
+) -> Result<Function> {
+    let addresses = addresses.iter().copied().collect::<HashSet<_>>();
+    let mut func = Function::new(translate::locals(&mut DefaultTranslator, body)?);
+    for (i, (op, _)) in ops.iter().enumerate() {
+        match op {
+            Operator::I64Const { value } if addresses.contains(&i) => {
+                func.instruction(&Instruction::I32Const(*value as u32 as i32))
+            }
+            op => func.instruction(&DefaultTranslator.translate_op(op)?),
+        };
+    }
+    Ok(func)
+}
+
+/// Returns the defined 64-bit memories whose limits fit a 32-bit memory.
+fn candidates(config: &WasmMutate) -> Vec<u32> {
+    let info = config.info();
+    let max_pages = 1 << 16;
+    (info.num_imported_memories()..info.num_memories())
+        .filter(|memory| {
+            let ty = &info.memory_types[*memory as usize];
+            ty.memory64
+                && !ty.shared
+                && ty.initial <= max_pages
+                && ty.maximum.map_or(true, |maximum| maximum <= max_pages)
+        })
+        .collect()
+}
+
+impl Mutator for Narrow64MemoryMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        config.consume_fuel(1)?;
+        let memory = match candidates(config).choose(config.rng()) {
+            Some(memory) => *memory,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!("Narrowing memory {} to 32 bits", memory);
+
+        let info = config.info();
+        let mut memories = MemorySection::new();
+        let reader = MemorySectionReader::new(info.raw_sections[info.memories.unwrap()].data, 0)?;
+        for (i, ty) in reader.into_iter().enumerate() {
+            let mut ty = DefaultTranslator.translate_memory_type(&ty?)?;
+            if i as u32 + info.num_imported_memories() == memory {
+                ty.memory64 = false;
+            }
+            memories.memory(ty);
+        }
+
+        let mut codes = CodeSection::new();
+        if info.code.is_some() {
+            for body in info.code_bodies()? {
+                config.consume_fuel(1)?;
+                let mut reader = body.get_operators_reader()?;
+                reader.allow_memarg64(true);
+                let ops = reader
+                    .into_iter_with_offsets()
+                    .collect::<wasmparser::Result<Vec<_>>>()?;
+                let addresses = match narrowable_addresses(&ops, memory)? {
+                    Some(addresses) => addresses,
+                    None => return Err(Error::no_mutations_applicable()),
+                };
+                codes.function(&narrow_body(&body, &ops, &addresses)?);
+            }
+        }
+
+        let mut data = DataSection::new();
+        if let Some(section) = info.data {
+            for segment in DataSectionReader::new(info.raw_sections[section].data, 0)? {
+                let segment = segment?;
+                match &segment.kind {
+                    DataKind::Active {
+                        memory_index,
+                        init_expr,
+                    } if *memory_index == memory => {
+                        let offset = match narrow_offset(init_expr)? {
+                            Some(offset) => Instruction::I32Const(offset as i32),
+                            None => return Err(Error::no_mutations_applicable()),
+                        };
+                        data.segment(DataSegment {
+                            mode: DataSegmentMode::Active {
+                                memory_index: memory,
+                                offset: &offset,
+                            },
+                            data: segment.data.iter().copied(),
+                        });
+                    }
+                    _ => DefaultTranslator.translate_data(segment, &mut data)?,
+                }
+            }
+        }
+
+        let mut ids = vec![SectionId::Memory];
+        if info.code.is_some() {
+            ids.push(SectionId::Code);
+        }
+        if info.data.is_some() {
+            ids.push(SectionId::Data);
+        }
+        let module = info.replace_or_insert_sections(&ids, |id, module| {
+            match id {
+                SectionId::Memory => module.section(&memories),
+                SectionId::Code => module.section(&codes),
+                _ => module.section(&data),
+            };
+        });
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        config.reduce && !candidates(config).is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Narrow64MemoryMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_narrow_memory64() {
+        crate::WasmMutate::default().reduce(true).match_mutation(
+            r#"
+            (module
+                (memory i64 1 2)
+                (func (param i32) (result i32)
+                    i64.const 8
+                    local.get 0
+                    i32.store offset=4
+                    i64.const 16
+                    i32.load
+                )
+                (data (i64.const 0) "abc")
+            )
+            "#,
+            Narrow64MemoryMutator,
+            r#"
+            (module
+                (memory 1 2)
+                (func (param i32) (result i32)
+                    i32.const 8
+                    local.get 0
+                    i32.store offset=4
+                    i32.const 16
+                    i32.load
+                )
+                (data (i32.const 0) "abc")
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_narrow_memory64_requires_constant_addresses() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory i64 1)
+                (func (param i64) (result i32)
+                    local.get 0
+                    i32.load
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.reduce(true);
+        config.setup(&wasm).unwrap();
+        assert!(Narrow64MemoryMutator.can_mutate(&config));
+        assert!(Narrow64MemoryMutator.mutate(&mut config).is_err());
+    }
+
+    #[test]
+    fn test_narrow_memory64_requires_constant_offsets() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory i64 1)
+                (data (offset i64.const 1 i64.const 2 i64.add) "abc")
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.reduce(true);
+        config.setup(&wasm).unwrap();
+        assert!(Narrow64MemoryMutator.can_mutate(&config));
+        assert!(Narrow64MemoryMutator.mutate(&mut config).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+//! Mutator that pads the LEB128 immediate of an integer constant.
+
+use super::Mutator;
+use crate::{Error, Result, WasmMutate};
+
+use rand::{seq::SliceRandom, Rng};
+use wasm_encoder::{CodeSection, Module};
+use wasmparser::{FunctionBody, Operator};
+
+/// Re-encodes the immediate of an `i32.const` or `i64.const` with a
+/// non-minimal LEB128, padding it with extra continuation bytes up to the
+/// maximum length allowed for its type.
+///
+/// The value is unchanged, but decoders insisting on minimal encodings reject
+/// the result, so this only runs when `allow_invalid` is set.
+#[derive(Clone, Copy)]
+pub struct NonMinimalLebMutator;
+
+/// Returns the offsets at which the immediate of each padable constant in
+/// `body` starts and ends, along with its value and maximum encoded length.
+fn constants(body: &FunctionBody) -> Result<Vec<(usize, usize, i64, usize)>> {
+    let mut reader = body.get_operators_reader()?;
+    reader.allow_memarg64(true);
+    let ops = reader
+        .into_iter_with_offsets()
+        .collect::<wasmparser::Result<Vec<_>>>()?;
+    let mut candidates = Vec::new();
+    for (i, (op, offset)) in ops.iter().enumerate() {
+        let (value, max_len) = match op {
+            Operator::I32Const { value } => (i64::from(*value), 5),
+            Operator::I64Const { value } => (*value, 10),
+            _ => continue,
+        };
+        // Skip the opcode; a constant is always followed by another operator.
+        let (start, end) = (offset + 1, ops[i + 1].1);
+        if end - start < max_len {
+            candidates.push((start, end, value, max_len));
+        }
+    }
+    Ok(candidates)
+}
+
+/// Encodes `value` as a signed LEB128 of exactly `len` bytes.
+fn padded_leb(value: i64, len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|i| {
+            let byte = (value >> (7 * i).min(63)) as u8 & 0x7f;
+            if i + 1 < len {
+                byte | 0x80
+            } else {
+                byte
+            }
+        })
+        .collect()
+}
+
+impl Mutator for NonMinimalLebMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let code_section = config.info().get_code_section();
+        let bodies = config.info().code_bodies()?;
+        let mut candidates = Vec::new();
+        for (i, body) in bodies.iter().enumerate() {
+            config.consume_fuel(1)?;
+            for constant in constants(body)? {
+                candidates.push((i, constant));
+            }
+        }
+
+        let (function_to_mutate, (start, end, value, max_len)) =
+            match candidates.choose(config.rng()) {
+                Some(candidate) => *candidate,
+                None => return Err(Error::no_mutations_applicable()),
+            };
+        let len = config.rng().gen_range(end - start + 1..=max_len);
+        log::trace!(
+            "Padding the constant {} at {} in function {} to {} bytes",
+            value,
+            start,
+            function_to_mutate,
+            len
+        );
+
+        let data = code_section.data;
+        let mut codes = CodeSection::new();
+        for (i, body) in bodies.iter().enumerate() {
+            let range = body.range();
+            if i != function_to_mutate {
+                codes.raw(&data[range.start..range.end]);
+                continue;
+            }
+            let mut body = Vec::with_capacity(range.end - range.start + len);
+            body.extend_from_slice(&data[range.start..start]);
+            body.extend(padded_leb(value, len));
+            body.extend_from_slice(&data[end..range.end]);
+            codes.raw(&body);
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        config.allow_invalid
+            && !config.reduce
+            && config.info().code_bodies().map_or(false, |bodies| {
+                bodies
+                    .iter()
+                    .any(|body| constants(body).map_or(false, |constants| !constants.is_empty()))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{padded_leb, NonMinimalLebMutator};
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_padded_leb() {
+        assert_eq!(padded_leb(1, 3), [0x81, 0x80, 0x00]);
+        assert_eq!(padded_leb(-1, 2), [0xff, 0x7f]);
+        assert_eq!(padded_leb(i64::MIN, 10).len(), 10);
+    }
+
+    #[test]
+    fn test_non_minimal_leb() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (func (result i32)
+                    i32.const -3
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.allow_invalid(true);
+        config.setup(&wasm).unwrap();
+        assert!(NonMinimalLebMutator.can_mutate(&config));
+        let mutation = NonMinimalLebMutator
+            .mutate(&mut config)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .finish();
+        assert!(mutation.len() > wasm.len());
+
+        // The padded constant still decodes to the same value.
+        let mut validator = wasmparser::Validator::new();
+        validator.validate_all(&mutation).unwrap();
+        for payload in wasmparser::Parser::new(0).parse_all(&mutation) {
+            if let wasmparser::Payload::CodeSectionEntry(body) = payload.unwrap() {
+                let ops = body
+                    .get_operators_reader()
+                    .unwrap()
+                    .into_iter()
+                    .collect::<wasmparser::Result<Vec<_>>>()
+                    .unwrap();
+                assert!(matches!(
+                    ops[..],
+                    [
+                        wasmparser::Operator::I32Const { value: -3 },
+                        wasmparser::Operator::End
+                    ]
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_non_minimal_leb_requires_allow_invalid() {
+        let wasm = wat::parse_str("(module (func (result i64) i64.const 1))").unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!NonMinimalLebMutator.can_mutate(&config));
+    }
+}
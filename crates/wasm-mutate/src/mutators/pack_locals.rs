@@ -0,0 +1,284 @@
+//! Mutator that packs two `i32` locals into a single `i64` local.
+
+use super::{DefaultTranslator, Mutator, Translator};
+use crate::module::{map_type, TypeInfo};
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use wasm_encoder::{CodeSection, Function, Instruction, Module, ValType};
+use wasmparser::{FunctionBody, Operator};
+
+/// Replaces two declared `i32` locals of a function with a new `i64` local,
+/// the first one living in its low 32 bits and the second one in its high 32
+/// bits.
+///
+/// Every `local.get` of either local extracts its half with shifts and
+/// `i32.wrap_i64`, and every `local.set` or `local.tee` masks the new value
+/// into its half. The new local is declared after all the others, so the
+/// locals declared after the packed ones are renumbered.
+///
+/// Since the two locals now alias the halves of a single local, it only runs
+/// when semantics don't have to be preserved.
+#[derive(Clone, Copy)]
+pub struct PackLocalsMutator;
+
+/// Returns the indices of the declared `i32` locals of `body`, counting the
+/// `num_params` parameters first.
+fn i32_locals(body: &FunctionBody, num_params: u32) -> Result<Vec<u32>> {
+    let mut locals = Vec::new();
+    let mut index = num_params;
+    for group in body.get_locals_reader()? {
+        let (count, ty) = group?;
+        if ty == wasmparser::Type::I32 {
+            locals.extend(index..index + count);
+        }
+        index += count;
+    }
+    Ok(locals)
+}
+
+fn num_params(config: &WasmMutate, function: u32) -> u32 {
+    match config.info().get_functype_idx(function) {
+        TypeInfo::Func(ty) => ty.params.len() as u32,
+    }
+}
+
+impl Mutator for PackLocalsMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let first_defined = config.info().num_imported_functions();
+        let code_section = config.info().get_code_section();
+        let bodies = config.info().code_bodies()?;
+        let mut candidates = Vec::new();
+        for (i, body) in bodies.iter().enumerate() {
+            config.consume_fuel(1)?;
+            let locals = i32_locals(body, num_params(config, first_defined + i as u32))?;
+            if locals.len() >= 2 {
+                candidates.push((i, locals));
+            }
+        }
+
+        let (function_to_mutate, locals) = match candidates.choose(config.rng()) {
+            Some(candidate) => candidate.clone(),
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        let mut packed = locals
+            .choose_multiple(config.rng(), 2)
+            .copied()
+            .collect::<Vec<_>>();
+        packed.sort_unstable();
+        let (low, high) = (packed[0], packed[1]);
+        log::trace!(
+            "Packing locals {} and {} of function {} into an i64 local",
+            low,
+            high,
+            first_defined + function_to_mutate as u32
+        );
+
+        let mut codes = CodeSection::new();
+        for (i, body) in bodies.iter().enumerate() {
+            if i != function_to_mutate {
+                codes.raw(&code_section.data[body.range().start..body.range().end]);
+                continue;
+            }
+
+            let num_params = num_params(config, first_defined + i as u32);
+            let mut types = Vec::new();
+            for group in body.get_locals_reader()? {
+                let (count, ty) = group?;
+                let ty = map_type(ty)?;
+                types.extend((0..count).map(|_| ty));
+            }
+            types.remove((high - num_params) as usize);
+            types.remove((low - num_params) as usize);
+            types.push(ValType::I64);
+            let mut locals: Vec<(u32, ValType)> = Vec::new();
+            for ty in types.iter() {
+                match locals.last_mut() {
+                    Some((count, last)) if last == ty => *count += 1,
+                    _ => locals.push((1, *ty)),
+                }
+            }
+            let pair = num_params + types.len() as u32 - 1;
+            let renumber = |index: u32| index - (index > low) as u32 - (index > high) as u32;
+
+            let mut func = Function::new(locals);
+            // Pushes the `i32` stored in the given half of the packed local.
+            let get = |func: &mut Function, high_half: bool| {
+                func.instruction(&Instruction::LocalGet(pair));
+                if high_half {
+                    func.instruction(&Instruction::I64Const(32));
+                    func.instruction(&Instruction::I64ShrU);
+                }
+                func.instruction(&Instruction::I32WrapI64);
+            };
+            // Pops an `i32` and stores it in the given half of the packed local.
+            let set = |func: &mut Function, high_half: bool| {
+                func.instruction(&Instruction::I64ExtendI32U);
+                if high_half {
+                    func.instruction(&Instruction::I64Const(32));
+                    func.instruction(&Instruction::I64Shl);
+                }
+                func.instruction(&Instruction::LocalGet(pair));
+                func.instruction(&Instruction::I64Const(if high_half {
+                    0xffff_ffff
+                } else {
+                    0xffff_ffff_0000_0000_u64 as i64
+                }));
+                func.instruction(&Instruction::I64And);
+                func.instruction(&Instruction::I64Or);
+                func.instruction(&Instruction::LocalSet(pair));
+            };
+
+            let mut reader = body.get_operators_reader()?;
+            reader.allow_memarg64(true);
+            for op in reader {
+                match op? {
+                    Operator::LocalGet { local_index } if local_index == low => {
+                        get(&mut func, false)
+                    }
+                    Operator::LocalGet { local_index } if local_index == high => {
+                        get(&mut func, true)
+                    }
+                    Operator::LocalSet { local_index } if local_index == low => {
+                        set(&mut func, false)
+                    }
+                    Operator::LocalSet { local_index } if local_index == high => {
+                        set(&mut func, true)
+                    }
+                    Operator::LocalTee { local_index } if local_index == low => {
+                        set(&mut func, false);
+                        get(&mut func, false);
+                    }
+                    Operator::LocalTee { local_index } if local_index == high => {
+                        set(&mut func, true);
+                        get(&mut func, true);
+                    }
+                    Operator::LocalGet { local_index } => {
+                        func.instruction(&Instruction::LocalGet(renumber(local_index)));
+                    }
+                    Operator::LocalSet { local_index } => {
+                        func.instruction(&Instruction::LocalSet(renumber(local_index)));
+                    }
+                    Operator::LocalTee { local_index } => {
+                        func.instruction(&Instruction::LocalTee(renumber(local_index)));
+                    }
+                    op => {
+                        func.instruction(&DefaultTranslator.translate_op(&op)?);
+                    }
+                }
+            }
+            codes.function(&func);
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        let first_defined = config.info().num_imported_functions();
+        !config.preserve_semantics
+            && !config.reduce
+            && config.info().code_bodies().map_or(false, |bodies| {
+                bodies.iter().enumerate().any(|(i, body)| {
+                    i32_locals(body, num_params(config, first_defined + i as u32))
+                        .map_or(false, |locals| locals.len() >= 2)
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PackLocalsMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_pack_locals() {
+        let original = r#"
+            (module
+                (func (param i32) (result i32)
+                    (local i32 f32 i32)
+                    local.get 0
+                    local.set 1
+                    f32.const 1
+                    local.set 2
+                    i32.const 7
+                    local.tee 3
+                    local.get 1
+                    i32.add
+                )
+            )
+        "#;
+        crate::mutators::match_mutation(
+            original,
+            PackLocalsMutator,
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    (local f32 i64)
+                    local.get 0
+                    i64.extend_i32_u
+                    local.get 2
+                    i64.const 0xffffffff00000000
+                    i64.and
+                    i64.or
+                    local.set 2
+                    f32.const 1
+                    local.set 1
+                    i32.const 7
+                    i64.extend_i32_u
+                    i64.const 32
+                    i64.shl
+                    local.get 2
+                    i64.const 0xffffffff
+                    i64.and
+                    i64.or
+                    local.set 2
+                    local.get 2
+                    i64.const 32
+                    i64.shr_u
+                    i32.wrap_i64
+                    local.get 2
+                    i32.wrap_i64
+                    i32.add
+                )
+            )
+            "#,
+        );
+
+        let wasm = wat::parse_str(original).unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        let mutation = PackLocalsMutator
+            .mutate(&mut config)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .finish();
+        wasmparser::Validator::new()
+            .validate_all(&mutation)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_pack_locals_requires_two_i32_locals() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (func (param i32)
+                    (local i32 i64)
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!PackLocalsMutator.can_mutate(&config));
+    }
+}
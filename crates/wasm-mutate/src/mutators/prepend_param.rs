@@ -0,0 +1,333 @@
+//! Mutator that prepends a parameter to a function and updates its callers.
+
+use super::{
+    global_to_accessors::find_or_add_type, translate, DefaultTranslator, Item, Mutator, Translator,
+};
+use crate::module::{map_primitive_type, PrimitiveTypeInfo, TypeInfo};
+use crate::{Error, Result, WasmMutate};
+
+use rand::{seq::SliceRandom, Rng};
+use std::collections::HashSet;
+use wasm_encoder::{
+    CodeSection, ElementSection, Function, FunctionSection, GlobalSection, Instruction, Module,
+    SectionId, TypeSection, ValType,
+};
+use wasmparser::{
+    ElementSectionReader, ExportSectionReader, ExternalKind, FunctionBody, GlobalSectionReader,
+    Operator, TypeSectionReader,
+};
+
+/// Prepends a new parameter to the type of a function, shifting the indices
+/// of its locals up by one, and passes a zero constant for it at every call
+/// site.
+///
+/// Since the new argument has to be pushed before the existing ones, callers
+/// stash the existing arguments in fresh locals around each call. Only
+/// functions which are solely used by direct `call`s are candidates, as other
+/// references such as exports or tables can't be fixed up.
+#[derive(Clone, Copy)]
+pub struct PrependParamMutator;
+
+/// Translator recording every function referenced outside of direct calls.
+#[derive(Default)]
+struct ReferencedFunctions(HashSet<u32>);
+
+impl Translator for ReferencedFunctions {
+    fn as_obj(&mut self) -> &mut dyn Translator {
+        self
+    }
+
+    fn remap(&mut self, item: Item, idx: u32) -> Result<u32> {
+        if let Item::Function = item {
+            self.0.insert(idx);
+        }
+        Ok(idx)
+    }
+}
+
+/// Returns the functions which are referenced in some way other than a direct
+/// call, such as by exports, the start section, tables, `ref.func` or
+/// `return_call`.
+pub(super) fn indirectly_referenced_functions(config: &WasmMutate) -> Result<HashSet<u32>> {
+    let info = config.info();
+    let mut referenced = ReferencedFunctions::default();
+    if let Some(start) = info.start_function() {
+        referenced.0.insert(start);
+    }
+    if let Some(section) = info.exports {
+        for export in ExportSectionReader::new(info.raw_sections[section].data, 0)? {
+            let export = export?;
+            if let ExternalKind::Func = export.kind {
+                referenced.0.insert(export.index);
+            }
+        }
+    }
+    if let Some(section) = info.globals {
+        for global in GlobalSectionReader::new(info.raw_sections[section].data, 0)? {
+            referenced.translate_global(global?, &mut GlobalSection::new())?;
+        }
+    }
+    if let Some(section) = info.elements {
+        for element in ElementSectionReader::new(info.raw_sections[section].data, 0)? {
+            referenced.translate_element(element?, &mut ElementSection::new())?;
+        }
+    }
+    // Tail calls can't be re-encoded, so their callees are treated as
+    // referenced as well.
+    for body in info.code_bodies()? {
+        let mut reader = body.get_operators_reader()?;
+        reader.allow_memarg64(true);
+        for op in reader {
+            match op? {
+                Operator::RefFunc { function_index } | Operator::ReturnCall { function_index } => {
+                    referenced.0.insert(function_index);
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(referenced.0)
+}
+
+/// Returns the defined functions which are only referenced by direct calls.
+fn candidates(config: &WasmMutate) -> Result<Vec<u32>> {
+    let info = config.info();
+    let referenced = indirectly_referenced_functions(config)?;
+    Ok((info.num_imported_functions()..info.num_functions())
+        .filter(|f| !referenced.contains(f))
+        .collect())
+}
+
+/// Re-encodes `body`, shifting its local indices by `shift` and passing
+/// `param` as an extra first argument to every call to `callee`.
+fn rewrite_body(
+    body: &FunctionBody,
+    num_params: u32,
+    shift: u32,
+    callee: u32,
+    callee_params: &[ValType],
+    param: &Instruction,
+) -> Result<Function> {
+    let mut locals = translate::locals(&mut DefaultTranslator, body)?;
+    let num_locals = num_params + locals.iter().map(|(count, _)| count).sum::<u32>();
+    let mut reader = body.get_operators_reader()?;
+    reader.allow_memarg64(true);
+    let ops = reader.into_iter().collect::<wasmparser::Result<Vec<_>>>()?;
+
+    // The locals stashing the arguments of calls to `callee` come after all
+    // existing locals.
+    let stash = num_locals + shift;
+    if ops
+        .iter()
+        .any(|op| matches!(op, Operator::Call { function_index } if *function_index == callee))
+    {
+        locals.extend(callee_params.iter().map(|ty| (1, *ty)));
+    }
+
+    let mut func = Function::new(locals);
+    for op in &ops {
+        match op {
+            Operator::LocalGet { local_index } => {
+                func.instruction(&Instruction::LocalGet(local_index + shift))
+            }
+            Operator::LocalSet { local_index } => {
+                func.instruction(&Instruction::LocalSet(local_index + shift))
+            }
+            Operator::LocalTee { local_index } => {
+                func.instruction(&Instruction::LocalTee(local_index + shift))
+            }
+            Operator::Call { function_index } if *function_index == callee => {
+                for i in (0..callee_params.len() as u32).rev() {
+                    func.instruction(&Instruction::LocalSet(stash + i));
+                }
+                func.instruction(param);
+                for i in 0..callee_params.len() as u32 {
+                    func.instruction(&Instruction::LocalGet(stash + i));
+                }
+                func.instruction(&Instruction::Call(callee))
+            }
+            op => func.instruction(&DefaultTranslator.translate_op(op)?),
+        };
+    }
+    Ok(func)
+}
+
+impl Mutator for PrependParamMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        config.consume_fuel(1)?;
+        let candidates = candidates(config)?;
+        let callee = match candidates.choose(config.rng()) {
+            Some(callee) => *callee,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        let (param_ty, param) = match config.rng().gen_range(0..4) {
+            0 => (PrimitiveTypeInfo::I32, Instruction::I32Const(0)),
+            1 => (PrimitiveTypeInfo::I64, Instruction::I64Const(0)),
+            2 => (PrimitiveTypeInfo::F32, Instruction::F32Const(0.0)),
+            _ => (PrimitiveTypeInfo::F64, Instruction::F64Const(0.0)),
+        };
+        log::trace!(
+            "Prepending a {:?} parameter to function {}",
+            param_ty,
+            callee
+        );
+
+        let info = config.info();
+        let callee_ty = match info.get_functype_idx(callee) {
+            TypeInfo::Func(ty) => ty.clone(),
+        };
+        let callee_params = callee_ty
+            .params
+            .iter()
+            .map(map_primitive_type)
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut types = TypeSection::new();
+        if let Some(section) = info.get_type_section() {
+            let mut reader = TypeSectionReader::new(section.data, 0)?;
+            for _ in 0..reader.get_count() {
+                translate::type_def(&mut DefaultTranslator, reader.read()?, &mut types)?;
+            }
+        }
+        let mut params = vec![param_ty];
+        params.extend(callee_ty.params.iter().cloned());
+        let new_ty = find_or_add_type(
+            config,
+            &mut types,
+            &mut Vec::new(),
+            params,
+            callee_ty.returns.clone(),
+        )?;
+
+        let mut functions = FunctionSection::new();
+        let first_defined = info.num_imported_functions();
+        for (i, ty) in info
+            .function_map
+            .iter()
+            .enumerate()
+            .skip(first_defined as usize)
+        {
+            functions.function(if i as u32 == callee { new_ty } else { *ty });
+        }
+
+        let mut codes = CodeSection::new();
+        for (i, body) in info.code_bodies()?.iter().enumerate() {
+            let function = first_defined + i as u32;
+            let num_params = match info.get_functype_idx(function) {
+                TypeInfo::Func(ty) => ty.params.len() as u32,
+            };
+            let shift = (function == callee) as u32;
+            codes.function(&rewrite_body(
+                body,
+                num_params,
+                shift,
+                callee,
+                &callee_params,
+                &param,
+            )?);
+        }
+
+        let module = info.replace_or_insert_sections(
+            &[SectionId::Type, SectionId::Function, SectionId::Code],
+            |id, module| {
+                match id {
+                    SectionId::Type => module.section(&types),
+                    SectionId::Function => module.section(&functions),
+                    _ => module.section(&codes),
+                };
+            },
+        );
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.reduce && candidates(config).map_or(false, |candidates| !candidates.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PrependParamMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_prepend_param() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func (param i32 i64) (result i32)
+                    (local f32)
+                    local.get 0
+                    local.get 1
+                    i32.wrap_i64
+                    i32.add
+                )
+                (func (export "f") (param i32) (result i32)
+                    local.get 0
+                    i64.const 2
+                    call 0
+                )
+            )
+            "#,
+            PrependParamMutator,
+            r#"
+            (module
+                (type (func (param i32 i64) (result i32)))
+                (type (func (param i32) (result i32)))
+                (type (func (param f64 i32 i64) (result i32)))
+                (func (type 2)
+                    (local f32)
+                    local.get 1
+                    local.get 2
+                    i32.wrap_i64
+                    i32.add
+                )
+                (func (type 1)
+                    (local i32 i64)
+                    local.get 0
+                    i64.const 2
+                    local.set 2
+                    local.set 1
+                    f64.const 0
+                    local.get 1
+                    local.get 2
+                    call 0
+                )
+                (export "f" (func 1))
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_prepend_param_requires_direct_calls_only() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (table 1 funcref)
+                (elem (i32.const 0) 0)
+                (func (export "f"))
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!PrependParamMutator.can_mutate(&config));
+
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (func)
+                (func (export "f") return_call 0)
+            )
+            "#,
+        )
+        .unwrap();
+        config.setup(&wasm).unwrap();
+        assert!(!PrependParamMutator.can_mutate(&config));
+    }
+}
@@ -0,0 +1,222 @@
+//! Mutator that removes stale entries from the `name` section.
+
+use super::Mutator;
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use wasm_encoder::{IndirectNameMap, Module, NameMap, NameSection, SectionId};
+use wasmparser::{BinaryReader, IndirectNamingReader, Name, NameSectionReader, NamingReader};
+
+/// Removes the entries of a `name` section which refer to items that no longer
+/// exist, such as functions removed by other reductions, and re-encodes the
+/// section.
+///
+/// Unknown subsections are dropped too, since their entries can't be checked.
+/// Names have no effect on the semantics of the module, so this runs
+/// regardless of `preserve_semantics`, but only when reducing.
+#[derive(Clone, Copy)]
+pub struct PruneNameSectionMutator;
+
+/// Copies the entries of `names` whose index is below `count` into a new map,
+/// returning it along with whether any entry was dropped.
+fn prune_map(mut names: NamingReader, count: u32) -> Result<(NameMap, bool)> {
+    let mut map = NameMap::new();
+    let mut pruned = false;
+    for _ in 0..names.get_count() {
+        let naming = names.read()?;
+        if naming.index < count {
+            map.append(naming.index, naming.name);
+        } else {
+            pruned = true;
+        }
+    }
+    Ok((map, pruned))
+}
+
+/// Like [`prune_map`], but for maps naming the locals or labels of each
+/// function, which are dropped along with their function.
+fn prune_indirect_map(
+    mut names: IndirectNamingReader,
+    functions: u32,
+) -> Result<(IndirectNameMap, bool)> {
+    let mut map = IndirectNameMap::new();
+    let mut pruned = false;
+    for _ in 0..names.get_indirect_count() {
+        let naming = names.read()?;
+        if naming.indirect_index < functions {
+            map.append(
+                naming.indirect_index,
+                &prune_map(naming.get_map()?, u32::MAX)?.0,
+            );
+        } else {
+            pruned = true;
+        }
+    }
+    Ok((map, pruned))
+}
+
+/// Re-encodes the `name` section in `data` without its stale entries, or
+/// returns `None` if it has none.
+fn prune(config: &WasmMutate, data: &[u8]) -> Result<Option<NameSection>> {
+    let info = config.info();
+    let mut reader = BinaryReader::new(data);
+    if reader.read_string()? != "name" {
+        return Ok(None);
+    }
+    let start = reader.current_position();
+
+    let mut section = NameSection::new();
+    let mut pruned = false;
+    for name in NameSectionReader::new(&data[start..], start)? {
+        pruned |= match name? {
+            Name::Module(name) => {
+                section.module(name.get_name()?);
+                false
+            }
+            Name::Function(names) => {
+                let (map, stale) = prune_map(names.get_map()?, info.num_functions())?;
+                section.functions(&map);
+                stale
+            }
+            Name::Local(names) => {
+                let (map, stale) =
+                    prune_indirect_map(names.get_indirect_map()?, info.num_functions())?;
+                section.locals(&map);
+                stale
+            }
+            Name::Label(names) => {
+                let (map, stale) =
+                    prune_indirect_map(names.get_indirect_map()?, info.num_functions())?;
+                section.labels(&map);
+                stale
+            }
+            Name::Type(names) => {
+                let (map, stale) = prune_map(names.get_map()?, info.num_types())?;
+                section.types(&map);
+                stale
+            }
+            Name::Table(names) => {
+                let (map, stale) = prune_map(names.get_map()?, info.num_tables())?;
+                section.tables(&map);
+                stale
+            }
+            Name::Memory(names) => {
+                let (map, stale) = prune_map(names.get_map()?, info.num_memories())?;
+                section.memories(&map);
+                stale
+            }
+            Name::Global(names) => {
+                let (map, stale) = prune_map(names.get_map()?, info.num_globals())?;
+                section.globals(&map);
+                stale
+            }
+            Name::Element(names) => {
+                let (map, stale) = prune_map(names.get_map()?, info.num_elements())?;
+                section.elements(&map);
+                stale
+            }
+            Name::Data(names) => {
+                let (map, stale) = prune_map(names.get_map()?, info.num_data())?;
+                section.data(&map);
+                stale
+            }
+            Name::Unknown { .. } => true,
+        };
+    }
+    Ok(if pruned { Some(section) } else { None })
+}
+
+/// Returns the index of every `name` section with stale entries, along with
+/// its pruned replacement.
+fn candidates(config: &WasmMutate) -> Result<Vec<(usize, NameSection)>> {
+    let mut candidates = Vec::new();
+    for (i, section) in config.info().raw_sections.iter().enumerate() {
+        if section.id != SectionId::Custom as u8 {
+            continue;
+        }
+        if let Some(pruned) = prune(config, section.data)? {
+            candidates.push((i, pruned));
+        }
+    }
+    Ok(candidates)
+}
+
+impl Mutator for PruneNameSectionMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        config.consume_fuel(1)?;
+        let candidates = candidates(config)?;
+        let (section, pruned) = match candidates.choose(config.rng()) {
+            Some(candidate) => candidate,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!("Pruning stale entries of the name section {}", section);
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(*section, pruned)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        config.reduce && candidates(config).map_or(false, |candidates| !candidates.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PruneNameSectionMutator;
+    use crate::mutators::Mutator;
+    use wasm_encoder::{
+        CodeSection, Function, FunctionSection, Instruction, Module, NameMap, NameSection,
+        TypeSection,
+    };
+
+    #[test]
+    fn test_prune_name_section() {
+        // A module with a single function whose name section still names a
+        // second, removed, function.
+        let mut types = TypeSection::new();
+        types.function([], []);
+        let mut functions = FunctionSection::new();
+        functions.function(0);
+        let mut codes = CodeSection::new();
+        let mut func = Function::new([]);
+        func.instruction(&Instruction::End);
+        codes.function(&func);
+        let mut function_names = NameMap::new();
+        function_names.append(0, "kept");
+        function_names.append(1, "removed");
+        let mut names = NameSection::new();
+        names.functions(&function_names);
+        let mut module = Module::new();
+        module
+            .section(&types)
+            .section(&functions)
+            .section(&codes)
+            .section(&names);
+        let wasm = module.finish();
+
+        let mut config = crate::WasmMutate::default();
+        config.reduce(true);
+        config.setup(&wasm).unwrap();
+        assert!(PruneNameSectionMutator.can_mutate(&config));
+        let mutation = PruneNameSectionMutator
+            .mutate(&mut config)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .finish();
+        assert_eq!(
+            wasmprinter::print_bytes(&mutation).unwrap(),
+            wasmprinter::print_bytes(wat::parse_str("(module (func $kept))").unwrap()).unwrap()
+        );
+
+        // Nothing is left to prune afterwards.
+        let mut config = crate::WasmMutate::default();
+        config.reduce(true);
+        config.setup(&mutation).unwrap();
+        assert!(!PruneNameSectionMutator.can_mutate(&config));
+    }
+}
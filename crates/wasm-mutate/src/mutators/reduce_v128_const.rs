@@ -0,0 +1,176 @@
+//! Mutator that reduces `v128.const` operators in function bodies.
+
+use super::Mutator;
+use crate::{Error, Result, WasmMutate};
+
+use rand::{seq::SliceRandom, Rng};
+use std::ops::Range;
+use wasm_encoder::{CodeSection, Module};
+use wasmparser::{FunctionBody, Operator};
+
+/// Reduces the value of a non-zero `v128.const` in a function body, either to
+/// zero or by halving each of its 32-bit lanes.
+///
+/// This is the code body counterpart of the `v128.const` handling of
+/// [`InitExpressionMutator`](super::modify_init_exprs::InitExpressionMutator).
+#[derive(Clone, Copy)]
+pub struct ReduceV128ConstMutator;
+
+/// Returns the byte ranges, relative to the code section, of the immediates
+/// of every non-zero `v128.const` in `body`.
+fn nonzero_constants(body: &FunctionBody) -> Result<Vec<(Range<usize>, i128)>> {
+    let mut constants = Vec::new();
+    let mut ops = body.get_operators_reader()?;
+    ops.allow_memarg64(true);
+    let mut ops = ops.into_iter_with_offsets().peekable();
+    while let Some(op) = ops.next() {
+        let value = match op?.0 {
+            Operator::V128Const { value } if value.i128() != 0 => value.i128(),
+            _ => continue,
+        };
+        // The immediate is the last 16 bytes of the instruction, which ends
+        // where the next operator starts. Every body ends with an `end`, so
+        // there always is a next operator.
+        let end = match ops.peek() {
+            Some(Ok((_, offset))) => *offset,
+            _ => continue,
+        };
+        constants.push((end - 16..end, value));
+    }
+    Ok(constants)
+}
+
+/// Halves each 32-bit lane of `value`.
+fn halve_lanes(value: i128) -> i128 {
+    let mut bytes = value.to_le_bytes();
+    for lane in bytes.chunks_mut(4) {
+        let halved = u32::from_le_bytes([lane[0], lane[1], lane[2], lane[3]]) / 2;
+        lane.copy_from_slice(&halved.to_le_bytes());
+    }
+    i128::from_le_bytes(bytes)
+}
+
+impl Mutator for ReduceV128ConstMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let code_section = config.info().get_code_section();
+        let bodies = config.info().code_bodies()?;
+        let mut candidates = Vec::new();
+        for (i, body) in bodies.iter().enumerate() {
+            config.consume_fuel(1)?;
+            for constant in nonzero_constants(body)? {
+                candidates.push((i, constant));
+            }
+        }
+
+        let (function_to_mutate, (immediate, value)) = match candidates.choose(config.rng()) {
+            Some(candidate) => candidate.clone(),
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        // Like for init expressions, give a 25% chance to go straight to zero.
+        let new_value = if config.rng().gen::<u8>() & 0b11 == 0 {
+            0
+        } else {
+            halve_lanes(value)
+        };
+        log::trace!(
+            "Reducing v128.const {:#x} to {:#x} in function {}",
+            value,
+            new_value,
+            function_to_mutate
+        );
+
+        let data = code_section.data;
+        let mut codes = CodeSection::new();
+        for (i, body) in bodies.iter().enumerate() {
+            let range = body.range();
+            if i != function_to_mutate {
+                codes.raw(&data[range.start..range.end]);
+                continue;
+            }
+            let mut body = data[range.start..range.end].to_vec();
+            let immediate = immediate.start - range.start..immediate.end - range.start;
+            body[immediate].copy_from_slice(&new_value.to_le_bytes());
+            codes.raw(&body);
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        config.reduce
+            && !config.preserve_semantics
+            && config.info().code_bodies().map_or(false, |bodies| {
+                bodies
+                    .iter()
+                    .any(|body| nonzero_constants(body).map_or(false, |c| !c.is_empty()))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReduceV128ConstMutator;
+    use crate::mutators::Mutator;
+
+    fn match_reduction(original: &str, expected: &str) {
+        crate::WasmMutate::default().reduce(true).match_mutation(
+            original,
+            ReduceV128ConstMutator,
+            expected,
+        )
+    }
+
+    #[test]
+    fn test_reduce_v128_const_to_zero() {
+        match_reduction(
+            r#"
+            (module
+                (func (result v128)
+                    v128.const i32x4 4 8 0 3
+                )
+            )
+            "#,
+            r#"
+            (module
+                (func (result v128)
+                    v128.const i32x4 0 0 0 0
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_reduce_v128_const_halves_lanes() {
+        match_reduction(
+            r#"
+            (module
+                (func (result v128)
+                    v128.const i32x4 4 8 0 3
+                )
+            )
+            "#,
+            r#"
+            (module
+                (func (result v128)
+                    v128.const i32x4 2 4 0 1
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_reduce_v128_const_requires_nonzero() {
+        let wasm = wat::parse_str("(module (func (result v128) v128.const i64x2 0 0))").unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.reduce(true);
+        config.setup(&wasm).unwrap();
+        assert!(!ReduceV128ConstMutator.can_mutate(&config));
+    }
+}
@@ -0,0 +1,241 @@
+//! Mutator that removes instructions from a function body.
+
+use super::{
+    select_to_if::value_type,
+    swap_commutative_operands::{pure_arity, subexpression_start},
+    Mutator,
+};
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use std::ops::Range;
+use wasm_encoder::{CodeSection, Module, ValType};
+use wasmparser::{FunctionBody, Operator};
+
+/// Shrinks a function body by removing some of its instructions while keeping
+/// the operand stack balanced.
+///
+/// Either a `nop` is removed, a pure subexpression whose value is immediately
+/// dropped is removed along with the `drop`, or a pure subexpression made of
+/// several instructions is replaced with a single zero constant of the same
+/// type. Pure subexpressions push exactly one value without popping anything,
+/// so the body stays valid, although its behavior changes.
+#[derive(Clone, Copy)]
+pub struct RemoveInstructionMutator;
+
+/// Returns the encoding of a constant zero of type `ty`.
+fn zero(ty: ValType) -> Option<Vec<u8>> {
+    match ty {
+        ValType::I32 => Some(vec![0x41, 0x00]),
+        ValType::I64 => Some(vec![0x42, 0x00]),
+        ValType::F32 => Some(vec![0x43, 0, 0, 0, 0]),
+        ValType::F64 => Some(vec![0x44, 0, 0, 0, 0, 0, 0, 0, 0]),
+        _ => None,
+    }
+}
+
+/// Returns the byte ranges, relative to the code section, of the instructions
+/// of `body` which can be removed, along with what to replace them with.
+fn removals(body: &FunctionBody) -> Result<Vec<(Range<usize>, Vec<u8>)>> {
+    let mut reader = body.get_operators_reader()?;
+    reader.allow_memarg64(true);
+    let ops = reader
+        .into_iter_with_offsets()
+        .collect::<wasmparser::Result<Vec<_>>>()?;
+    let mut candidates = Vec::new();
+    for (i, (op, offset)) in ops.iter().enumerate() {
+        // The final `end` is always followed by something, so this is only
+        // `None` past the end of the body.
+        let next = match ops.get(i + 1) {
+            Some((_, next)) => *next,
+            None => break,
+        };
+        match op {
+            Operator::Nop => candidates.push((*offset..next, Vec::new())),
+            Operator::Drop if i > 0 => {
+                if let Some(start) = subexpression_start(&ops, i - 1) {
+                    candidates.push((ops[start].1..next, Vec::new()));
+                }
+            }
+            op if pure_arity(op).map_or(false, |arity| arity > 0) => {
+                let start = match subexpression_start(&ops, i) {
+                    Some(start) => ops[start].1,
+                    None => continue,
+                };
+                if let Some(zero) = value_type(op, &[], &[]).and_then(zero) {
+                    if zero.len() < next - start {
+                        candidates.push((start..next, zero));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(candidates)
+}
+
+impl Mutator for RemoveInstructionMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let code_section = config.info().get_code_section();
+        let bodies = config.info().code_bodies()?;
+        let mut candidates = Vec::new();
+        for (i, body) in bodies.iter().enumerate() {
+            config.consume_fuel(1)?;
+            for removal in removals(body)? {
+                candidates.push((i, removal));
+            }
+        }
+
+        let (function_to_mutate, (removed, replacement)) = match candidates.choose(config.rng()) {
+            Some(candidate) => candidate.clone(),
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!(
+            "Removing the instructions at {:?} in function {}",
+            removed,
+            function_to_mutate
+        );
+
+        let data = code_section.data;
+        let mut codes = CodeSection::new();
+        for (i, body) in bodies.iter().enumerate() {
+            let range = body.range();
+            if i != function_to_mutate {
+                codes.raw(&data[range.start..range.end]);
+                continue;
+            }
+            let mut body = Vec::with_capacity(range.end - range.start);
+            body.extend_from_slice(&data[range.start..removed.start]);
+            body.extend_from_slice(&replacement);
+            body.extend_from_slice(&data[removed.end..range.end]);
+            codes.raw(&body);
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        config.reduce
+            && config.info().code_bodies().map_or(false, |bodies| {
+                bodies
+                    .iter()
+                    .any(|body| removals(body).map_or(false, |removals| !removals.is_empty()))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RemoveInstructionMutator;
+    use crate::mutators::Mutator;
+
+    fn match_reduction(original: &str, expected: &str) {
+        crate::WasmMutate::default().reduce(true).match_mutation(
+            original,
+            RemoveInstructionMutator,
+            expected,
+        )
+    }
+
+    #[test]
+    fn test_remove_dropped_subexpression() {
+        match_reduction(
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.const 1
+                    i32.add
+                    drop
+                    local.get 0
+                )
+            )
+            "#,
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    local.get 0
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_replace_subexpression_with_zero() {
+        match_reduction(
+            r#"
+            (module
+                (func (param i64) (result i64)
+                    local.get 0
+                    i64.const 3
+                    i64.mul
+                )
+            )
+            "#,
+            r#"
+            (module
+                (func (param i64) (result i64)
+                    i64.const 0
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_remove_nop() {
+        match_reduction(
+            r#"
+            (module
+                (func
+                    nop
+                )
+            )
+            "#,
+            r#"
+            (module
+                (func)
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_remove_around_memory64_offsets() {
+        match_reduction(
+            r#"
+            (module
+                (memory i64 1)
+                (func (result i32)
+                    nop
+                    i64.const 0
+                    i32.load offset=0x100000000
+                )
+            )
+            "#,
+            r#"
+            (module
+                (memory i64 1)
+                (func (result i32)
+                    i64.const 0
+                    i32.load offset=0x100000000
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_remove_instruction_requires_reduce() {
+        let wasm = wat::parse_str("(module (func nop))").unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!RemoveInstructionMutator.can_mutate(&config));
+    }
+}
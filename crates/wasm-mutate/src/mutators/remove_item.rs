@@ -9,7 +9,7 @@
 
 use crate::mutators::{translate, Item, Mutator, Translator};
 use crate::Error;
-use crate::{ModuleInfo, Result, WasmMutate};
+use crate::{Result, WasmMutate};
 use rand::Rng;
 use std::collections::HashSet;
 use wasm_encoder::*;
@@ -40,13 +40,7 @@ impl Mutator for RemoveItemMutator {
         let idx = self.0.choose_removal_index(config);
         log::trace!("attempting to remove {:?} index {}", self.0, idx);
 
-        let result = RemoveItem {
-            item: self.0,
-            idx,
-            referenced_functions: HashSet::new(),
-            function_reference_action: Funcref::Save,
-        }
-        .remove(config.info());
+        let result = RemoveItem::new(self.0, idx, None).remove(config);
         match result {
             Ok(result) => {
                 log::debug!("removed {:?} index {}", self.0, idx);
@@ -101,9 +95,12 @@ impl Item {
     }
 }
 
-struct RemoveItem {
+pub(super) struct RemoveItem {
     item: Item,
     idx: u32,
+    /// The index that references to the removed item are redirected to, if
+    /// any. Without one, removing a referenced item fails.
+    replacement: Option<u32>,
     function_reference_action: Funcref,
     referenced_functions: HashSet<u32>,
 }
@@ -119,7 +116,26 @@ enum Funcref {
 }
 
 impl RemoveItem {
-    fn remove(&mut self, info: &ModuleInfo) -> Result<Module> {
+    /// Creates a translator removing the `idx`th `item`, optionally
+    /// redirecting references to it to the earlier `replacement` index.
+    pub(super) fn new(item: Item, idx: u32, replacement: Option<u32>) -> RemoveItem {
+        debug_assert!(replacement.map_or(true, |replacement| replacement < idx));
+        RemoveItem {
+            item,
+            idx,
+            replacement,
+            referenced_functions: HashSet::new(),
+            function_reference_action: Funcref::Save,
+        }
+    }
+
+    /// Rewrites the module of `config` without the item being removed.
+    ///
+    /// Every index in the module may need renumbering, so this consumes fuel
+    /// for each section and each item it rewrites, bailing out with an "out
+    /// of fuel" error on pathologically large modules.
+    pub(super) fn remove(&mut self, config: &WasmMutate) -> Result<Module> {
+        let info = config.info();
         const CUSTOM: u8 = SectionId::Custom as u8;
         const TYPE: u8 = SectionId::Type as u8;
         const IMPORT: u8 = SectionId::Import as u8;
@@ -140,6 +156,7 @@ impl RemoveItem {
         // module section-by-section. Sections are rewritten on-the-fly.
         let mut module = Module::new();
         for section in info.raw_sections.iter() {
+            config.consume_fuel(1)?;
             match section.id {
                 CUSTOM => {
                     module.section(section);
@@ -147,6 +164,7 @@ impl RemoveItem {
 
                 TYPE => {
                     self.filter_out(
+                        config,
                         &mut module,
                         0,
                         TypeSectionReader::new(section.data, 0)?,
@@ -167,6 +185,7 @@ impl RemoveItem {
                     let mut memory = 0;
                     let mut tag = 0;
                     for item in ImportSectionReader::new(section.data, 0)? {
+                        config.consume_fuel(1)?;
                         let item = item?;
                         match &item.ty {
                             wasmparser::TypeRef::Func(ty) => {
@@ -211,6 +230,7 @@ impl RemoveItem {
 
                 FUNCTION => {
                     self.filter_out(
+                        config,
                         &mut module,
                         info.num_imported_functions(),
                         FunctionSectionReader::new(section.data, 0)?,
@@ -225,6 +245,7 @@ impl RemoveItem {
 
                 TABLE => {
                     self.filter_out(
+                        config,
                         &mut module,
                         info.num_imported_tables(),
                         TableSectionReader::new(section.data, 0)?,
@@ -239,6 +260,7 @@ impl RemoveItem {
 
                 MEMORY => {
                     self.filter_out(
+                        config,
                         &mut module,
                         info.num_imported_memories(),
                         MemorySectionReader::new(section.data, 0)?,
@@ -253,6 +275,7 @@ impl RemoveItem {
 
                 GLOBAL => {
                     self.filter_out(
+                        config,
                         &mut module,
                         info.num_imported_globals(),
                         GlobalSectionReader::new(section.data, 0)?,
@@ -264,6 +287,7 @@ impl RemoveItem {
                 EXPORT => {
                     let mut result = ExportSection::new();
                     for item in ExportSectionReader::new(section.data, 0)? {
+                        config.consume_fuel(1)?;
                         let item = item?;
                         let e = match &item.kind {
                             ExternalKind::Func => {
@@ -295,6 +319,7 @@ impl RemoveItem {
 
                 ELEMENT => {
                     self.filter_out(
+                        config,
                         &mut module,
                         0,
                         ElementSectionReader::new(section.data, 0)?,
@@ -309,6 +334,7 @@ impl RemoveItem {
                     // module, so indicate so in our internal state here.
                     self.function_reference_action = Funcref::RequireReferenced;
                     self.filter_out(
+                        config,
                         &mut module,
                         info.num_imported_functions(),
                         CodeSectionReader::new(section.data, 0)?,
@@ -319,6 +345,7 @@ impl RemoveItem {
 
                 DATA => {
                     self.filter_out(
+                        config,
                         &mut module,
                         0,
                         DataSectionReader::new(section.data, 0)?,
@@ -342,6 +369,7 @@ impl RemoveItem {
 
                 TAG => {
                     self.filter_out(
+                        config,
                         &mut module,
                         info.num_imported_tags(),
                         TagSectionReader::new(section.data, 0)?,
@@ -374,6 +402,7 @@ impl RemoveItem {
     /// imported globals because local globals are numbered afterwards.
     fn filter_out<S, T>(
         &mut self,
+        config: &WasmMutate,
         module: &mut Module,
         offset: u32,
         mut section: S,
@@ -387,6 +416,7 @@ impl RemoveItem {
         let mut result = T::default();
         let mut index = offset;
         while !section.eof() {
+            config.consume_fuel(1)?;
             let item = section.read()?;
             if index != self.idx || section_item != self.item {
                 encode(self, item, &mut result)?;
@@ -414,7 +444,8 @@ impl Translator for RemoveItem {
     /// * Otherwise if `idx` is less than the index being removed, it's entirely
     ///   unmodified since we're only modifying later items.
     /// * Otherwise if `idx` matches the index that's being removed then this
-    ///   means that the item was actually uses. In this situation we simply say
+    ///   means that the item was actually uses. If a replacement was given
+    ///   then the reference is redirected to it, otherwise we simply say
     ///   that the mutation is not applicable. This will bail out this entire
     ///   attempt to remove the `idx`th item and loops like `wasm-shrink` will
     ///   try something else.
@@ -438,8 +469,8 @@ impl Translator for RemoveItem {
             Ok(idx)
         } else if idx == self.idx {
             // If we're removing a referenced item then that means that this
-            // mutation fails.
-            Err(Error::no_mutations_applicable())
+            // mutation fails, unless references are redirected elsewhere.
+            self.replacement.ok_or_else(Error::no_mutations_applicable)
         } else {
             // Otherwise this item comes after the item being removed, so
             // this item's index has decreased by one.
@@ -482,6 +513,44 @@ mod tests {
             RemoveItemMutator(Item::Type),
             r#"(module)"#,
         );
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (type (func (param i32)))
+                (type (func (result i64)))
+                (type (func (param f32)))
+                (type (func))
+                (import "env" "f" (func (type 0)))
+                (table 1 funcref)
+                (func (type 3)
+                    f32.const 1
+                    i32.const 0
+                    call_indirect (type 2)
+                    block (type 3)
+                    end
+                )
+                (func (type 2))
+            )
+            "#,
+            RemoveItemMutator(Item::Type),
+            r#"
+            (module
+                (type (func (param i32)))
+                (type (func (param f32)))
+                (type (func))
+                (import "env" "f" (func (type 0)))
+                (table 1 funcref)
+                (func (type 2)
+                    f32.const 1
+                    i32.const 0
+                    call_indirect (type 1)
+                    block (type 2)
+                    end
+                )
+                (func (type 1))
+            )
+            "#,
+        );
     }
 
     #[test]
@@ -749,4 +818,25 @@ mod tests {
             )"#,
         );
     }
+
+    #[test]
+    fn remove_out_of_fuel() {
+        use crate::mutators::Mutator;
+        use crate::ErrorKind;
+
+        let wasm = wat::parse_str(
+            r#"(module
+                    (global i32 (i32.const 0))
+                    (global i32 (i32.const 1))
+                    (global i32 (i32.const 2))
+                    (global i32 (i32.const 3))
+            )"#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        config.fuel(3);
+        let result = RemoveItemMutator(Item::Global).mutate(&mut config);
+        assert!(matches!(result.err().unwrap().kind(), ErrorKind::OutOfFuel));
+    }
 }
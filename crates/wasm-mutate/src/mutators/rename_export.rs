@@ -1,14 +1,15 @@
 //! Mutator that generates a random renaming of a preexisting export
 use super::Mutator;
-use crate::{Result, WasmMutate};
-use rand::Rng;
+use crate::{Error, Result, WasmMutate};
+use rand::{seq::SliceRandom, Rng};
 use wasm_encoder::{Export, ExportSection, Module};
 use wasmparser::ExportSectionReader;
 
 /// Generates a random renaming of pre-existing exports.
 ///
 /// The export entry is selected randomly and then a new `field` name is
-/// generated.
+/// generated. When reducing, the new name is a shorter prefix of the original
+/// one instead, so names shrink toward the empty string.
 #[derive(Clone, Copy)]
 pub struct RenameExportMutator {
     /// The maximum length of the generated export entry
@@ -40,6 +41,19 @@ impl RenameExportMutator {
             return Ok(ret);
         }
     }
+
+    /// Picks a random proper prefix of `original` which isn't already the
+    /// name of an export.
+    fn shorter_string(&self, config: &mut WasmMutate, original: &str) -> Option<String> {
+        let prefixes = original
+            .char_indices()
+            .map(|(i, _)| &original[..i])
+            .filter(|prefix| !config.info().export_names.contains(*prefix))
+            .collect::<Vec<_>>();
+        prefixes
+            .choose(config.rng())
+            .map(|prefix| prefix.to_string())
+    }
 }
 
 impl Mutator for RenameExportMutator {
@@ -60,6 +74,13 @@ impl Mutator for RenameExportMutator {
             let new_name = if skip_at != i {
                 // otherwise bypass
                 String::from(export.name)
+            } else if config.reduce {
+                let new_name = match self.shorter_string(config, export.name) {
+                    Some(name) => name,
+                    None => return Err(Error::no_mutations_applicable()),
+                };
+                log::debug!("Shortening export {:?} to {:?}", export, new_name);
+                new_name
             } else {
                 let new_name = self.limited_string(config, export.name)?;
                 log::debug!("Renaming export {:?} by {:?}", export, new_name);
@@ -100,6 +121,7 @@ impl Mutator for RenameExportMutator {
 #[cfg(test)]
 mod tests {
     use super::RenameExportMutator;
+    use crate::mutators::Mutator;
     use crate::WasmMutate;
     use std::sync::Arc;
 
@@ -129,4 +151,61 @@ mod tests {
         (export "" (func 0)))"#,
         );
     }
+
+    #[test]
+    fn test_rename_export_preserves_indices() {
+        let mut config = WasmMutate::default();
+        config.raw_mutate_func(Some(Arc::new(|data, _| {
+            if data == b"mem" {
+                *data = b"memory".to_vec();
+            }
+            Ok(())
+        })));
+        config.match_mutation(
+            r#"
+            (module
+                (func)
+                (func (export "f"))
+                (memory (export "mem") 1)
+            )
+            "#,
+            RenameExportMutator { max_name_size: 10 },
+            r#"
+            (module
+                (func)
+                (func (export "f"))
+                (memory (export "memory") 1)
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_rename_export_reduce() {
+        WasmMutate::default().reduce(true).match_mutation(
+            r#"
+            (module
+                (func)
+                (func (export "fn"))
+                (memory (export "mem") 1)
+            )
+            "#,
+            RenameExportMutator { max_name_size: 10 },
+            r#"
+            (module
+                (func)
+                (func (export "fn"))
+                (memory (export "") 1)
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_rename_export_requires_exports() {
+        let wasm = wat::parse_str("(module (func))").unwrap();
+        let mut config = WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!RenameExportMutator { max_name_size: 10 }.can_mutate(&config));
+    }
 }
@@ -0,0 +1,261 @@
+//! Mutators that switch between `return` and a `br` to the function's label.
+
+use super::{translate, DefaultTranslator, Mutator, Translator};
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use wasm_encoder::{CodeSection, Function, Instruction, Module};
+use wasmparser::{FunctionBody, Operator};
+
+/// Rewrites a `return` into a `br` targeting the implicit block of the
+/// function body, whose label index is the number of frames enclosing the
+/// `return`.
+#[derive(Clone, Copy)]
+pub struct ReturnToBrMutator;
+
+/// Rewrites a `br` targeting the implicit block of the function body into a
+/// `return`, undoing [`ReturnToBrMutator`].
+#[derive(Clone, Copy)]
+pub struct BrToReturnMutator;
+
+/// Returns the index of every operator in `ops` for which `rewritable` holds,
+/// along with the number of `block`, `loop`, `if` and `try` frames enclosing
+/// it.
+fn rewritable_ops(ops: &[Operator], rewritable: fn(&Operator, u32) -> bool) -> Vec<(usize, u32)> {
+    let mut candidates = Vec::new();
+    let mut depth = 0u32;
+    for (i, op) in ops.iter().enumerate() {
+        match op {
+            Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Try { .. } => depth += 1,
+            // The final `end` of the body closes the function's own frame.
+            Operator::End | Operator::Delegate { .. } => depth = depth.saturating_sub(1),
+            op if rewritable(op, depth) => candidates.push((i, depth)),
+            _ => {}
+        }
+    }
+    candidates
+}
+
+fn is_return(op: &Operator, _depth: u32) -> bool {
+    matches!(op, Operator::Return)
+}
+
+fn is_br_to_function(op: &Operator, depth: u32) -> bool {
+    matches!(op, Operator::Br { relative_depth } if *relative_depth == depth)
+}
+
+/// Re-encodes `body` with the operator at index `target` replaced by
+/// `replacement`.
+fn replace_op(
+    body: &FunctionBody,
+    ops: &[Operator],
+    target: usize,
+    replacement: &Instruction,
+) -> Result<Function> {
+    let mut func = Function::new(translate::locals(&mut DefaultTranslator, body)?);
+    for (i, op) in ops.iter().enumerate() {
+        if i == target {
+            func.instruction(replacement);
+        } else {
+            func.instruction(&DefaultTranslator.translate_op(op)?);
+        }
+    }
+    Ok(func)
+}
+
+/// Replaces a random operator for which `rewritable` holds with the
+/// instruction `replacement` returns for the operator's depth.
+fn rewrite<'a>(
+    config: &'a mut WasmMutate,
+    rewritable: fn(&Operator, u32) -> bool,
+    replacement: fn(u32) -> Instruction<'static>,
+) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+    let code_section = config.info().get_code_section();
+    let mut bodies = Vec::new();
+    let mut candidates = Vec::new();
+    for (i, body) in config.info().code_bodies()?.into_iter().enumerate() {
+        config.consume_fuel(1)?;
+        let mut ops = body.get_operators_reader()?;
+        ops.allow_memarg64(true);
+        let ops = ops.into_iter().collect::<wasmparser::Result<Vec<_>>>()?;
+        for (op, depth) in rewritable_ops(&ops, rewritable) {
+            candidates.push((i, op, depth));
+        }
+        bodies.push((body, ops));
+    }
+
+    let (function_to_mutate, target, depth) = match candidates.choose(config.rng()) {
+        Some(candidate) => *candidate,
+        None => return Err(Error::no_mutations_applicable()),
+    };
+    let replacement = replacement(depth);
+    log::trace!(
+        "Replacing operator {} of function {} with {:?}",
+        target,
+        function_to_mutate,
+        replacement
+    );
+
+    let mut codes = CodeSection::new();
+    for (i, (body, ops)) in bodies.iter().enumerate() {
+        if i == function_to_mutate {
+            codes.function(&replace_op(body, ops, target, &replacement)?);
+        } else {
+            codes.raw(&code_section.data[body.range().start..body.range().end]);
+        }
+    }
+
+    Ok(Box::new(std::iter::once(Ok(config
+        .info()
+        .replace_section(config.info().code.unwrap(), &codes)))))
+}
+
+/// Returns whether any function body contains an operator for which
+/// `rewritable` holds.
+fn any_rewritable(config: &WasmMutate, rewritable: fn(&Operator, u32) -> bool) -> bool {
+    config.info().code_bodies().map_or(false, |bodies| {
+        bodies.iter().any(|body| {
+            body.get_operators_reader().map_or(false, |mut ops| {
+                ops.allow_memarg64(true);
+                ops.into_iter()
+                    .collect::<wasmparser::Result<Vec<_>>>()
+                    .map_or(false, |ops| !rewritable_ops(&ops, rewritable).is_empty())
+            })
+        })
+    })
+}
+
+impl Mutator for ReturnToBrMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        rewrite(config, is_return, Instruction::Br)
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        any_rewritable(config, is_return)
+    }
+}
+
+impl Mutator for BrToReturnMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        rewrite(config, is_br_to_function, |_| Instruction::Return)
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        any_rewritable(config, is_br_to_function)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BrToReturnMutator, ReturnToBrMutator};
+    use crate::mutators::Mutator;
+
+    const WITH_RETURN: &str = r#"
+        (module
+            (func (param i32) (result i32)
+                block
+                    loop
+                        local.get 0
+                        if
+                            i32.const 1
+                            return
+                        end
+                    end
+                end
+                i32.const 0
+            )
+        )
+    "#;
+
+    const WITH_BR: &str = r#"
+        (module
+            (func (param i32) (result i32)
+                block
+                    loop
+                        local.get 0
+                        if
+                            i32.const 1
+                            br 3
+                        end
+                    end
+                end
+                i32.const 0
+            )
+        )
+    "#;
+
+    #[test]
+    fn test_return_to_br() {
+        crate::mutators::match_mutation(WITH_RETURN, ReturnToBrMutator, WITH_BR);
+    }
+
+    #[test]
+    fn test_br_to_return() {
+        crate::mutators::match_mutation(WITH_BR, BrToReturnMutator, WITH_RETURN);
+    }
+
+    #[test]
+    fn test_br_to_return_from_nested_block() {
+        let original = r#"
+            (module
+                (func (result i32)
+                    block
+                        block
+                            i32.const 7
+                            br 2
+                        end
+                    end
+                    i32.const 0
+                )
+            )
+        "#;
+        crate::mutators::match_mutation(
+            original,
+            BrToReturnMutator,
+            r#"
+            (module
+                (func (result i32)
+                    block
+                        block
+                            i32.const 7
+                            return
+                        end
+                    end
+                    i32.const 0
+                )
+            )
+            "#,
+        );
+
+        let wasm = wat::parse_str(original).unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        let mutation = BrToReturnMutator
+            .mutate(&mut config)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .finish();
+        wasmparser::Validator::new()
+            .validate_all(&mutation)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_br_to_return_requires_function_label() {
+        let wasm = wat::parse_str("(module (func block br 0 end))").unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!BrToReturnMutator.can_mutate(&config));
+    }
+}
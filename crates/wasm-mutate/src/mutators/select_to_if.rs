@@ -0,0 +1,327 @@
+//! Mutator that rewrites a `select` into an `if`/`else`.
+
+use super::{
+    global_to_accessors::global_types, swap_commutative_operands::subexpression_start, translate,
+    DefaultTranslator, Mutator, Translator,
+};
+use crate::module::{map_primitive_type, map_type, TypeInfo};
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use wasm_encoder::{BlockType, CodeSection, Function, Instruction, Module, ValType};
+use wasmparser::{FunctionBody, Operator};
+
+/// Replaces a `select` with an `if`/`else` picking one of the two values based
+/// on the condition.
+///
+/// Both values and the condition are stashed in fresh locals first, so they
+/// are still all evaluated in order. The `if` needs the type of the values,
+/// which is taken from the immediate of a typed `select`. For an untyped
+/// `select` the condition has to be computed by a pure subexpression, and the
+/// type is derived from the instruction right before it, which pushes the
+/// second value.
+#[derive(Clone, Copy)]
+pub struct SelectToIfMutator;
+
+/// Returns the type of the value pushed by `op`, if it is easily known.
+pub(super) fn value_type(
+    op: &Operator,
+    locals: &[ValType],
+    globals: &[ValType],
+) -> Option<ValType> {
+    use Operator::*;
+    match op {
+        LocalGet { local_index } | LocalTee { local_index } => {
+            locals.get(*local_index as usize).copied()
+        }
+        GlobalGet { global_index } => globals.get(*global_index as usize).copied(),
+
+        I32Const { .. }
+        | I32Load { .. }
+        | I32Load8S { .. }
+        | I32Load8U { .. }
+        | I32Load16S { .. }
+        | I32Load16U { .. }
+        | I32Eqz
+        | I32Clz
+        | I32Ctz
+        | I32Popcnt
+        | I32Add
+        | I32Sub
+        | I32Mul
+        | I32And
+        | I32Or
+        | I32Xor
+        | I32Shl
+        | I32ShrS
+        | I32ShrU
+        | I32Rotl
+        | I32Rotr
+        | I32Eq
+        | I32Ne
+        | I32LtS
+        | I32LtU
+        | I32GtS
+        | I32GtU
+        | I32LeS
+        | I32LeU
+        | I32GeS
+        | I32GeU
+        | I64Eqz
+        | I64Eq
+        | I64Ne
+        | I64LtS
+        | I64LtU
+        | I64GtS
+        | I64GtU
+        | I64LeS
+        | I64LeU
+        | I64GeS
+        | I64GeU
+        | I32WrapI64 => Some(ValType::I32),
+
+        I64Const { .. }
+        | I64Load { .. }
+        | I64Load8S { .. }
+        | I64Load8U { .. }
+        | I64Load16S { .. }
+        | I64Load16U { .. }
+        | I64Load32S { .. }
+        | I64Load32U { .. }
+        | I64Clz
+        | I64Ctz
+        | I64Popcnt
+        | I64Add
+        | I64Sub
+        | I64Mul
+        | I64And
+        | I64Or
+        | I64Xor
+        | I64Shl
+        | I64ShrS
+        | I64ShrU
+        | I64Rotl
+        | I64Rotr
+        | I64ExtendI32S
+        | I64ExtendI32U => Some(ValType::I64),
+
+        F32Const { .. } | F32Load { .. } => Some(ValType::F32),
+        F64Const { .. } | F64Load { .. } => Some(ValType::F64),
+        _ => None,
+    }
+}
+
+/// Returns the types of the parameters and locals of `function`.
+pub(super) fn local_types(
+    config: &WasmMutate,
+    function: u32,
+    body: &FunctionBody,
+) -> Result<Vec<ValType>> {
+    let mut locals = match config.info().get_functype_idx(function) {
+        TypeInfo::Func(ty) => ty
+            .params
+            .iter()
+            .map(map_primitive_type)
+            .collect::<Result<Vec<_>>>()?,
+    };
+    for local in body.get_locals_reader()? {
+        let (count, ty) = local?;
+        let ty = map_type(ty)?;
+        locals.extend((0..count).map(|_| ty));
+    }
+    Ok(locals)
+}
+
+/// Returns the index and value type of every `select` in `ops` which can be
+/// rewritten.
+fn selects(
+    ops: &[(Operator, usize)],
+    locals: &[ValType],
+    globals: &[ValType],
+) -> Result<Vec<(usize, ValType)>> {
+    let mut candidates = Vec::new();
+    for (i, (op, _)) in ops.iter().enumerate() {
+        let ty = match op {
+            Operator::TypedSelect { ty } => map_type(*ty)?,
+            Operator::Select if i > 0 => match subexpression_start(ops, i - 1) {
+                Some(condition) if condition > 0 => {
+                    match value_type(&ops[condition - 1].0, locals, globals) {
+                        Some(ty) => ty,
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            },
+            _ => continue,
+        };
+        candidates.push((i, ty));
+    }
+    Ok(candidates)
+}
+
+impl Mutator for SelectToIfMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let globals = global_types(config)?
+            .into_iter()
+            .map(|global| map_type(global.content_type))
+            .collect::<Result<Vec<_>>>()?;
+        let first_defined = config.info().num_imported_functions();
+        let code_section = config.info().get_code_section();
+        let bodies = config.info().code_bodies()?;
+        let mut candidates = Vec::new();
+        for (i, body) in bodies.iter().enumerate() {
+            config.consume_fuel(1)?;
+            let locals = local_types(config, first_defined + i as u32, body)?;
+            let mut reader = body.get_operators_reader()?;
+            reader.allow_memarg64(true);
+            let ops = reader
+                .into_iter_with_offsets()
+                .collect::<wasmparser::Result<Vec<_>>>()?;
+            for (op, ty) in selects(&ops, &locals, &globals)? {
+                candidates.push((i, op, ty, locals.len() as u32));
+            }
+        }
+
+        let (function_to_mutate, target, ty, num_locals) = match candidates.choose(config.rng()) {
+            Some(candidate) => *candidate,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!(
+            "Rewriting the `select` at operator {} of function {} into an `if`",
+            target,
+            function_to_mutate
+        );
+
+        let mut codes = CodeSection::new();
+        for (i, body) in bodies.iter().enumerate() {
+            if i != function_to_mutate {
+                codes.raw(&code_section.data[body.range().start..body.range().end]);
+                continue;
+            }
+            let mut locals = translate::locals(&mut DefaultTranslator, body)?;
+            locals.push((2, ty));
+            locals.push((1, ValType::I32));
+            let (first, second, condition) = (num_locals, num_locals + 1, num_locals + 2);
+
+            let mut func = Function::new(locals);
+            let mut reader = body.get_operators_reader()?;
+            reader.allow_memarg64(true);
+            for (j, op) in reader.into_iter().enumerate() {
+                let op = op?;
+                if j != target {
+                    func.instruction(&DefaultTranslator.translate_op(&op)?);
+                    continue;
+                }
+                func.instruction(&Instruction::LocalSet(condition));
+                func.instruction(&Instruction::LocalSet(second));
+                func.instruction(&Instruction::LocalSet(first));
+                func.instruction(&Instruction::LocalGet(condition));
+                func.instruction(&Instruction::If(BlockType::Result(ty)));
+                func.instruction(&Instruction::LocalGet(first));
+                func.instruction(&Instruction::Else);
+                func.instruction(&Instruction::LocalGet(second));
+                func.instruction(&Instruction::End);
+            }
+            codes.function(&func);
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.reduce
+            && config.info().code_bodies().map_or(false, |bodies| {
+                bodies.iter().any(|body| {
+                    body.get_operators_reader().map_or(false, |mut ops| {
+                        ops.allow_memarg64(true);
+                        ops.into_iter().any(|op| {
+                            op.map_or(false, |op| {
+                                matches!(op, Operator::Select | Operator::TypedSelect { .. })
+                            })
+                        })
+                    })
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SelectToIfMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_select_to_if() {
+        let original = r#"
+            (module
+                (func (param i32 i32) (result i32)
+                    i32.const 1
+                    local.get 1
+                    local.get 0
+                    i32.eqz
+                    select
+                )
+            )
+        "#;
+        crate::mutators::match_mutation(
+            original,
+            SelectToIfMutator,
+            r#"
+            (module
+                (func (param i32 i32) (result i32)
+                    (local i32 i32 i32)
+                    i32.const 1
+                    local.get 1
+                    local.get 0
+                    i32.eqz
+                    local.set 4
+                    local.set 3
+                    local.set 2
+                    local.get 4
+                    if (result i32)
+                        local.get 2
+                    else
+                        local.get 3
+                    end
+                )
+            )
+            "#,
+        );
+
+        let wasm = wat::parse_str(original).unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        let mutation = SelectToIfMutator
+            .mutate(&mut config)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .finish();
+        wasmparser::Validator::new()
+            .validate_all(&mutation)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_select_to_if_requires_select() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    local.get 0
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!SelectToIfMutator.can_mutate(&config));
+    }
+}
@@ -0,0 +1,204 @@
+//! Mutator that wraps a function body in a loop which runs exactly once.
+
+use super::{translate, DefaultTranslator, Mutator, Translator};
+use crate::module::{map_primitive_type, TypeInfo};
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use wasm_encoder::{BlockType, CodeSection, Function, Instruction, Module};
+use wasmparser::{FunctionBody, Operator};
+
+/// Wraps the body of a function in a `loop` producing the function's results.
+///
+/// Nothing branches back to the start of the loop, so it runs exactly once,
+/// but runtimes still have to compile it as a loop. Branches to the function's
+/// own label are relabeled to skip the loop. Functions containing a
+/// `delegate` are left alone, as are functions with multiple results unless
+/// the module already defines a suitable type for the loop and multi-value
+/// is enabled.
+#[derive(Clone, Copy)]
+pub struct SingleIterationLoopMutator;
+
+/// Returns the block type a frame wrapping the body of `function` needs, if it
+/// can be expressed.
+fn body_block_type(config: &WasmMutate, function: u32) -> Result<Option<BlockType>> {
+    let info = config.info();
+    let returns = match info.get_functype_idx(function) {
+        TypeInfo::Func(ty) => &ty.returns,
+    };
+    Ok(match returns.as_slice() {
+        [] => Some(BlockType::Empty),
+        [ty] => Some(BlockType::Result(map_primitive_type(ty)?)),
+        _ if config.multi_value => info
+            .types_map
+            .iter()
+            .position(|ty| match ty {
+                TypeInfo::Func(ty) => ty.params.is_empty() && ty.returns == *returns,
+            })
+            .map(|ty| BlockType::FunctionType(ty as u32)),
+        _ => None,
+    })
+}
+
+/// Re-encodes `ops` inside of the frame `frame`, incrementing the labels of
+/// branches targeting the function itself.
+fn wrap(body: &FunctionBody, ops: &[Operator], frame: &Instruction) -> Result<Function> {
+    let mut func = Function::new(translate::locals(&mut DefaultTranslator, body)?);
+    func.instruction(frame);
+    let mut depth = 0;
+    let (last, ops) = ops.split_last().unwrap();
+    for op in ops {
+        let relabel = |label: u32| if label == depth { label + 1 } else { label };
+        func.instruction(&match op {
+            Operator::Br { relative_depth } => Instruction::Br(relabel(*relative_depth)),
+            Operator::BrIf { relative_depth } => Instruction::BrIf(relabel(*relative_depth)),
+            Operator::BrTable { table } => Instruction::BrTable(
+                table
+                    .targets()
+                    .map(|target| Ok(relabel(target?)))
+                    .collect::<Result<Vec<_>>>()?
+                    .into(),
+                relabel(table.default()),
+            ),
+            op => DefaultTranslator.translate_op(op)?,
+        });
+        match op {
+            Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Try { .. } => depth += 1,
+            Operator::End => depth -= 1,
+            _ => {}
+        }
+    }
+    func.instruction(&Instruction::End);
+    func.instruction(&DefaultTranslator.translate_op(last)?);
+    Ok(func)
+}
+
+/// Wraps the body of a random function in the frame `frame` returns for the
+/// function's block type.
+pub(super) fn wrap_body<'a>(
+    config: &'a mut WasmMutate,
+    frame: fn(BlockType) -> Instruction<'static>,
+) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+    let first_defined = config.info().num_imported_functions();
+    let code_section = config.info().get_code_section();
+    let bodies = config.info().code_bodies()?;
+    let mut candidates = Vec::new();
+    for (i, body) in bodies.iter().enumerate() {
+        config.consume_fuel(1)?;
+        let ty = match body_block_type(config, first_defined + i as u32)? {
+            Some(ty) => ty,
+            None => continue,
+        };
+        let mut reader = body.get_operators_reader()?;
+        reader.allow_memarg64(true);
+        let ops = reader.into_iter().collect::<wasmparser::Result<Vec<_>>>()?;
+        if !ops.iter().any(|op| matches!(op, Operator::Delegate { .. })) {
+            candidates.push((i, ops, ty));
+        }
+    }
+
+    let (function_to_mutate, ops, ty) = match candidates.choose(config.rng()) {
+        Some(candidate) => candidate,
+        None => return Err(Error::no_mutations_applicable()),
+    };
+    log::trace!(
+        "Wrapping the body of function {} in a {:?}",
+        first_defined + *function_to_mutate as u32,
+        frame(*ty)
+    );
+
+    let mut codes = CodeSection::new();
+    for (i, body) in bodies.iter().enumerate() {
+        if i == *function_to_mutate {
+            codes.function(&wrap(body, ops, &frame(*ty))?);
+        } else {
+            codes.raw(&code_section.data[body.range().start..body.range().end]);
+        }
+    }
+
+    Ok(Box::new(std::iter::once(Ok(config
+        .info()
+        .replace_section(config.info().code.unwrap(), &codes)))))
+}
+
+impl Mutator for SingleIterationLoopMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        wrap_body(config, Instruction::Loop)
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.reduce && config.info().has_code()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SingleIterationLoopMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_single_iteration_loop() {
+        let original = r#"
+            (module
+                (func (param i32) (result i32)
+                    local.get 0
+                    if
+                        i32.const 1
+                        br 1
+                    end
+                    local.get 0
+                    i32.const 2
+                    i32.add
+                )
+            )
+        "#;
+        crate::mutators::match_mutation(
+            original,
+            SingleIterationLoopMutator,
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    loop (result i32)
+                        local.get 0
+                        if
+                            i32.const 1
+                            br 2
+                        end
+                        local.get 0
+                        i32.const 2
+                        i32.add
+                    end
+                )
+            )
+            "#,
+        );
+
+        let wasm = wat::parse_str(original).unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        let mutation = SingleIterationLoopMutator
+            .mutate(&mut config)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .finish();
+        wasmparser::Validator::new()
+            .validate_all(&mutation)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_single_iteration_loop_requires_code() {
+        let wasm = wat::parse_str("(module (memory 1))").unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!SingleIterationLoopMutator.can_mutate(&config));
+    }
+}
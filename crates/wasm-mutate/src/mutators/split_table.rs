@@ -0,0 +1,582 @@
+//! Mutator that splits a table into two smaller tables.
+
+use super::select_to_if::local_types;
+use super::translate::{self, InitExprKind, Item};
+use super::{DefaultTranslator, Mutator, Translator};
+use crate::module::{map_primitive_type, TypeInfo};
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use wasm_encoder::{
+    BlockType, CodeSection, ElementSection, Function, Instruction, Module, SectionId, TableSection,
+    ValType,
+};
+use wasmparser::{
+    ElementKind, ElementSectionReader, ExportSectionReader, ExternalKind, InitExpr, Operator,
+    TableSectionReader, Type,
+};
+
+/// Splits a defined funcref table in two at a random index, moving the
+/// entries from that index onwards into a new table at the end of the table
+/// index space.
+///
+/// Active element segments writing to the moved entries are retargeted at the
+/// new table, and every `call_indirect`, `table.get` and `table.set` on the
+/// original table branches on its index to reach the right half, while
+/// `table.size` adds up the sizes of both halves. Tables which are exported
+/// or used by any other table instruction aren't split, so every dynamic
+/// index keeps referring to the same entry and semantics are preserved.
+/// Having more than one table requires the reference types proposal.
+#[derive(Clone, Copy)]
+pub struct SplitTableMutator;
+
+/// A table which may be split.
+struct Candidate {
+    index: u32,
+    ty: wasmparser::TableType,
+    /// The offset and length of each active element segment writing to this
+    /// table.
+    segments: Vec<(u32, u32)>,
+}
+
+/// Returns the offset of an element segment if it's a constant.
+fn const_offset(expr: &InitExpr<'_>) -> Option<u32> {
+    let mut reader = expr.get_operators_reader();
+    match (reader.read().ok()?, reader.read().ok()?) {
+        (Operator::I32Const { value }, Operator::End) if reader.eof() => Some(value as u32),
+        _ => None,
+    }
+}
+
+/// Returns the block type of an `if` which consumes the arguments of a call
+/// to a function of type `ty` and produces its results, if one can be
+/// expressed with the enabled features.
+fn call_block_type(config: &WasmMutate, ty: u32) -> Option<BlockType> {
+    let TypeInfo::Func(func) = config.info().types_map.get(ty as usize)?;
+    match (func.params.len(), func.returns.as_slice()) {
+        (0, []) => Some(BlockType::Empty),
+        (0, [result]) => map_primitive_type(result).ok().map(BlockType::Result),
+        _ if config.multi_value => Some(BlockType::FunctionType(ty)),
+        _ => None,
+    }
+}
+
+/// Stops `index` from being split.
+fn discard(tables: &mut [Option<Candidate>], index: u32) {
+    for table in tables.iter_mut() {
+        if matches!(table, Some(t) if t.index == index) {
+            *table = None;
+        }
+    }
+}
+
+/// Returns whether `op` is an instruction on `table` which is rewritten when
+/// it gets split.
+fn remapped(op: &Operator, table: u32) -> bool {
+    match *op {
+        Operator::CallIndirect { table_index, .. } => table_index == table,
+        Operator::TableGet { table: t }
+        | Operator::TableSet { table: t }
+        | Operator::TableSize { table: t } => t == table,
+        _ => false,
+    }
+}
+/// Returns the defined funcref tables with at least two entries whose active
+/// element segments all have constant offsets.
+fn candidates(config: &WasmMutate) -> Result<Vec<Candidate>> {
+    let info = config.info();
+    let section = match info.tables {
+        Some(section) => section,
+        None => return Ok(Vec::new()),
+    };
+    let mut tables = Vec::new();
+    let reader = TableSectionReader::new(info.raw_sections[section].data, 0)?;
+    for (i, ty) in reader.into_iter().enumerate() {
+        let ty = ty?;
+        if ty.element_type == Type::FuncRef && ty.initial >= 2 {
+            tables.push(Some(Candidate {
+                index: info.num_imported_tables() + i as u32,
+                ty,
+                segments: Vec::new(),
+            }));
+        }
+    }
+
+    if let Some(section) = info.elements {
+        for element in ElementSectionReader::new(info.raw_sections[section].data, 0)? {
+            let element = element?;
+            let (table_index, init_expr) = match &element.kind {
+                ElementKind::Active {
+                    table_index,
+                    init_expr,
+                } => (*table_index, init_expr),
+                _ => continue,
+            };
+            let table = tables
+                .iter_mut()
+                .find(|t| matches!(t, Some(t) if t.index == table_index));
+            if let Some(table) = table {
+                match const_offset(init_expr) {
+                    Some(offset) => {
+                        let len = element.items.get_items_reader()?.get_count();
+                        table.as_mut().unwrap().segments.push((offset, len));
+                    }
+                    // Which half a segment ends up in can't be determined.
+                    None => *table = None,
+                }
+            }
+        }
+    }
+
+    // Exported tables may be accessed by other modules, which can't be
+    // remapped.
+    if let Some(section) = info.exports {
+        for export in ExportSectionReader::new(info.raw_sections[section].data, 0)? {
+            let export = export?;
+            if matches!(export.kind, ExternalKind::Table) {
+                discard(&mut tables, export.index);
+            }
+        }
+    }
+
+    if info.code.is_some() {
+        for body in info.code_bodies()? {
+            config.consume_fuel(1)?;
+            let mut reader = body.get_operators_reader()?;
+            reader.allow_memarg64(true);
+            for op in reader {
+                match op? {
+                    Operator::CallIndirect {
+                        index, table_index, ..
+                    } if call_block_type(config, index).is_none() => {
+                        discard(&mut tables, table_index)
+                    }
+                    // Tail calls can't be translated yet.
+                    Operator::ReturnCallIndirect { table_index, .. } => {
+                        discard(&mut tables, table_index)
+                    }
+                    Operator::TableInit { table, .. }
+                    | Operator::TableFill { table }
+                    | Operator::TableGrow { table } => discard(&mut tables, table),
+                    Operator::TableCopy {
+                        dst_table,
+                        src_table,
+                    } => {
+                        discard(&mut tables, dst_table);
+                        discard(&mut tables, src_table);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    Ok(tables.into_iter().flatten().collect())
+}
+
+/// Translates an element segment to write to `table` at `offset` instead.
+struct Retarget {
+    table: u32,
+    offset: u32,
+}
+
+impl Translator for Retarget {
+    fn as_obj(&mut self) -> &mut dyn Translator {
+        self
+    }
+
+    fn remap(&mut self, item: Item, idx: u32) -> Result<u32> {
+        Ok(match item {
+            Item::Table => self.table,
+            _ => idx,
+        })
+    }
+
+    fn translate_init_expr(
+        &mut self,
+        e: &InitExpr<'_>,
+        _ty: &Type,
+        kind: InitExprKind,
+    ) -> Result<Instruction<'static>> {
+        match kind {
+            InitExprKind::ElementOffset => Ok(Instruction::I32Const(self.offset as i32)),
+            _ => translate::init_expr(self, e),
+        }
+    }
+}
+
+/// Emits a branch on the table index on top of the stack, running `low` with
+/// the index if it's below `split` and `high` with the index minus `split`
+/// otherwise. The index is stashed in the local `index`, and the local `value`
+/// is pushed after it if given.
+fn dispatch(
+    func: &mut Function,
+    ty: BlockType,
+    split: u32,
+    index: u32,
+    value: Option<u32>,
+    low: Instruction,
+    high: Instruction,
+) {
+    func.instruction(&Instruction::LocalTee(index));
+    func.instruction(&Instruction::I32Const(split as i32));
+    func.instruction(&Instruction::I32LtU);
+    func.instruction(&Instruction::If(ty));
+    func.instruction(&Instruction::LocalGet(index));
+    if let Some(value) = value {
+        func.instruction(&Instruction::LocalGet(value));
+    }
+    func.instruction(&low);
+    func.instruction(&Instruction::Else);
+    func.instruction(&Instruction::LocalGet(index));
+    func.instruction(&Instruction::I32Const(split as i32));
+    func.instruction(&Instruction::I32Sub);
+    if let Some(value) = value {
+        func.instruction(&Instruction::LocalGet(value));
+    }
+    func.instruction(&high);
+    func.instruction(&Instruction::End);
+}
+
+impl Mutator for SplitTableMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        config.consume_fuel(1)?;
+        let candidates = candidates(config)?;
+        let table = match candidates.choose(config.rng()) {
+            Some(table) => table,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        let split = config.rng().gen_range(1..table.ty.initial);
+        // Segments can't be split without renumbering all segments after
+        // them, so only split between segments.
+        if table
+            .segments
+            .iter()
+            .any(|(offset, len)| *offset < split && offset.saturating_add(*len) > split)
+        {
+            return Err(Error::no_mutations_applicable());
+        }
+        let info = config.info();
+        let new_table = info.num_tables();
+        log::trace!(
+            "Moving entries {}.. of table {} into new table {}",
+            split,
+            table.index,
+            new_table
+        );
+
+        let mut tables = TableSection::new();
+        let reader = TableSectionReader::new(info.raw_sections[info.tables.unwrap()].data, 0)?;
+        for (i, ty) in reader.into_iter().enumerate() {
+            let mut ty = DefaultTranslator.translate_table_type(&ty?)?;
+            if info.num_imported_tables() + i as u32 == table.index {
+                ty.minimum = split;
+            }
+            tables.table(ty);
+        }
+        tables.table(wasm_encoder::TableType {
+            element_type: ValType::FuncRef,
+            minimum: table.ty.initial - split,
+            maximum: table.ty.maximum.map(|max| max - split),
+        });
+
+        let mut elements = ElementSection::new();
+        if let Some(section) = info.elements {
+            let data = info.raw_sections[section].data;
+            let mut reader = ElementSectionReader::new(data, 0)?;
+            for _ in 0..reader.get_count() {
+                config.consume_fuel(1)?;
+                let start = reader.original_position();
+                let element = reader.read()?;
+                let end = reader.original_position();
+                let offset = match &element.kind {
+                    ElementKind::Active {
+                        table_index,
+                        init_expr,
+                    } if *table_index == table.index => const_offset(init_expr).unwrap(),
+                    _ => {
+                        elements.raw(&data[start..end]);
+                        continue;
+                    }
+                };
+                if offset < split {
+                    elements.raw(&data[start..end]);
+                } else {
+                    let mut retarget = Retarget {
+                        table: new_table,
+                        offset: offset - split,
+                    };
+                    retarget.translate_element(element, &mut elements)?;
+                }
+            }
+        }
+
+        let mut codes = CodeSection::new();
+        if info.code.is_some() {
+            let code_section = info.get_code_section();
+            for (i, body) in info.code_bodies()?.iter().enumerate() {
+                config.consume_fuel(1)?;
+                let mut reader = body.get_operators_reader()?;
+                reader.allow_memarg64(true);
+                if !reader
+                    .into_iter()
+                    .any(|op| op.map_or(false, |op| remapped(&op, table.index)))
+                {
+                    codes.raw(&code_section.data[body.range().start..body.range().end]);
+                    continue;
+                }
+
+                let index = local_types(config, info.num_imported_functions() + i as u32, body)?
+                    .len() as u32;
+                let value = index + 1;
+                let mut locals = translate::locals(&mut DefaultTranslator, body)?;
+                locals.push((1, ValType::I32));
+                locals.push((1, ValType::FuncRef));
+                let mut func = Function::new(locals);
+                let mut reader = body.get_operators_reader()?;
+                reader.allow_memarg64(true);
+                for op in reader {
+                    let op = op?;
+                    if !remapped(&op, table.index) {
+                        func.instruction(&DefaultTranslator.translate_op(&op)?);
+                        continue;
+                    }
+                    match op {
+                        Operator::CallIndirect { index: ty, .. } => dispatch(
+                            &mut func,
+                            call_block_type(config, ty).unwrap(),
+                            split,
+                            index,
+                            None,
+                            Instruction::CallIndirect {
+                                ty,
+                                table: table.index,
+                            },
+                            Instruction::CallIndirect {
+                                ty,
+                                table: new_table,
+                            },
+                        ),
+                        Operator::TableGet { .. } => dispatch(
+                            &mut func,
+                            BlockType::Result(ValType::FuncRef),
+                            split,
+                            index,
+                            None,
+                            Instruction::TableGet { table: table.index },
+                            Instruction::TableGet { table: new_table },
+                        ),
+                        Operator::TableSet { .. } => {
+                            func.instruction(&Instruction::LocalSet(value));
+                            dispatch(
+                                &mut func,
+                                BlockType::Empty,
+                                split,
+                                index,
+                                Some(value),
+                                Instruction::TableSet { table: table.index },
+                                Instruction::TableSet { table: new_table },
+                            );
+                        }
+                        _ => {
+                            func.instruction(&Instruction::TableSize { table: table.index });
+                            func.instruction(&Instruction::TableSize { table: new_table });
+                            func.instruction(&Instruction::I32Add);
+                        }
+                    }
+                }
+                codes.function(&func);
+            }
+        }
+
+        let module = info.replace_multiple_sections(|_, id, module| {
+            if id == SectionId::Table as u8 {
+                module.section(&tables);
+                true
+            } else if id == SectionId::Element as u8 {
+                module.section(&elements);
+                true
+            } else if id == SectionId::Code as u8 {
+                module.section(&codes);
+                true
+            } else {
+                false
+            }
+        });
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        let info = config.info();
+        config.reference_types && info.num_tables() > info.num_imported_tables()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SplitTableMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_split_table() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (table 4 8 funcref)
+                (func $f)
+                (func $g)
+                (elem (i32.const 0) $f)
+                (elem (i32.const 2) $g $f)
+                (func (param i32)
+                    local.get 0
+                    call_indirect)
+            )
+            "#,
+            SplitTableMutator,
+            r#"
+            (module
+                (table 2 8 funcref)
+                (table 2 6 funcref)
+                (func $f)
+                (func $g)
+                (elem (i32.const 0) $f)
+                (elem (table 1) (i32.const 0) func $g $f)
+                (func (param i32)
+                    (local i32 funcref)
+                    local.get 0
+                    local.tee 1
+                    i32.const 2
+                    i32.lt_u
+                    if
+                        local.get 1
+                        call_indirect
+                    else
+                        local.get 1
+                        i32.const 2
+                        i32.sub
+                        call_indirect 1 (type 0)
+                    end)
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_split_table_remaps_table_instructions() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (table 4 funcref)
+                (func $f)
+                (elem (i32.const 3) $f)
+                (func (param i32) (result i32)
+                    local.get 0
+                    local.get 0
+                    table.get 0
+                    table.set 0
+                    table.size 0)
+            )
+            "#,
+            SplitTableMutator,
+            r#"
+            (module
+                (table 3 funcref)
+                (table 1 funcref)
+                (func $f)
+                (elem (table 1) (i32.const 0) func $f)
+                (func (param i32) (result i32)
+                    (local i32 funcref)
+                    local.get 0
+                    local.get 0
+                    local.tee 1
+                    i32.const 3
+                    i32.lt_u
+                    if (result funcref)
+                        local.get 1
+                        table.get 0
+                    else
+                        local.get 1
+                        i32.const 3
+                        i32.sub
+                        table.get 1
+                    end
+                    local.set 2
+                    local.tee 1
+                    i32.const 3
+                    i32.lt_u
+                    if
+                        local.get 1
+                        local.get 2
+                        table.set 0
+                    else
+                        local.get 1
+                        i32.const 3
+                        i32.sub
+                        local.get 2
+                        table.set 1
+                    end
+                    table.size 0
+                    table.size 1
+                    i32.add)
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_split_table_requires_constant_offsets() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "" "" (global i32))
+                (table 4 funcref)
+                (func $f)
+                (elem (global.get 0) $f)
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(SplitTableMutator.can_mutate(&config));
+        assert!(SplitTableMutator.mutate(&mut config).is_err());
+
+        config.reference_types(false);
+        assert!(!SplitTableMutator.can_mutate(&config));
+    }
+
+    #[test]
+    fn test_split_table_skips_tables_it_cant_remap() {
+        for (wat, multi_value) in [
+            (r#"(module (table (export "t") 4 funcref))"#, true),
+            (
+                r#"(module
+                    (table 4 funcref)
+                    (func (result i32)
+                        ref.null func
+                        i32.const 1
+                        table.grow 0))"#,
+                true,
+            ),
+            (
+                r#"(module
+                    (table 4 funcref)
+                    (func (param i32)
+                        local.get 0
+                        local.get 0
+                        call_indirect (param i32)))"#,
+                false,
+            ),
+        ] {
+            let wasm = wat::parse_str(wat).unwrap();
+            let mut config = crate::WasmMutate::default();
+            config.multi_value(multi_value);
+            config.setup(&wasm).unwrap();
+            assert!(SplitTableMutator.can_mutate(&config));
+            assert!(SplitTableMutator.mutate(&mut config).is_err());
+        }
+    }
+}
@@ -0,0 +1,287 @@
+//! Mutator that turns a run of single-byte stores into a `memory.fill`.
+
+use super::{translate, DefaultTranslator, Mutator, Translator};
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use std::convert::TryFrom;
+use wasm_encoder::{CodeSection, Function, Instruction, Module};
+use wasmparser::{FunctionBody, Operator};
+
+/// Rewrites three or more consecutive `i32.store8`s of the same byte to
+/// consecutive constant addresses into a single `memory.fill`.
+///
+/// Each store must be of the form `i32.const addr; i32.const value;
+/// i32.store8 offset=o` on a 32-bit memory. Note that if the filled range is
+/// out of bounds, `memory.fill` traps before writing anything whereas the
+/// stores would have written the in-bounds bytes first, so this doesn't
+/// preserve semantics.
+#[derive(Clone, Copy)]
+pub struct StoresToFillMutator;
+
+/// A run of stores, given as operator indices.
+#[derive(Clone, Copy)]
+struct Fill {
+    function: usize,
+    /// The index of the first operator of the first store.
+    start: usize,
+    /// The number of stores.
+    len: usize,
+    memory: u32,
+    address: u32,
+    value: u8,
+}
+
+/// Returns the memory, effective address and stored byte of the store
+/// starting at `ops[i]`, if it is a store of a constant byte to a constant
+/// address.
+fn constant_store(config: &WasmMutate, ops: &[Operator], i: usize) -> Option<(u32, u32, u8)> {
+    let (address, value, memarg) = match ops.get(i..i + 3)? {
+        [Operator::I32Const { value: address }, Operator::I32Const { value }, Operator::I32Store8 { memarg }] => {
+            (*address as u32, *value as u8, memarg)
+        }
+        _ => return None,
+    };
+    if config.info().memory_types[memarg.memory as usize].memory64 {
+        return None;
+    }
+    let address = address.checked_add(u32::try_from(memarg.offset).ok()?)?;
+    Some((memarg.memory, address, value))
+}
+
+fn fills(config: &WasmMutate, function: usize, ops: &[Operator]) -> Vec<Fill> {
+    let mut fills = Vec::new();
+    let mut i = 0;
+    while i < ops.len() {
+        let (memory, address, value) = match constant_store(config, ops, i) {
+            Some(store) => store,
+            None => {
+                i += 1;
+                continue;
+            }
+        };
+        let mut len = 1;
+        while let Some(next) = constant_store(config, ops, i + 3 * len) {
+            match address.checked_add(len as u32) {
+                Some(next_address) if next == (memory, next_address, value) => len += 1,
+                _ => break,
+            }
+        }
+        if len >= 3 {
+            fills.push(Fill {
+                function,
+                start: i,
+                len,
+                memory,
+                address,
+                value,
+            });
+        }
+        i += 3 * len;
+    }
+    fills
+}
+
+fn fill(body: &FunctionBody, ops: &[Operator], fill: Fill) -> Result<Function> {
+    let mut func = Function::new(translate::locals(&mut DefaultTranslator, body)?);
+    for op in &ops[..fill.start] {
+        func.instruction(&DefaultTranslator.translate_op(op)?);
+    }
+    func.instruction(&Instruction::I32Const(fill.address as i32));
+    func.instruction(&Instruction::I32Const(fill.value.into()));
+    func.instruction(&Instruction::I32Const(fill.len as i32));
+    func.instruction(&Instruction::MemoryFill(fill.memory));
+    for op in &ops[fill.start + 3 * fill.len..] {
+        func.instruction(&DefaultTranslator.translate_op(op)?);
+    }
+    Ok(func)
+}
+
+impl Mutator for StoresToFillMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let code_section = config.info().get_code_section();
+        let mut bodies = Vec::new();
+        let mut candidates = Vec::new();
+        for (i, body) in config.info().code_bodies()?.into_iter().enumerate() {
+            config.consume_fuel(1)?;
+            let mut ops = body.get_operators_reader()?;
+            ops.allow_memarg64(true);
+            let ops = ops.into_iter().collect::<wasmparser::Result<Vec<_>>>()?;
+            candidates.extend(fills(config, i, &ops));
+            bodies.push((body, ops));
+        }
+
+        let chosen = match candidates.choose(config.rng()) {
+            Some(candidate) => *candidate,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!(
+            "Turning {} stores at operator {} of function {} into a memory.fill",
+            chosen.len,
+            chosen.start,
+            chosen.function
+        );
+
+        let mut codes = CodeSection::new();
+        for (i, (body, ops)) in bodies.iter().enumerate() {
+            if i == chosen.function {
+                codes.function(&fill(body, ops, chosen)?);
+            } else {
+                codes.raw(&code_section.data[body.range().start..body.range().end]);
+            }
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.reduce
+            && !config.preserve_semantics
+            && config.bulk_memory
+            && config.info().code_bodies().map_or(false, |bodies| {
+                bodies.iter().enumerate().any(|(i, body)| {
+                    body.get_operators_reader()
+                        .and_then(|mut ops| {
+                            ops.allow_memarg64(true);
+                            ops.into_iter().collect::<wasmparser::Result<Vec<_>>>()
+                        })
+                        .map_or(false, |ops| !fills(config, i, &ops).is_empty())
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StoresToFillMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_stores_to_fill() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (memory 1)
+                (func
+                    i32.const 16
+                    i32.const 7
+                    i32.store8
+                    i32.const 13
+                    i32.const 263
+                    i32.store8 offset=4
+                    i32.const 18
+                    i32.const 7
+                    i32.store8
+                    nop
+                )
+            )
+            "#,
+            StoresToFillMutator,
+            r#"
+            (module
+                (memory 1)
+                (func
+                    i32.const 16
+                    i32.const 7
+                    i32.const 3
+                    memory.fill
+                    nop
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_stores_to_fill_requires_bulk_memory() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory 1)
+                (func
+                    i32.const 0
+                    i32.const 7
+                    i32.store8
+                    i32.const 1
+                    i32.const 7
+                    i32.store8
+                    i32.const 2
+                    i32.const 7
+                    i32.store8
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(StoresToFillMutator.can_mutate(&config));
+
+        config.bulk_memory(false);
+        assert!(!StoresToFillMutator.can_mutate(&config));
+
+        config.bulk_memory(true);
+        config.preserve_semantics(true);
+        assert!(!StoresToFillMutator.can_mutate(&config));
+    }
+
+    #[test]
+    fn test_stores_to_fill_requires_consecutive_addresses() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory 1)
+                (func
+                    i32.const 0
+                    i32.const 7
+                    i32.store8
+                    i32.const 1
+                    i32.const 7
+                    i32.store8
+                    i32.const 3
+                    i32.const 7
+                    i32.store8
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!StoresToFillMutator.can_mutate(&config));
+    }
+
+    #[test]
+    fn test_stores_to_fill_with_memory64() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory 1)
+                (memory i64 1)
+                (func (result i32)
+                    i32.const 0
+                    i32.const 7
+                    i32.store8
+                    i32.const 1
+                    i32.const 7
+                    i32.store8
+                    i32.const 2
+                    i32.const 7
+                    i32.store8
+                    i64.const 0
+                    i32.load 1 offset=0x100000000
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(StoresToFillMutator.can_mutate(&config));
+    }
+}
@@ -0,0 +1,218 @@
+//! Mutator that swaps the operands of a commutative binary operator.
+
+use super::Mutator;
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use wasm_encoder::{CodeSection, Module};
+use wasmparser::{FunctionBody, Operator};
+
+/// Swaps the two operand subexpressions of a commutative operator such as
+/// `i32.add`, `i32.mul` or `i64.and`.
+///
+/// Only operands computed by side-effect free, non-trapping instructions are
+/// swapped, so the order in which they are evaluated doesn't matter.
+#[derive(Clone, Copy)]
+pub struct SwapCommutativeOperandsMutator;
+
+fn is_commutative(op: &Operator) -> bool {
+    use Operator::*;
+    matches!(
+        op,
+        I32Add
+            | I32Mul
+            | I32And
+            | I32Or
+            | I32Xor
+            | I32Eq
+            | I32Ne
+            | I64Add
+            | I64Mul
+            | I64And
+            | I64Or
+            | I64Xor
+            | I64Eq
+            | I64Ne
+    )
+}
+
+/// Returns how many operands `op` pops if it is a pure, non-trapping
+/// instruction pushing a single value, or `None` otherwise.
+pub(super) fn pure_arity(op: &Operator) -> Option<usize> {
+    use Operator::*;
+    match op {
+        I32Const { .. }
+        | I64Const { .. }
+        | F32Const { .. }
+        | F64Const { .. }
+        | LocalGet { .. }
+        | GlobalGet { .. } => Some(0),
+
+        I32Eqz | I32Clz | I32Ctz | I32Popcnt | I32Extend8S | I32Extend16S | I32WrapI64 | I64Eqz
+        | I64Clz | I64Ctz | I64Popcnt | I64Extend8S | I64Extend16S | I64Extend32S
+        | I64ExtendI32S | I64ExtendI32U => Some(1),
+
+        I32Add | I32Sub | I32Mul | I32And | I32Or | I32Xor | I32Shl | I32ShrS | I32ShrU
+        | I32Rotl | I32Rotr | I32Eq | I32Ne | I32LtS | I32LtU | I32GtS | I32GtU | I32LeS
+        | I32LeU | I32GeS | I32GeU | I64Add | I64Sub | I64Mul | I64And | I64Or | I64Xor
+        | I64Shl | I64ShrS | I64ShrU | I64Rotl | I64Rotr | I64Eq | I64Ne | I64LtS | I64LtU
+        | I64GtS | I64GtU | I64LeS | I64LeU | I64GeS | I64GeU => Some(2),
+
+        _ => None,
+    }
+}
+
+/// Returns the index of the first operator of the pure subexpression which
+/// produces the value pushed by `ops[end]`.
+pub(super) fn subexpression_start(ops: &[(Operator, usize)], end: usize) -> Option<usize> {
+    let mut needed = 1;
+    let mut i = end;
+    loop {
+        needed = needed - 1 + pure_arity(&ops[i].0)?;
+        if needed == 0 {
+            return Some(i);
+        }
+        i = i.checked_sub(1)?;
+    }
+}
+
+/// Returns the offsets at which the left operand, the right operand and the
+/// operator itself start for every commutative operator in `body` whose
+/// operands can be swapped.
+fn swappable_operands(body: &FunctionBody) -> Result<Vec<(usize, usize, usize)>> {
+    let mut reader = body.get_operators_reader()?;
+    reader.allow_memarg64(true);
+    let ops = reader
+        .into_iter_with_offsets()
+        .collect::<wasmparser::Result<Vec<_>>>()?;
+    let mut candidates = Vec::new();
+    for (i, (op, offset)) in ops.iter().enumerate() {
+        if i < 2 || !is_commutative(op) {
+            continue;
+        }
+        let right = match subexpression_start(&ops, i - 1) {
+            Some(right) if right > 0 => right,
+            _ => continue,
+        };
+        if let Some(left) = subexpression_start(&ops, right - 1) {
+            candidates.push((ops[left].1, ops[right].1, *offset));
+        }
+    }
+    Ok(candidates)
+}
+
+impl Mutator for SwapCommutativeOperandsMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let code_section = config.info().get_code_section();
+        let bodies = config.info().code_bodies()?;
+        let mut candidates = Vec::new();
+        for (i, body) in bodies.iter().enumerate() {
+            config.consume_fuel(1)?;
+            for operands in swappable_operands(body)? {
+                candidates.push((i, operands));
+            }
+        }
+
+        let (function_to_mutate, (left, right, op)) = match candidates.choose(config.rng()) {
+            Some(candidate) => *candidate,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!(
+            "Swapping the operands of the operator at {} in function {}",
+            op,
+            function_to_mutate
+        );
+
+        let data = code_section.data;
+        let mut codes = CodeSection::new();
+        for (i, body) in bodies.iter().enumerate() {
+            let range = body.range();
+            if i != function_to_mutate {
+                codes.raw(&data[range.start..range.end]);
+                continue;
+            }
+            let mut body = Vec::with_capacity(range.end - range.start);
+            body.extend_from_slice(&data[range.start..left]);
+            body.extend_from_slice(&data[right..op]);
+            body.extend_from_slice(&data[left..right]);
+            body.extend_from_slice(&data[op..range.end]);
+            codes.raw(&body);
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.reduce
+            && config.info().code_bodies().map_or(false, |bodies| {
+                bodies.iter().any(|body| {
+                    body.get_operators_reader().map_or(false, |mut ops| {
+                        ops.allow_memarg64(true);
+                        ops.into_iter()
+                            .any(|op| op.map_or(false, |op| is_commutative(&op)))
+                    })
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SwapCommutativeOperandsMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_swap_add_operands() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (global i32 (i32.const 3))
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.const 2
+                    i32.mul
+                    global.get 0
+                    i32.add
+                )
+            )
+            "#,
+            SwapCommutativeOperandsMutator,
+            r#"
+            (module
+                (global i32 (i32.const 3))
+                (func (param i32) (result i32)
+                    global.get 0
+                    local.get 0
+                    i32.const 2
+                    i32.mul
+                    i32.add
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_swap_requires_commutative_op() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    local.get 0
+                    call 0
+                    i32.sub
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!SwapCommutativeOperandsMutator.can_mutate(&config));
+    }
+}
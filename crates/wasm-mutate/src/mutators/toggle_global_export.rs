@@ -0,0 +1,135 @@
+//! Mutator that exports an internal global or un-exports an exported one.
+
+use super::Mutator;
+use crate::{Result, WasmMutate};
+
+use rand::Rng;
+use wasm_encoder::{Export, ExportSection, Module, SectionId};
+use wasmparser::{ExportSectionReader, ExternalKind};
+
+/// Picks a random global and removes all of its exports if it has any, or
+/// exports it under a fresh name otherwise.
+///
+/// This changes the module's interface, so it is not semantics-preserving.
+#[derive(Clone, Copy)]
+pub struct ToggleGlobalExportMutator;
+
+impl Mutator for ToggleGlobalExportMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        config.consume_fuel(1)?;
+        let num_globals = config.info().num_globals();
+        let global = config.rng().gen_range(0..num_globals);
+
+        let info = config.info();
+        let mut exports = Vec::new();
+        if let Some(section) = info.exports {
+            let mut reader = ExportSectionReader::new(info.raw_sections[section].data, 0)?;
+            for _ in 0..reader.get_count() {
+                exports.push(reader.read()?);
+            }
+        }
+        let is_exported = |export: &wasmparser::Export| {
+            matches!(export.kind, ExternalKind::Global) && export.index == global
+        };
+
+        let mut new_exports = ExportSection::new();
+        for export in exports.iter().filter(|export| !is_exported(export)) {
+            let item = match export.kind {
+                ExternalKind::Func => Export::Function(export.index),
+                ExternalKind::Table => Export::Table(export.index),
+                ExternalKind::Memory => Export::Memory(export.index),
+                ExternalKind::Global => Export::Global(export.index),
+                ExternalKind::Tag => Export::Tag(export.index),
+            };
+            new_exports.export(export.name, item);
+        }
+        if exports.iter().any(is_exported) {
+            log::trace!("Removing the exports of global {}", global);
+        } else {
+            let mut name = format!("global{}", global);
+            let mut suffix = 1;
+            while info.export_names.contains(&name) {
+                name = format!("global{}_{}", global, suffix);
+                suffix += 1;
+            }
+            log::trace!("Exporting global {} as {:?}", global, name);
+            new_exports.export(&name, Export::Global(global));
+        }
+
+        let module = info.replace_or_insert_sections(&[SectionId::Export], |_, module| {
+            module.section(&new_exports);
+        });
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.preserve_semantics && config.info().num_globals() > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ToggleGlobalExportMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_toggle_global_export_adds_export() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (global (mut i32) (i32.const 7))
+                (func (export "global0"))
+            )
+            "#,
+            ToggleGlobalExportMutator,
+            r#"
+            (module
+                (global (mut i32) (i32.const 7))
+                (func (export "global0"))
+                (export "global0_1" (global 0))
+            )
+            "#,
+        );
+        crate::mutators::match_mutation(
+            r#"(module (global i64 (i64.const 0)))"#,
+            ToggleGlobalExportMutator,
+            r#"(module (global i64 (i64.const 0)) (export "global0" (global 0)))"#,
+        );
+    }
+
+    #[test]
+    fn test_toggle_global_export_removes_export() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (global (export "g") (export "h") i32 (i32.const 7))
+                (func (export "f"))
+            )
+            "#,
+            ToggleGlobalExportMutator,
+            r#"
+            (module
+                (global i32 (i32.const 7))
+                (func (export "f"))
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_toggle_global_export_requires_global() {
+        let wasm = wat::parse_str(r#"(module (func (export "f")))"#).unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!ToggleGlobalExportMutator.can_mutate(&config));
+
+        let wasm = wat::parse_str(r#"(module (global i32 (i32.const 0)))"#).unwrap();
+        config.setup(&wasm).unwrap();
+        assert!(ToggleGlobalExportMutator.can_mutate(&config));
+        config.preserve_semantics(true);
+        assert!(!ToggleGlobalExportMutator.can_mutate(&config));
+    }
+}
@@ -61,6 +61,13 @@ pub trait Translator {
         ty(self.as_obj(), t)
     }
 
+    fn translate_field_type(
+        &mut self,
+        ty: &wasmparser::FieldType,
+    ) -> Result<wasm_encoder::FieldType> {
+        field_type(self.as_obj(), ty)
+    }
+
     fn translate_global(&mut self, g: Global, s: &mut GlobalSection) -> Result<()> {
         global(self.as_obj(), g, s)
     }
@@ -130,9 +137,38 @@ pub fn type_def(t: &mut dyn Translator, ty: TypeDef, s: &mut TypeSection) -> Res
             );
             Ok(())
         }
+        TypeDef::Struct(ty) => {
+            s.struct_(
+                ty.fields
+                    .iter()
+                    .map(|f| t.translate_field_type(f))
+                    .collect::<Result<Vec<_>>>()?,
+            );
+            Ok(())
+        }
+        TypeDef::Array(ty) => {
+            s.array(t.translate_field_type(&ty.field)?);
+            Ok(())
+        }
     }
 }
 
+pub fn field_type(
+    t: &mut dyn Translator,
+    ty: &wasmparser::FieldType,
+) -> Result<wasm_encoder::FieldType> {
+    Ok(wasm_encoder::FieldType {
+        element_type: match ty.element_type {
+            wasmparser::StorageType::I8 => wasm_encoder::StorageType::I8,
+            wasmparser::StorageType::I16 => wasm_encoder::StorageType::I16,
+            wasmparser::StorageType::Val(ty) => {
+                wasm_encoder::StorageType::Val(t.translate_ty(&ty)?)
+            }
+        },
+        mutable: ty.mutable,
+    })
+}
+
 pub fn table_type(
     t: &mut dyn Translator,
     ty: &wasmparser::TableType,
@@ -152,6 +188,7 @@ pub fn memory_type(
         memory64: ty.memory64,
         minimum: ty.initial,
         maximum: ty.maximum,
+        shared: ty.shared,
     })
 }
 
@@ -952,16 +989,18 @@ pub fn data(t: &mut dyn Translator, data: wasmparser::Data<'_>, s: &mut DataSect
     Ok(())
 }
 
-pub fn code(t: &mut dyn Translator, body: FunctionBody<'_>, s: &mut CodeSection) -> Result<()> {
-    let locals = body
-        .get_locals_reader()?
+pub fn locals(t: &mut dyn Translator, body: &FunctionBody<'_>) -> Result<Vec<(u32, ValType)>> {
+    body.get_locals_reader()?
         .into_iter()
         .map(|local| {
             let (cnt, ty) = local?;
             Ok((cnt, t.translate_ty(&ty)?))
         })
-        .collect::<Result<Vec<_>>>()?;
-    let mut func = Function::new(locals);
+        .collect()
+}
+
+pub fn code(t: &mut dyn Translator, body: FunctionBody<'_>, s: &mut CodeSection) -> Result<()> {
+    let mut func = Function::new(locals(t, &body)?);
 
     let mut reader = body.get_operators_reader()?;
     reader.allow_memarg64(true);
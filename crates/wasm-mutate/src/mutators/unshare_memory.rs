@@ -0,0 +1,157 @@
+//! Mutator that removes the `shared` flag from a memory.
+
+use super::{narrow_memory64::MemargProbe, translate, DefaultTranslator, Mutator, Translator};
+use crate::{Error, Result, WasmMutate};
+
+use rand::seq::SliceRandom;
+use wasm_encoder::{MemorySection, Module};
+use wasmparser::MemorySectionReader;
+
+/// Turns a defined shared memory into a non-shared one, which can remove the
+/// module's dependency on the threads proposal.
+///
+/// Atomic instructions require a shared memory, so only memories which no
+/// atomic instruction accesses are rewritten. The memory keeps its maximum.
+#[derive(Clone, Copy)]
+pub struct UnshareMemoryMutator;
+
+/// The prefix byte of all threads proposal instructions.
+const ATOMIC_PREFIX: u8 = 0xfe;
+
+/// Returns the defined shared memories which aren't accessed by any atomic
+/// instruction.
+fn candidates(config: &WasmMutate) -> Result<Vec<u32>> {
+    let info = config.info();
+    let mut memories = (info.num_imported_memories()..info.num_memories())
+        .filter(|memory| info.memory_types[*memory as usize].shared)
+        .collect::<Vec<_>>();
+    if memories.is_empty() || info.code.is_none() {
+        return Ok(memories);
+    }
+
+    let data = info.get_code_section().data;
+    for body in info.code_bodies()? {
+        let mut reader = body.get_operators_reader()?;
+        reader.allow_memarg64(true);
+        for op in reader.into_iter_with_offsets() {
+            let (op, offset) = op?;
+            if data[offset] != ATOMIC_PREFIX {
+                continue;
+            }
+            let mut probe = MemargProbe::default();
+            probe.translate_op(&op)?;
+            if let Some(memarg) = probe.0 {
+                memories.retain(|memory| *memory != memarg.memory);
+            }
+        }
+    }
+    Ok(memories)
+}
+
+impl Mutator for UnshareMemoryMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        config.consume_fuel(1)?;
+        let memory = match candidates(config)?.choose(config.rng()) {
+            Some(memory) => *memory,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+        log::trace!("Removing the shared flag of memory {}", memory);
+
+        let info = config.info();
+        let section = info.memories.unwrap();
+        let mut memories = MemorySection::new();
+        let mut reader = MemorySectionReader::new(info.raw_sections[section].data, 0)?;
+        for i in info.num_imported_memories()..info.num_memories() {
+            let mut ty = translate::memory_type(&mut DefaultTranslator, &reader.read()?)?;
+            if i == memory {
+                ty.shared = false;
+            }
+            memories.memory(ty);
+        }
+
+        Ok(Box::new(std::iter::once(Ok(
+            info.replace_section(section, &memories)
+        ))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        config.reduce && candidates(config).map_or(false, |memories| !memories.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UnshareMemoryMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_unshare_memory() {
+        let original = r#"
+            (module
+                (memory 1 2 shared)
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.load
+                )
+            )
+        "#;
+        crate::WasmMutate::default().reduce(true).match_mutation(
+            original,
+            UnshareMemoryMutator,
+            r#"
+            (module
+                (memory 1 2)
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.load
+                )
+            )
+            "#,
+        );
+
+        let wasm = wat::parse_str(original).unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.reduce(true);
+        config.setup(&wasm).unwrap();
+        let mutation = UnshareMemoryMutator
+            .mutate(&mut config)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .finish();
+        let features = wasmparser::WasmFeatures {
+            threads: false,
+            ..Default::default()
+        };
+        assert!(wasmparser::Validator::new_with_features(features)
+            .validate_all(&wasm)
+            .is_err());
+        wasmparser::Validator::new_with_features(features)
+            .validate_all(&mutation)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_unshare_memory_requires_no_atomics() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory 1 1 shared)
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.atomic.load
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.reduce(true);
+        config.setup(&wasm).unwrap();
+        assert!(!UnshareMemoryMutator.can_mutate(&config));
+    }
+}
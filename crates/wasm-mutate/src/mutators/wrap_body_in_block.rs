@@ -0,0 +1,86 @@
+//! Mutator that wraps a function body in an outer block.
+
+use super::{single_iteration_loop::wrap_body, Mutator};
+use crate::{Result, WasmMutate};
+
+use wasm_encoder::{Instruction, Module};
+
+/// Wraps the body of a function in a `block` producing the function's results.
+///
+/// Branches to the function's own label are relabeled to target the function
+/// rather than the new block, so the body still exits the same way. Functions
+/// are picked the same way as by
+/// [`SingleIterationLoopMutator`](super::single_iteration_loop::SingleIterationLoopMutator).
+#[derive(Clone, Copy)]
+pub struct WrapBodyInBlockMutator;
+
+impl Mutator for WrapBodyInBlockMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        wrap_body(config, Instruction::Block)
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.reduce && config.info().has_code()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WrapBodyInBlockMutator;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn test_wrap_body_in_block() {
+        let original = r#"
+            (module
+                (func (param i32) (result i32)
+                    block
+                        local.get 0
+                        br_if 0
+                        i32.const 1
+                        br 1
+                    end
+                    i32.const 2
+                    return
+                )
+            )
+        "#;
+        crate::mutators::match_mutation(
+            original,
+            WrapBodyInBlockMutator,
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    block (result i32)
+                        block
+                            local.get 0
+                            br_if 0
+                            i32.const 1
+                            br 2
+                        end
+                        i32.const 2
+                        return
+                    end
+                )
+            )
+            "#,
+        );
+
+        let wasm = wat::parse_str(original).unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        let mutation = WrapBodyInBlockMutator
+            .mutate(&mut config)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .finish();
+        wasmparser::Validator::new()
+            .validate_all(&mutation)
+            .unwrap();
+    }
+}
@@ -0,0 +1,166 @@
+//! Mutator that sets the static offset of a memory access to zero.
+
+use super::translate::{self, Translator};
+use super::Mutator;
+use crate::{Error, Result, WasmMutate};
+
+use rand::Rng;
+use wasm_encoder::{CodeSection, MemArg, Module};
+use wasmparser::MemoryImmediate;
+
+/// Rewrites the `offset` immediate of a load or store to zero.
+///
+/// This changes the accessed address, so it is not semantics-preserving.
+#[derive(Clone, Copy)]
+pub struct ZeroMemArgOffsetMutator;
+
+/// Translator which zeroes the offset of the `target`th memory access with a
+/// non-zero offset, counting how many such accesses it has seen.
+struct ZeroOffset {
+    target: Option<usize>,
+    seen: usize,
+}
+
+impl Translator for ZeroOffset {
+    fn as_obj(&mut self) -> &mut dyn Translator {
+        self
+    }
+
+    fn translate_memarg(&mut self, arg: &MemoryImmediate) -> Result<MemArg> {
+        let mut memarg = translate::memarg(self, arg)?;
+        if arg.offset != 0 {
+            if self.target == Some(self.seen) {
+                memarg.offset = 0;
+            }
+            self.seen += 1;
+        }
+        Ok(memarg)
+    }
+}
+
+impl Mutator for ZeroMemArgOffsetMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let code_section = config.info().get_code_section();
+        let bodies = config.info().code_bodies()?;
+
+        // Count the accesses with a non-zero offset in each function.
+        let mut counts = Vec::with_capacity(bodies.len());
+        for body in bodies.iter() {
+            config.consume_fuel(1)?;
+            let mut counter = ZeroOffset {
+                target: None,
+                seen: 0,
+            };
+            translate::code(&mut counter, *body, &mut CodeSection::new())?;
+            counts.push(counter.seen);
+        }
+        let total = counts.iter().sum::<usize>();
+        if total == 0 {
+            return Err(Error::no_mutations_applicable());
+        }
+
+        let mut access = config.rng().gen_range(0..total);
+        let function_to_mutate = counts
+            .iter()
+            .position(|count| {
+                if access < *count {
+                    return true;
+                }
+                access -= count;
+                false
+            })
+            .unwrap();
+        log::trace!(
+            "Zeroing the offset of access {} in function {}",
+            access,
+            function_to_mutate
+        );
+
+        let mut codes = CodeSection::new();
+        for (i, body) in bodies.iter().enumerate() {
+            if i == function_to_mutate {
+                let mut translator = ZeroOffset {
+                    target: Some(access),
+                    seen: 0,
+                };
+                translate::code(&mut translator, *body, &mut codes)?;
+            } else {
+                codes.raw(&code_section.data[body.range().start..body.range().end]);
+            }
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.preserve_semantics && config.info().has_nonempty_code()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ZeroMemArgOffsetMutator;
+
+    #[test]
+    fn test_zero_memarg_offset() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (memory 1)
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.load offset=8
+                )
+            )
+            "#,
+            ZeroMemArgOffsetMutator,
+            r#"
+            (module
+                (memory 1)
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.load offset=0
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_zero_memarg_offset_store() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (memory 1)
+                (func (param i32)
+                    local.get 0
+                    i64.const 1
+                    i64.store offset=16
+                    local.get 0
+                    i32.load8_u
+                    drop
+                )
+            )
+            "#,
+            ZeroMemArgOffsetMutator,
+            r#"
+            (module
+                (memory 1)
+                (func (param i32)
+                    local.get 0
+                    i64.const 1
+                    i64.store
+                    local.get 0
+                    i32.load8_u
+                    drop
+                )
+            )
+            "#,
+        );
+    }
+}
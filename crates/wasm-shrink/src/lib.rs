@@ -236,6 +236,8 @@ impl ShrinkRun {
 
             // We'll never enable this here.
             deterministic_only: false,
+            floats: true,
+            gc: false,
         });
 
         validator.validate_all(wasm)?;
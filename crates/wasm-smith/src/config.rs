@@ -125,6 +125,12 @@ pub trait Config: 'static + std::fmt::Debug {
         100
     }
 
+    /// The maximum total number of bytes across all generated data segments.
+    /// Defaults to `usize::MAX`.
+    fn max_data_bytes(&self) -> usize {
+        usize::MAX
+    }
+
     /// The maximum number of instructions to generate in a function
     /// body. Defaults to 100.
     ///
@@ -345,6 +351,21 @@ pub trait Config: 'static + std::fmt::Debug {
         false
     }
 
+    /// Returns whether only instructions whose results are the same on every
+    /// engine may be generated.
+    ///
+    /// This mirrors `wasmparser`'s `deterministic_only` validation flag. When
+    /// enabled, relaxed SIMD instructions are never generated, even if
+    /// [`Config::relaxed_simd_enabled`] is true. Neither are float demotions
+    /// and promotions, whose NaN results aren't canonicalized, while NaN
+    /// values are otherwise canonicalized as if [`Config::canonicalize_nans`]
+    /// was true.
+    ///
+    /// Defaults to `false`.
+    fn deterministic_only(&self) -> bool {
+        false
+    }
+
     /// Returns the kinds of instructions allowed in the generated wasm
     /// programs.
     ///
@@ -392,8 +413,10 @@ pub struct SwarmConfig {
     pub allow_start_export: bool,
     pub bulk_memory_enabled: bool,
     pub canonicalize_nans: bool,
+    pub deterministic_only: bool,
     pub exceptions_enabled: bool,
     pub max_aliases: usize,
+    pub max_data_bytes: usize,
     pub max_data_segments: usize,
     pub max_element_segments: usize,
     pub max_elements: usize,
@@ -451,6 +474,7 @@ impl<'a> Arbitrary<'a> for SwarmConfig {
             max_element_segments: u.int_in_range(0..=MAX_MAXIMUM)?,
             max_elements: u.int_in_range(0..=MAX_MAXIMUM)?,
             max_data_segments: u.int_in_range(0..=MAX_MAXIMUM)?,
+            max_data_bytes: u.int_in_range(0..=MAX_MAXIMUM * 1024)?,
             max_instructions: u.int_in_range(0..=MAX_MAXIMUM)?,
             max_memories: u.int_in_range(0..=100)?,
             max_tables,
@@ -489,6 +513,7 @@ impl<'a> Arbitrary<'a> for SwarmConfig {
             memory64_enabled: false,
             max_type_size: 1000,
             canonicalize_nans: false,
+            deterministic_only: false,
         })
     }
 }
@@ -558,6 +583,10 @@ impl Config for SwarmConfig {
         self.max_data_segments
     }
 
+    fn max_data_bytes(&self) -> usize {
+        self.max_data_bytes
+    }
+
     fn max_instructions(&self) -> usize {
         self.max_instructions
     }
@@ -661,4 +690,8 @@ impl Config for SwarmConfig {
     fn canonicalize_nans(&self) -> bool {
         self.canonicalize_nans
     }
+
+    fn deterministic_only(&self) -> bool {
+        self.deterministic_only
+    }
 }
@@ -1029,12 +1029,17 @@ impl Module {
             return Ok(());
         }
 
+        let mut remaining_bytes = self.config.max_data_bytes();
         arbitrary_loop(
             u,
             self.config.min_data_segments(),
             self.config.max_data_segments(),
             |u| {
-                let init: Vec<u8> = u.arbitrary()?;
+                // Once the byte budget is exhausted keep generating segments,
+                // just with empty contents, so `min_data_segments` is honored.
+                let mut init: Vec<u8> = u.arbitrary()?;
+                init.truncate(remaining_bytes);
+                remaining_bytes -= init.len();
 
                 // Passive data can only be generated if bulk memory is enabled.
                 // Otherwise if there are no memories we *only* generate passive
@@ -1174,6 +1179,7 @@ pub(crate) fn arbitrary_memtype(u: &mut Unstructured, config: &dyn Config) -> Re
         minimum,
         maximum,
         memory64,
+        shared: false,
     })
 }
 
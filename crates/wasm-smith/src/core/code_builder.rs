@@ -254,12 +254,12 @@ instructions! {
     (Some(i32_on_stack), f32_convert_i32_u, Numeric),
     (Some(i64_on_stack), f32_convert_i64_s, Numeric),
     (Some(i64_on_stack), f32_convert_i64_u, Numeric),
-    (Some(f64_on_stack), f32_demote_f64, Numeric),
+    (Some(f64_on_stack_nondeterministic), f32_demote_f64, Numeric),
     (Some(i32_on_stack), f64_convert_i32_s, Numeric),
     (Some(i32_on_stack), f64_convert_i32_u, Numeric),
     (Some(i64_on_stack), f64_convert_i64_s, Numeric),
     (Some(i64_on_stack), f64_convert_i64_u, Numeric),
-    (Some(f32_on_stack), f64_promote_f32, Numeric),
+    (Some(f32_on_stack_nondeterministic), f64_promote_f32, Numeric),
     (Some(f32_on_stack), i32_reinterpret_f32, Numeric),
     (Some(f64_on_stack), i64_reinterpret_f64, Numeric),
     (Some(i32_on_stack), f32_reinterpret_i32, Numeric),
@@ -529,8 +529,8 @@ instructions! {
     (Some(simd_v128_on_stack), i32x4_trunc_sat_f64x2u_zero, Vector),
     (Some(simd_v128_on_stack), f64x2_convert_low_i32x4s, Vector),
     (Some(simd_v128_on_stack), f64x2_convert_low_i32x4u, Vector),
-    (Some(simd_v128_on_stack), f32x4_demote_f64x2_zero, Vector),
-    (Some(simd_v128_on_stack), f64x2_promote_low_f32x4, Vector),
+    (Some(simd_v128_on_stack_nondeterministic), f32x4_demote_f64x2_zero, Vector),
+    (Some(simd_v128_on_stack_nondeterministic), f64x2_promote_low_f32x4, Vector),
     (Some(simd_v128_on_stack_relaxed), i32x4_relaxed_trunc_sat_f32x4s, Vector),
     (Some(simd_v128_on_stack_relaxed), i32x4_relaxed_trunc_sat_f32x4u, Vector),
     (Some(simd_v128_on_stack_relaxed), i32x4_relaxed_trunc_sat_f64x2s_zero, Vector),
@@ -855,7 +855,7 @@ impl CodeBuilder<'_> {
             // is based off Cranelift's pass for nan canonicalization for which
             // instructions to canonicalize, but the general idea is most
             // floating-point operations.
-            if module.config.canonicalize_nans() {
+            if module.config.canonicalize_nans() || module.config.deterministic_only() {
                 match instructions.last().unwrap() {
                     Instruction::F32Ceil
                     | Instruction::F32Floor
@@ -2586,6 +2586,10 @@ fn f32_on_stack(_: &Module, builder: &mut CodeBuilder) -> bool {
     builder.types_on_stack(&[ValType::F32])
 }
 
+fn f32_on_stack_nondeterministic(module: &Module, builder: &mut CodeBuilder) -> bool {
+    !module.config.deterministic_only() && f32_on_stack(module, builder)
+}
+
 fn f32_abs(_: &mut Unstructured, _: &Module, builder: &mut CodeBuilder) -> Result<Instruction> {
     builder.pop_operands(&[ValType::F32]);
     builder.push_operands(&[ValType::F32]);
@@ -2679,6 +2683,10 @@ fn f64_on_stack(_: &Module, builder: &mut CodeBuilder) -> bool {
     builder.types_on_stack(&[ValType::F64])
 }
 
+fn f64_on_stack_nondeterministic(module: &Module, builder: &mut CodeBuilder) -> bool {
+    !module.config.deterministic_only() && f64_on_stack(module, builder)
+}
+
 fn f64_abs(_: &mut Unstructured, _: &Module, builder: &mut CodeBuilder) -> Result<Instruction> {
     builder.pop_operands(&[ValType::F64]);
     builder.push_operands(&[ValType::F64]);
@@ -3457,9 +3465,21 @@ fn simd_v128_on_stack(module: &Module, builder: &mut CodeBuilder) -> bool {
     module.config.simd_enabled() && builder.types_on_stack(&[ValType::V128])
 }
 
+#[inline]
+fn simd_v128_on_stack_nondeterministic(module: &Module, builder: &mut CodeBuilder) -> bool {
+    !module.config.deterministic_only() && simd_v128_on_stack(module, builder)
+}
+
+#[inline]
+fn relaxed_simd_enabled(module: &Module) -> bool {
+    // Relaxed SIMD instructions may produce different results on different
+    // engines.
+    module.config.relaxed_simd_enabled() && !module.config.deterministic_only()
+}
+
 #[inline]
 fn simd_v128_on_stack_relaxed(module: &Module, builder: &mut CodeBuilder) -> bool {
-    module.config.relaxed_simd_enabled() && builder.types_on_stack(&[ValType::V128])
+    relaxed_simd_enabled(module) && builder.types_on_stack(&[ValType::V128])
 }
 
 #[inline]
@@ -3469,7 +3489,7 @@ fn simd_v128_v128_on_stack(module: &Module, builder: &mut CodeBuilder) -> bool {
 
 #[inline]
 fn simd_v128_v128_on_stack_relaxed(module: &Module, builder: &mut CodeBuilder) -> bool {
-    module.config.relaxed_simd_enabled() && builder.types_on_stack(&[ValType::V128, ValType::V128])
+    relaxed_simd_enabled(module) && builder.types_on_stack(&[ValType::V128, ValType::V128])
 }
 
 #[inline]
@@ -3480,7 +3500,7 @@ fn simd_v128_v128_v128_on_stack(module: &Module, builder: &mut CodeBuilder) -> b
 
 #[inline]
 fn simd_v128_v128_v128_on_stack_relaxed(module: &Module, builder: &mut CodeBuilder) -> bool {
-    module.config.relaxed_simd_enabled()
+    relaxed_simd_enabled(module)
         && builder.types_on_stack(&[ValType::V128, ValType::V128, ValType::V128])
 }
 
@@ -103,24 +103,31 @@ fn smoke_can_smith_valid_webassembly_one_point_oh() {
         cfg.max_tables = 1;
         if let Ok(module) = Module::new(cfg, &mut u) {
             let wasm_bytes = module.to_bytes();
-            // This table should set to `true` only features specified in wasm-core-1 spec.
+            let mut validator = Validator::new_with_features(WasmFeatures::wasm1());
+            validate(&mut validator, &wasm_bytes);
+        }
+    }
+}
+
+#[test]
+fn smoke_test_deterministic_only() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 2048];
+    for _ in 0..1024 {
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        let mut cfg = SwarmConfig::arbitrary(&mut u).unwrap();
+        cfg.simd_enabled = true;
+        cfg.relaxed_simd_enabled = true;
+        cfg.deterministic_only = true;
+        if let Ok(module) = Module::new(cfg, &mut u) {
+            let wasm_bytes = module.to_bytes();
+            // Relaxed SIMD instructions must not be generated even though
+            // they are enabled.
             let features = WasmFeatures {
-                mutable_global: true, // available in 1.0
-                saturating_float_to_int: false,
-                sign_extension: false,
-                reference_types: false,
-                multi_value: false,
-                bulk_memory: false,
-                simd: false,
                 relaxed_simd: false,
-                threads: false,
-                tail_call: false,
-                deterministic_only: false,
-                multi_memory: false,
-                exceptions: false,
-                memory64: false,
-                extended_const: false,
-                component_model: false,
+                deterministic_only: true,
+                ..wasm_features()
             };
             let mut validator = Validator::new_with_features(features);
             validate(&mut validator, &wasm_bytes);
@@ -128,6 +135,39 @@ fn smoke_can_smith_valid_webassembly_one_point_oh() {
     }
 }
 
+#[test]
+fn max_data_bytes() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 2048];
+    for max_data_bytes in 0..100 {
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        let mut cfg = SwarmConfig::arbitrary(&mut u).unwrap();
+        cfg.max_data_bytes = max_data_bytes;
+        let min_data_segments = cfg.max_data_segments.min(4);
+        cfg.min_data_segments = min_data_segments;
+        cfg.bulk_memory_enabled = true;
+        if let Ok(module) = Module::new(cfg, &mut u) {
+            let wasm_bytes = module.to_bytes();
+            let mut validator = Validator::new_with_features(wasm_features());
+            validate(&mut validator, &wasm_bytes);
+
+            let mut total = 0;
+            let mut segments = 0;
+            for payload in wasmparser::Parser::new(0).parse_all(&wasm_bytes) {
+                if let wasmparser::Payload::DataSection(reader) = payload.unwrap() {
+                    for data in reader {
+                        total += data.unwrap().data.len();
+                        segments += 1;
+                    }
+                }
+            }
+            assert!(total <= max_data_bytes);
+            assert!(segments >= min_data_segments);
+        }
+    }
+}
+
 fn validate(validator: &mut Validator, bytes: &[u8]) {
     let err = match validator.validate_all(bytes) {
         Ok(_) => return,
@@ -216,6 +216,8 @@ fn validate_benchmark(c: &mut Criterion) {
             memory64: true,
             extended_const: true,
             deterministic_only: false,
+            floats: true,
+            gc: false,
             mutable_global: true,
             saturating_float_to_int: true,
             sign_extension: true,
@@ -233,5 +235,37 @@ fn validate_benchmark(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benchmark, it_works_benchmark, validate_benchmark);
+fn validate_parallel_benchmark(c: &mut Criterion) {
+    // A module with many functions of moderate size, which is where validating
+    // function bodies in parallel pays off.
+    let mut wat = String::from("(module");
+    for _ in 0..4000 {
+        wat.push_str("(func (param i32) (result i32) local.get 0");
+        for i in 0..50 {
+            wat.push_str(&format!(" i32.const {} i32.add", i));
+        }
+        wat.push(')');
+    }
+    wat.push(')');
+    let wasm = wat::parse_str(&wat).unwrap();
+    c.bench_function("validate serial", |b| {
+        b.iter(|| Validator::new().validate_all(&wasm).unwrap())
+    });
+    #[cfg(feature = "rayon")]
+    c.bench_function("validate parallel", |b| {
+        let threads = rayon::current_num_threads();
+        b.iter(|| {
+            Validator::new()
+                .validate_all_parallel(&wasm, threads)
+                .unwrap()
+        })
+    });
+}
+
+criterion_group!(
+    benchmark,
+    it_works_benchmark,
+    validate_benchmark,
+    validate_parallel_benchmark
+);
 criterion_main!(benchmark);
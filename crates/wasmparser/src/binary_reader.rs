@@ -14,13 +14,13 @@
  */
 
 use crate::{
-    limits::*, Alias, AliasKind, BlockType, BrTable, CanonicalOption, ComponentExport,
+    limits::*, Alias, AliasKind, ArrayType, BlockType, BrTable, CanonicalOption, ComponentExport,
     ComponentFuncType, ComponentFunction, ComponentImport, ComponentStartFunction, ComponentType,
-    ComponentTypeDef, CustomSectionKind, Export, ExternalKind, FuncType, GlobalType, Ieee32,
-    Ieee64, Import, InitExpr, Instance, InstanceType, InterfaceType, InterfaceTypeRef, LinkingType,
-    MemoryImmediate, MemoryType, ModuleArg, ModuleArgKind, ModuleType, NameType, Operator,
-    PrimitiveInterfaceType, RelocType, SIMDLaneIndex, SectionCode, TableType, TagKind, TagType,
-    Type, TypeDef, TypeRef, VariantCase, V128,
+    ComponentTypeDef, CustomSectionKind, Export, ExternalKind, FieldType, FuncType, GlobalType,
+    Ieee32, Ieee64, Import, InitExpr, Instance, InstanceType, InterfaceType, InterfaceTypeRef,
+    LinkingType, MemoryImmediate, MemoryType, ModuleArg, ModuleArgKind, ModuleType, NameType,
+    Operator, PrimitiveInterfaceType, RelocType, SIMDLaneIndex, SectionCode, StorageType,
+    StructType, TableType, TagKind, TagType, Type, TypeDef, TypeRef, VariantCase, V128,
 };
 use crate::{ComponentArg, ComponentArgKind};
 use std::convert::TryInto;
@@ -77,6 +77,7 @@ pub(crate) struct BinaryReaderErrorInner {
     pub(crate) message: String,
     pub(crate) offset: usize,
     pub(crate) needed_hint: Option<usize>,
+    pub(crate) missing_feature: Option<&'static str>,
 }
 
 /// The result for `BinaryReader` operations.
@@ -102,16 +103,28 @@ impl BinaryReaderError {
                 message,
                 offset,
                 needed_hint: None,
+                missing_feature: None,
             }),
         }
     }
 
+    pub(crate) fn feature_disabled(
+        message: impl Into<String>,
+        feature: &'static str,
+        offset: usize,
+    ) -> Self {
+        let mut err = BinaryReaderError::new(message, offset);
+        err.inner.missing_feature = Some(feature);
+        err
+    }
+
     pub(crate) fn eof(offset: usize, needed_hint: usize) -> Self {
         BinaryReaderError {
             inner: Box::new(BinaryReaderErrorInner {
                 message: "unexpected end-of-file".to_string(),
                 offset,
                 needed_hint: Some(needed_hint),
+                missing_feature: None,
             }),
         }
     }
@@ -125,6 +138,13 @@ impl BinaryReaderError {
     pub fn offset(&self) -> usize {
         self.inner.offset
     }
+
+    /// Get the name of the [`WasmFeatures`](crate::WasmFeatures) field which
+    /// must be enabled to get past this error, if the error is caused by a
+    /// disabled feature.
+    pub fn missing_feature(&self) -> Option<&'static str> {
+        self.inner.missing_feature
+    }
 }
 
 /// A binary reader of the WebAssembly structures and types.
@@ -277,9 +297,49 @@ impl<'a> BinaryReader<'a> {
         })
     }
 
+    fn read_field_type(&mut self) -> Result<FieldType> {
+        let element_type = match self.peek()? {
+            0x7a => {
+                self.position += 1;
+                StorageType::I8
+            }
+            0x79 => {
+                self.position += 1;
+                StorageType::I16
+            }
+            _ => StorageType::Val(self.read_type()?),
+        };
+        Ok(FieldType {
+            element_type,
+            mutable: match self.read_u8()? {
+                0x00 => false,
+                0x01 => true,
+                _ => {
+                    return Err(BinaryReaderError::new(
+                        "malformed mutability",
+                        self.original_position() - 1,
+                    ))
+                }
+            },
+        })
+    }
+
+    pub(crate) fn read_struct_type(&mut self) -> Result<StructType> {
+        let size = self.read_size(MAX_WASM_STRUCT_FIELDS, "struct fields")?;
+        Ok(StructType {
+            fields: (0..size)
+                .map(|_| self.read_field_type())
+                .collect::<Result<_>>()?,
+        })
+    }
+
     pub(crate) fn read_type_def(&mut self) -> Result<TypeDef> {
         Ok(match self.read_u8()? {
             0x60 => TypeDef::Func(self.read_func_type()?),
+            0x5f => TypeDef::Struct(self.read_struct_type()?),
+            0x5e => TypeDef::Array(ArrayType {
+                field: self.read_field_type()?,
+            }),
             x => return self.invalid_leading_byte(x, "type"),
         })
     }
@@ -762,7 +822,10 @@ impl<'a> BinaryReader<'a> {
         let attribute = self.read_u8()?;
         if attribute != 0 {
             return Err(BinaryReaderError::new(
-                "invalid tag attributes",
+                format!(
+                    "invalid tag attributes: unknown attribute {:#04x}",
+                    attribute
+                ),
                 self.original_position() - 1,
             ));
         }
@@ -1,5 +1,6 @@
 mod code;
 mod data;
+mod dylink;
 mod elements;
 mod exports;
 mod functions;
@@ -18,6 +19,7 @@ mod types;
 
 pub use self::code::*;
 pub use self::data::*;
+pub use self::dylink::*;
 pub use self::elements::*;
 pub use self::exports::*;
 pub use self::functions::*;
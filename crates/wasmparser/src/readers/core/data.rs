@@ -107,6 +107,20 @@ impl<'a> DataSectionReader<'a> {
     /// }
     /// ```
     pub fn read<'b>(&mut self) -> Result<Data<'b>>
+    where
+        'a: 'b,
+    {
+        Ok(self.read_with_flags()?.0)
+    }
+
+    /// Same as [`DataSectionReader::read`], but also returns the flags the
+    /// data segment was encoded with.
+    ///
+    /// This is 0 for an active segment of memory 0 in the compact encoding,
+    /// 1 for a passive segment and 2 for an active segment with an explicit
+    /// memory index. When bulk memory is forbidden this is the memory index
+    /// of the segment instead.
+    pub fn read_with_flags<'b>(&mut self) -> Result<(Data<'b>, u32)>
     where
         'a: 'b,
     {
@@ -147,7 +161,7 @@ impl<'a> DataSectionReader<'a> {
         let segment_end = self.reader.original_position();
         let range = Range::new(segment_start, segment_end);
 
-        Ok(Data { kind, data, range })
+        Ok((Data { kind, data, range }, flags))
     }
 }
 
@@ -181,3 +195,40 @@ impl<'a> IntoIterator for DataSectionReader<'a> {
         SectionIteratorLimited::new(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_data_flags() {
+        let data = [
+            3, // count
+            0x00, 0x41, 0x00, 0x0b, 1, 0xaa, // active, memory 0
+            0x01, 1, 0xbb, // passive
+            0x02, 0x00, 0x41, 0x00, 0x0b, 1, 0xcc, // active, explicit memory 0
+        ];
+        let mut reader = DataSectionReader::new(&data, 0).unwrap();
+        let (segments, flags): (Vec<_>, Vec<_>) = (0..reader.get_count())
+            .map(|_| reader.read_with_flags().unwrap())
+            .unzip();
+        assert_eq!(flags, [0, 1, 2]);
+        assert!(matches!(
+            segments[0].kind,
+            DataKind::Active {
+                memory_index: 0,
+                ..
+            }
+        ));
+        assert!(matches!(segments[1].kind, DataKind::Passive));
+        assert!(matches!(
+            segments[2].kind,
+            DataKind::Active {
+                memory_index: 0,
+                ..
+            }
+        ));
+        let bytes = segments.iter().map(|s| s.data[0]).collect::<Vec<_>>();
+        assert_eq!(bytes, [0xaa, 0xbb, 0xcc]);
+    }
+}
@@ -0,0 +1,318 @@
+use crate::{BinaryReader, BinaryReaderError, Range, Result, SectionIterator, SectionReader};
+
+const WASM_DYLINK_MEM_INFO: u8 = 1;
+const WASM_DYLINK_NEEDED: u8 = 2;
+const WASM_DYLINK_EXPORT_INFO: u8 = 3;
+const WASM_DYLINK_IMPORT_INFO: u8 = 4;
+
+/// Represents the memory and table requirements of a dynamic library.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MemInfo {
+    /// The size of the library's static data, in bytes.
+    pub mem_size: u32,
+    /// The required alignment of the static data, as a power of two.
+    pub mem_align: u32,
+    /// The number of table entries the library needs.
+    pub table_size: u32,
+    /// The required alignment of the table entries, as a power of two.
+    pub table_align: u32,
+}
+
+/// Represents the flags of an export in the `dylink.0` custom section.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ExportInfo<'a> {
+    /// The name of the export.
+    pub name: &'a str,
+    /// The symbol flags of the export.
+    pub flags: u32,
+}
+
+/// Represents the flags of an import in the `dylink.0` custom section.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ImportInfo<'a> {
+    /// The module name of the import.
+    pub module: &'a str,
+    /// The field name of the import.
+    pub field: &'a str,
+    /// The symbol flags of the import.
+    pub flags: u32,
+}
+
+/// Represents a subsection read from the `dylink.0` custom section.
+#[derive(Debug, Clone)]
+pub enum DylinkSubsection<'a> {
+    /// The memory and table requirements of the library.
+    MemInfo(MemInfo),
+    /// The names of the libraries this library depends on.
+    Needed(Vec<&'a str>),
+    /// Additional information about the library's exports.
+    ExportInfo(Vec<ExportInfo<'a>>),
+    /// Additional information about the library's imports.
+    ImportInfo(Vec<ImportInfo<'a>>),
+    /// An unknown subsection.
+    Unknown {
+        /// The identifier for this subsection.
+        ty: u8,
+        /// The contents of this subsection.
+        data: &'a [u8],
+        /// The range of bytes, relative to the start of the original data
+        /// stream, that the contents of this subsection reside in.
+        range: Range,
+    },
+}
+
+/// A reader for the `dylink.0` custom section of a WebAssembly module, as
+/// produced for dynamic linking by toolchains such as Emscripten.
+pub struct DylinkSectionReader<'a> {
+    reader: BinaryReader<'a>,
+}
+
+impl<'a> DylinkSectionReader<'a> {
+    /// Constructs a new `DylinkSectionReader` from the given data and offset.
+    ///
+    /// # Examples
+    /// ```
+    /// # let data: &[u8] = &[0x01, 0x04, 0x10, 0x02, 0x00, 0x00,
+    /// #     0x02, 0x09, 0x01, 0x07, 0x6c, 0x69, 0x62, 0x63, 0x2e, 0x73, 0x6f];
+    /// use wasmparser::{DylinkSectionReader, DylinkSubsection};
+    /// let mut reader = DylinkSectionReader::new(data, 0).expect("dylink reader");
+    /// match reader.read().expect("mem info") {
+    ///     DylinkSubsection::MemInfo(info) => {
+    ///         assert_eq!(info.mem_size, 16);
+    ///         assert_eq!(info.mem_align, 2);
+    ///     }
+    ///     _ => panic!("expected mem info"),
+    /// }
+    /// match reader.read().expect("needed") {
+    ///     DylinkSubsection::Needed(needed) => assert_eq!(needed, ["libc.so"]),
+    ///     _ => panic!("expected needed libraries"),
+    /// }
+    /// assert!(reader.eof());
+    /// ```
+    pub fn new(data: &'a [u8], offset: usize) -> Result<DylinkSectionReader<'a>> {
+        Ok(DylinkSectionReader {
+            reader: BinaryReader::new_with_offset(data, offset),
+        })
+    }
+
+    /// Determines if the reader is at the end of the section.
+    pub fn eof(&self) -> bool {
+        self.reader.eof()
+    }
+
+    /// Gets the original position of the section reader.
+    pub fn original_position(&self) -> usize {
+        self.reader.original_position()
+    }
+
+    /// Reads a subsection from the section.
+    pub fn read<'b>(&mut self) -> Result<DylinkSubsection<'b>>
+    where
+        'a: 'b,
+    {
+        let ty = self.reader.read_u8()?;
+        let payload_len = self.reader.read_var_u32()? as usize;
+        let payload_start = self.reader.position;
+        let payload_end = payload_start + payload_len;
+        if self.reader.buffer.len() < payload_end {
+            return Err(BinaryReaderError::new(
+                "dylink subsection extends past end of the section",
+                self.reader.original_offset + self.reader.buffer.len(),
+            ));
+        }
+        let offset = self.reader.original_offset + payload_start;
+        let data = &self.reader.buffer[payload_start..payload_end];
+        self.reader.skip_to(payload_end);
+
+        let mut reader = BinaryReader::new_with_offset(data, offset);
+        let subsection = match ty {
+            WASM_DYLINK_MEM_INFO => DylinkSubsection::MemInfo(MemInfo {
+                mem_size: reader.read_var_u32()?,
+                mem_align: reader.read_var_u32()?,
+                table_size: reader.read_var_u32()?,
+                table_align: reader.read_var_u32()?,
+            }),
+            WASM_DYLINK_NEEDED => {
+                let count = reader.read_var_u32()?;
+                DylinkSubsection::Needed(
+                    (0..count)
+                        .map(|_| reader.read_string())
+                        .collect::<Result<_>>()?,
+                )
+            }
+            WASM_DYLINK_EXPORT_INFO => {
+                let count = reader.read_var_u32()?;
+                DylinkSubsection::ExportInfo(
+                    (0..count)
+                        .map(|_| {
+                            Ok(ExportInfo {
+                                name: reader.read_string()?,
+                                flags: reader.read_var_u32()?,
+                            })
+                        })
+                        .collect::<Result<_>>()?,
+                )
+            }
+            WASM_DYLINK_IMPORT_INFO => {
+                let count = reader.read_var_u32()?;
+                DylinkSubsection::ImportInfo(
+                    (0..count)
+                        .map(|_| {
+                            Ok(ImportInfo {
+                                module: reader.read_string()?,
+                                field: reader.read_string()?,
+                                flags: reader.read_var_u32()?,
+                            })
+                        })
+                        .collect::<Result<_>>()?,
+                )
+            }
+            ty => {
+                return Ok(DylinkSubsection::Unknown {
+                    ty,
+                    data,
+                    range: Range::new(offset, payload_end + self.reader.original_offset),
+                })
+            }
+        };
+        if !reader.eof() {
+            return Err(BinaryReaderError::new(
+                "unexpected content in dylink subsection",
+                reader.original_position(),
+            ));
+        }
+        Ok(subsection)
+    }
+}
+
+impl<'a> SectionReader for DylinkSectionReader<'a> {
+    type Item = DylinkSubsection<'a>;
+    fn read(&mut self) -> Result<Self::Item> {
+        DylinkSectionReader::read(self)
+    }
+    fn eof(&self) -> bool {
+        DylinkSectionReader::eof(self)
+    }
+    fn original_position(&self) -> usize {
+        DylinkSectionReader::original_position(self)
+    }
+    fn range(&self) -> Range {
+        self.reader.range()
+    }
+}
+
+impl<'a> IntoIterator for DylinkSectionReader<'a> {
+    type Item = Result<DylinkSubsection<'a>>;
+    type IntoIter = SectionIterator<DylinkSectionReader<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        SectionIterator::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_dylink_section() {
+        let data = [
+            // mem info
+            WASM_DYLINK_MEM_INFO,
+            5,
+            0x80,
+            0x01,
+            3,
+            1,
+            0,
+            // needed
+            WASM_DYLINK_NEEDED,
+            7,
+            2,
+            2,
+            b'l',
+            b'a',
+            2,
+            b'l',
+            b'b',
+            // export info
+            WASM_DYLINK_EXPORT_INFO,
+            4,
+            1,
+            1,
+            b'f',
+            2,
+            // import info
+            WASM_DYLINK_IMPORT_INFO,
+            8,
+            1,
+            3,
+            b'e',
+            b'n',
+            b'v',
+            1,
+            b'g',
+            0x10,
+            // unknown
+            42,
+            1,
+            0xff,
+        ];
+
+        let subsections = DylinkSectionReader::new(&data, 0)
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(subsections.len(), 5);
+        match &subsections[0] {
+            DylinkSubsection::MemInfo(info) => assert_eq!(
+                *info,
+                MemInfo {
+                    mem_size: 128,
+                    mem_align: 3,
+                    table_size: 1,
+                    table_align: 0,
+                }
+            ),
+            s => panic!("unexpected subsection {:?}", s),
+        }
+        match &subsections[1] {
+            DylinkSubsection::Needed(needed) => assert_eq!(*needed, ["la", "lb"]),
+            s => panic!("unexpected subsection {:?}", s),
+        }
+        match &subsections[2] {
+            DylinkSubsection::ExportInfo(exports) => {
+                assert_eq!(
+                    *exports,
+                    [ExportInfo {
+                        name: "f",
+                        flags: 2
+                    }]
+                )
+            }
+            s => panic!("unexpected subsection {:?}", s),
+        }
+        match &subsections[3] {
+            DylinkSubsection::ImportInfo(imports) => assert_eq!(
+                *imports,
+                [ImportInfo {
+                    module: "env",
+                    field: "g",
+                    flags: 0x10,
+                }]
+            ),
+            s => panic!("unexpected subsection {:?}", s),
+        }
+        match &subsections[4] {
+            DylinkSubsection::Unknown { ty: 42, data, .. } => assert_eq!(*data, [0xff]),
+            s => panic!("unexpected subsection {:?}", s),
+        }
+    }
+
+    #[test]
+    fn read_dylink_section_rejects_trailing_bytes() {
+        let data = [WASM_DYLINK_MEM_INFO, 5, 0, 0, 0, 0, 0];
+        assert!(DylinkSectionReader::new(&data, 0).unwrap().read().is_err());
+    }
+}
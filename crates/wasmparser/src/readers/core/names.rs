@@ -13,7 +13,10 @@
  * limitations under the License.
  */
 
-use crate::{BinaryReader, BinaryReaderError, Range, Result, SectionIterator, SectionReader};
+use crate::{
+    BinaryReader, BinaryReaderError, Range, Result, SectionIterator, SectionIteratorLimited,
+    SectionReader, SectionWithLimitedItems,
+};
 
 /// Represents a name for an index from the names section.
 #[derive(Debug, Copy, Clone)]
@@ -119,6 +122,41 @@ impl<'a> NamingReader<'a> {
     }
 }
 
+impl<'a> SectionReader for NamingReader<'a> {
+    type Item = Naming<'a>;
+
+    fn read(&mut self) -> Result<Self::Item> {
+        NamingReader::read(self)
+    }
+
+    fn eof(&self) -> bool {
+        self.reader.eof()
+    }
+
+    fn original_position(&self) -> usize {
+        NamingReader::original_position(self)
+    }
+
+    fn range(&self) -> Range {
+        self.reader.range()
+    }
+}
+
+impl<'a> SectionWithLimitedItems for NamingReader<'a> {
+    fn get_count(&self) -> u32 {
+        NamingReader::get_count(self)
+    }
+}
+
+impl<'a> IntoIterator for NamingReader<'a> {
+    type Item = Result<Naming<'a>>;
+    type IntoIter = SectionIteratorLimited<NamingReader<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        SectionIteratorLimited::new(self)
+    }
+}
+
 /// Represents a name map from the names custom section.
 #[derive(Debug, Copy, Clone)]
 pub struct NameMap<'a> {
@@ -205,6 +243,41 @@ impl<'a> IndirectNamingReader<'a> {
     }
 }
 
+impl<'a> SectionReader for IndirectNamingReader<'a> {
+    type Item = IndirectNaming<'a>;
+
+    fn read(&mut self) -> Result<Self::Item> {
+        IndirectNamingReader::read(self)
+    }
+
+    fn eof(&self) -> bool {
+        self.reader.eof()
+    }
+
+    fn original_position(&self) -> usize {
+        IndirectNamingReader::original_position(self)
+    }
+
+    fn range(&self) -> Range {
+        self.reader.range()
+    }
+}
+
+impl<'a> SectionWithLimitedItems for IndirectNamingReader<'a> {
+    fn get_count(&self) -> u32 {
+        IndirectNamingReader::get_indirect_count(self)
+    }
+}
+
+impl<'a> IntoIterator for IndirectNamingReader<'a> {
+    type Item = Result<IndirectNaming<'a>>;
+    type IntoIter = SectionIteratorLimited<IndirectNamingReader<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        SectionIteratorLimited::new(self)
+    }
+}
+
 /// Represents an indirect name map.
 #[derive(Debug, Copy, Clone)]
 pub struct IndirectNameMap<'a> {
@@ -263,6 +336,47 @@ pub enum Name<'a> {
 }
 
 /// A reader for the name custom section of a WebAssembly module.
+///
+/// # Examples
+///
+/// ```
+/// use wasmparser::{Name, NameSectionReader, Parser, Payload};
+///
+/// let wasm = wat::parse_str(r#"
+///     (module $m
+///         (func $f (param $x i32))
+///     )
+/// "#).unwrap();
+/// for payload in Parser::new(0).parse_all(&wasm) {
+///     let (data, data_offset) = match payload.unwrap() {
+///         Payload::CustomSection { name: "name", data, data_offset, .. } => (data, data_offset),
+///         _ => continue,
+///     };
+///     let names = NameSectionReader::new(data, data_offset).unwrap();
+///     for name in names {
+///         match name.unwrap() {
+///             Name::Module(name) => assert_eq!(name.get_name().unwrap(), "m"),
+///             Name::Function(map) => {
+///                 for naming in map.get_map().unwrap() {
+///                     let naming = naming.unwrap();
+///                     assert_eq!((naming.index, naming.name), (0, "f"));
+///                 }
+///             }
+///             Name::Local(map) => {
+///                 for locals in map.get_indirect_map().unwrap() {
+///                     let locals = locals.unwrap();
+///                     assert_eq!(locals.indirect_index, 0);
+///                     for naming in locals.get_map().unwrap() {
+///                         let naming = naming.unwrap();
+///                         assert_eq!((naming.index, naming.name), (0, "x"));
+///                     }
+///                 }
+///             }
+///             _ => {}
+///         }
+///     }
+/// }
+/// ```
 pub struct NameSectionReader<'a> {
     reader: BinaryReader<'a>,
 }
@@ -278,7 +392,7 @@ impl<'a> NameSectionReader<'a> {
     fn verify_section_end(&self, end: usize) -> Result<()> {
         if self.reader.buffer.len() < end {
             return Err(BinaryReaderError::new(
-                "name entry extends past end of the code section",
+                "name entry extends past end of the name section",
                 self.reader.original_offset + self.reader.buffer.len(),
             ));
         }
@@ -352,3 +466,59 @@ impl<'a> IntoIterator for NameSectionReader<'a> {
         SectionIterator::new(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_name_section() {
+        let unknown = [0x7f, 2, 0xaa, 0xbb];
+        let function_names = [1, 4, 1, 3, 1, b'f'];
+        let data = [&unknown[..], &function_names[..]].concat();
+        let mut reader = NameSectionReader::new(&data, 100).unwrap();
+        match reader.read().unwrap() {
+            Name::Unknown { ty, data, range } => {
+                assert_eq!(ty, 0x7f);
+                assert_eq!(data, [0xaa, 0xbb]);
+                assert_eq!(range, Range::new(102, 104));
+            }
+            name => panic!("unexpected name {:?}", name),
+        }
+        match reader.read().unwrap() {
+            Name::Function(map) => {
+                let names = map
+                    .get_map()
+                    .unwrap()
+                    .into_iter()
+                    .map(|n| n.map(|n| (n.index, n.name)))
+                    .collect::<Result<Vec<_>>>()
+                    .unwrap();
+                assert_eq!(names, [(3, "f")]);
+            }
+            name => panic!("unexpected name {:?}", name),
+        }
+        assert!(reader.eof());
+    }
+
+    #[test]
+    fn read_malformed_name_section() {
+        // The subsection claims more bytes than there are.
+        let data = [1, 10, 1, 0];
+        let mut reader = NameSectionReader::new(&data, 100).unwrap();
+        let err = reader.read().unwrap_err();
+        assert_eq!(err.offset(), 104);
+
+        // The function name isn't valid UTF-8.
+        let data = [1, 4, 1, 0, 1, 0xff];
+        let mut reader = NameSectionReader::new(&data, 100).unwrap();
+        let map = match reader.read().unwrap() {
+            Name::Function(map) => map,
+            name => panic!("unexpected name {:?}", name),
+        };
+        let mut names = map.get_map().unwrap().into_iter();
+        let err = names.next().unwrap().unwrap_err();
+        assert_eq!(err.offset(), 105);
+        assert!(names.next().is_none());
+    }
+}
@@ -41,6 +41,14 @@ pub enum Type {
 pub enum TypeDef {
     /// The type is a function.
     Func(FuncType),
+    /// The type is a struct.
+    ///
+    /// Part of the GC proposal.
+    Struct(StructType),
+    /// The type is an array.
+    ///
+    /// Part of the GC proposal.
+    Array(ArrayType),
 }
 
 /// Represents a type of a function in a WebAssembly module.
@@ -52,6 +60,40 @@ pub struct FuncType {
     pub returns: Box<[Type]>,
 }
 
+/// Represents a type of a struct in a WebAssembly module.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct StructType {
+    /// The struct's fields.
+    pub fields: Box<[FieldType]>,
+}
+
+/// Represents a type of an array in a WebAssembly module.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ArrayType {
+    /// The type of the array's elements.
+    pub field: FieldType,
+}
+
+/// Represents the type of a struct field or of an array's elements.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct FieldType {
+    /// The type stored in the field.
+    pub element_type: StorageType,
+    /// Whether or not the field is mutable.
+    pub mutable: bool,
+}
+
+/// Represents the type stored in a struct field or array element.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum StorageType {
+    /// The packed type i8.
+    I8,
+    /// The packed type i16.
+    I16,
+    /// A value type.
+    Val(Type),
+}
+
 /// Represents a table's type.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct TableType {
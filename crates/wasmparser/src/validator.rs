@@ -14,8 +14,9 @@
  */
 
 use crate::{
-    limits::*, BinaryReaderError, Encoding, FunctionBody, Parser, Payload, Range, Result,
-    SectionReader, SectionWithLimitedItems, Type, WASM_COMPONENT_VERSION, WASM_MODULE_VERSION,
+    limits::*, BinaryReaderError, Encoding, ExternalKind, FunctionBody, Parser, Payload, Range,
+    Result, SectionReader, SectionWithLimitedItems, Type, WASM_COMPONENT_VERSION,
+    WASM_MODULE_VERSION,
 };
 use std::mem;
 use std::sync::Arc;
@@ -38,12 +39,109 @@ pub fn validate(bytes: &[u8]) -> Result<Types> {
     Validator::new().validate_all(bytes)
 }
 
+/// Same as [`validate`], except that on failure the error is rendered along
+/// with the name of the section it occurred in and a hex dump of the bytes
+/// around its offset.
+///
+/// This is meant as a debugging aid; use [`validate`] to handle errors
+/// programmatically.
+pub fn validate_and_explain(bytes: &[u8]) -> std::result::Result<Types, String> {
+    let err = match validate(bytes) {
+        Ok(types) => return Ok(types),
+        Err(e) => e,
+    };
+    let offset = err.offset();
+    let mut explanation = err.to_string();
+    if let Some(section) = section_containing(bytes, offset) {
+        explanation.push_str(&format!("\nin {}", section));
+    }
+
+    let start = offset.min(bytes.len()).saturating_sub(8);
+    let end = (start + 16).min(bytes.len());
+    if start < end {
+        let mut dump = format!("{:08x}:", start);
+        let mut marker = " ".repeat(dump.len());
+        for (i, byte) in bytes[start..end].iter().enumerate() {
+            dump.push_str(&format!(" {:02x}", byte));
+            marker.push_str(if start + i == offset { " ^^" } else { "   " });
+        }
+        explanation.push_str(&format!("\n{}\n{}", dump, marker.trim_end()));
+    }
+    Err(explanation)
+}
+
+/// Returns a description of the innermost section of `bytes` whose contents
+/// include `offset`, as far as `bytes` can be parsed.
+fn section_containing(bytes: &[u8], offset: usize) -> Option<String> {
+    use crate::Payload::*;
+    let mut result = None;
+    for payload in Parser::new(0).parse_all(bytes) {
+        let (name, range) = match &payload {
+            Ok(payload) => match payload {
+                TypeSection(s) => ("type section".to_string(), s.range()),
+                ImportSection(s) => ("import section".to_string(), s.range()),
+                FunctionSection(s) => ("function section".to_string(), s.range()),
+                TableSection(s) => ("table section".to_string(), s.range()),
+                MemorySection(s) => ("memory section".to_string(), s.range()),
+                TagSection(s) => ("tag section".to_string(), s.range()),
+                GlobalSection(s) => ("global section".to_string(), s.range()),
+                ExportSection(s) => ("export section".to_string(), s.range()),
+                StartSection { range, .. } => ("start section".to_string(), *range),
+                ElementSection(s) => ("element section".to_string(), s.range()),
+                DataCountSection { range, .. } => ("data count section".to_string(), *range),
+                DataSection(s) => ("data section".to_string(), s.range()),
+                CodeSectionStart { range, .. } => ("code section".to_string(), *range),
+                CustomSection { name, range, .. } => (format!("custom section `{}`", name), *range),
+                UnknownSection { id, range, .. } => (format!("unknown section {}", id), *range),
+                ComponentTypeSection(s) => ("component type section".to_string(), s.range()),
+                ComponentImportSection(s) => ("component import section".to_string(), s.range()),
+                ComponentFunctionSection(s) => {
+                    ("component function section".to_string(), s.range())
+                }
+                ModuleSection { range, .. } => ("module section".to_string(), *range),
+                ComponentSection { range, .. } => ("component section".to_string(), *range),
+                InstanceSection(s) => ("instance section".to_string(), s.range()),
+                ComponentExportSection(s) => ("component export section".to_string(), s.range()),
+                ComponentStartSection(s) => ("component start section".to_string(), s.range()),
+                AliasSection(s) => ("alias section".to_string(), s.range()),
+                Version { .. } | CodeSectionEntry(_) | End(_) => continue,
+            },
+            Err(_) => break,
+        };
+        // Nested modules and components are followed by their own sections,
+        // so the last match is the innermost one.
+        if range.start <= offset && offset <= range.end {
+            result = Some(name);
+        }
+    }
+    result
+}
+
 #[test]
 fn test_validate() {
     assert!(validate(&[0x0, 0x61, 0x73, 0x6d, 0x1, 0x0, 0x0, 0x0]).is_ok());
     assert!(validate(&[0x0, 0x61, 0x73, 0x6d, 0x2, 0x0, 0x0, 0x0]).is_err());
 }
 
+#[test]
+fn test_validate_and_explain() {
+    assert!(validate_and_explain(&[0x0, 0x61, 0x73, 0x6d, 0x1, 0x0, 0x0, 0x0]).is_ok());
+
+    let bytes = wat::parse_str("(module (func (result i32) i64.const 0))").unwrap();
+    let offset = validate(&bytes).err().unwrap().offset();
+    let explanation = validate_and_explain(&bytes).err().unwrap();
+    assert!(explanation.starts_with("type mismatch"));
+    assert!(explanation.contains(&format!("(at offset {})", offset)));
+    assert!(explanation.contains("in code section"));
+    let dump = bytes[offset - 2..=offset]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(" ");
+    assert!(explanation.contains(&dump), "{}", explanation);
+    assert!(explanation.ends_with("^^"), "{}", explanation);
+}
+
 mod component;
 mod core;
 mod func;
@@ -53,8 +151,8 @@ pub mod types;
 use self::component::*;
 pub use self::core::ValidatorResources;
 use self::core::*;
-use self::types::{TypeList, Types};
-pub use func::FuncValidator;
+use self::types::{AtomicAccess, MemoryAccess, TypeList, Types};
+pub use func::{FuncSnapshot, FuncValidator};
 
 fn check_max(cur_len: usize, amt_added: u32, max: usize, desc: &str, offset: usize) -> Result<()> {
     if max
@@ -75,6 +173,83 @@ fn check_max(cur_len: usize, amt_added: u32, max: usize, desc: &str, offset: usi
     Ok(())
 }
 
+/// Like [`check_max`], but reports which feature lifts the limit when only a
+/// single item is allowed.
+fn check_max_or_proposal(
+    cur_len: usize,
+    amt_added: u32,
+    max: usize,
+    desc: &str,
+    feature: &'static str,
+    offset: usize,
+) -> Result<()> {
+    if max == 1 && cur_len + amt_added as usize > 1 {
+        return Err(BinaryReaderError::feature_disabled(
+            format!(
+                "multiple {} require the {} proposal",
+                desc,
+                feature.replace('_', "-")
+            ),
+            feature,
+            offset,
+        ));
+    }
+    check_max(cur_len, amt_added, max, desc, offset)
+}
+
+/// Returns the offset up to which the input has been validated once `payload`
+/// has been passed to [`Validator::payload`].
+fn validated_offset(payload: &Payload) -> usize {
+    use crate::Payload::*;
+    match payload {
+        Version { range, .. }
+        | StartSection { range, .. }
+        | DataCountSection { range, .. }
+        | CustomSection { range, .. }
+        | UnknownSection { range, .. } => range.end,
+
+        // Nested modules and components and the code section are validated
+        // through subsequent payloads, so only their headers are done.
+        ModuleSection { range, .. }
+        | ComponentSection { range, .. }
+        | CodeSectionStart { range, .. } => range.start,
+        CodeSectionEntry(body) => body.range().end,
+
+        TypeSection(s) => s.range().end,
+        ImportSection(s) => s.range().end,
+        FunctionSection(s) => s.range().end,
+        TableSection(s) => s.range().end,
+        MemorySection(s) => s.range().end,
+        TagSection(s) => s.range().end,
+        GlobalSection(s) => s.range().end,
+        ExportSection(s) => s.range().end,
+        ElementSection(s) => s.range().end,
+        DataSection(s) => s.range().end,
+        ComponentTypeSection(s) => s.range().end,
+        ComponentImportSection(s) => s.range().end,
+        ComponentFunctionSection(s) => s.range().end,
+        InstanceSection(s) => s.range().end,
+        ComponentExportSection(s) => s.range().end,
+        ComponentStartSection(s) => s.range().end,
+        AliasSection(s) => s.range().end,
+
+        End(offset) => *offset,
+    }
+}
+
+/// How [`Validator::validate_all`] and friends validate function bodies.
+#[derive(Clone, Copy)]
+enum Parallelism {
+    /// On the current thread.
+    Serial,
+    /// On a dedicated `rayon` thread pool with this many threads.
+    #[cfg(feature = "rayon")]
+    Threads(usize),
+    /// On the global `rayon` thread pool.
+    #[cfg(feature = "rayon")]
+    Rayon,
+}
+
 /// Validator for a WebAssembly binary module or component.
 ///
 /// This structure encapsulates state necessary to validate a WebAssembly
@@ -118,8 +293,33 @@ pub struct Validator {
     /// Enabled WebAssembly feature flags, dictating what's valid and what
     /// isn't.
     features: WasmFeatures,
+
+    /// Whether [`Validator::validate_all`] rejects defined functions that can
+    /// never be reached.
+    require_live_functions: bool,
+
+    /// Whether [`Validator::validate_all`] records the loads and stores of
+    /// function bodies.
+    record_memory_accesses: bool,
+
+    /// The only kind of top-level binary accepted, if restricted.
+    accepted_encoding: Option<Encoding>,
+
+    /// Callback notified of how far [`Validator::validate_all`] has gotten.
+    on_progress: Option<Box<dyn FnMut(usize, usize) + Send>>,
+}
+
+/// A snapshot of how far a [`Validator`] has gotten through its input,
+/// created with [`Validator::save_state`].
+#[derive(Clone)]
+pub struct ValidatorCheckpoint {
+    state: State,
+    types: TypeList,
+    module: Option<ModuleState>,
+    components: Vec<ComponentState>,
 }
 
+#[derive(Clone)]
 enum State {
     /// A header has not yet been parsed.
     ///
@@ -201,6 +401,22 @@ impl Default for State {
 }
 
 /// Flags for features that are enabled for validation.
+///
+/// Besides setting the fields directly, each feature can be toggled with a
+/// chainable `with_*` method:
+///
+/// ```
+/// use wasmparser::WasmFeatures;
+///
+/// let features = WasmFeatures::default()
+///     .with_exceptions(true)
+///     .with_memory64(true)
+///     .with_simd(false);
+/// assert!(features.exceptions);
+/// assert!(features.memory64);
+/// assert!(!features.simd);
+/// assert!(features.bulk_memory);
+/// ```
 #[derive(Hash, Debug, Copy, Clone)]
 pub struct WasmFeatures {
     /// The WebAssembly `mutable-global` proposal (enabled by default)
@@ -225,6 +441,11 @@ pub struct WasmFeatures {
     pub tail_call: bool,
     /// Whether or not only deterministic instructions are allowed
     pub deterministic_only: bool,
+    /// Whether or not floating-point types and instructions are allowed
+    /// (enabled by default)
+    ///
+    /// Disabling this is useful for targets without floating-point hardware.
+    pub floats: bool,
     /// The WebAssembly multi memory proposal
     pub multi_memory: bool,
     /// The WebAssembly exception handling proposal
@@ -235,30 +456,177 @@ pub struct WasmFeatures {
     pub extended_const: bool,
     /// The WebAssembly component model proposal.
     pub component_model: bool,
+    /// The WebAssembly GC proposal.
+    pub gc: bool,
 }
 
 impl WasmFeatures {
-    pub(crate) fn check_value_type(&self, ty: Type) -> Result<(), &'static str> {
+    /// Returns the features of the WebAssembly 1.0 specification.
+    ///
+    /// Besides the MVP this only includes importing and exporting mutable
+    /// globals, which the released specification already allows.
+    pub fn wasm1() -> WasmFeatures {
+        WasmFeatures {
+            mutable_global: true,
+            saturating_float_to_int: false,
+            sign_extension: false,
+            reference_types: false,
+            multi_value: false,
+            bulk_memory: false,
+            simd: false,
+            relaxed_simd: false,
+            threads: false,
+            tail_call: false,
+            deterministic_only: false,
+            floats: true,
+            multi_memory: false,
+            exceptions: false,
+            memory64: false,
+            extended_const: false,
+            component_model: false,
+            gc: false,
+        }
+    }
+
+    /// Returns the features of the WebAssembly 2.0 specification.
+    ///
+    /// These are the features of [`WasmFeatures::wasm1`] along with the
+    /// non-trapping float-to-int conversions, sign extension operators,
+    /// multi-value, reference types, bulk memory operations and fixed-width
+    /// SIMD proposals.
+    pub fn wasm2() -> WasmFeatures {
+        WasmFeatures {
+            saturating_float_to_int: true,
+            sign_extension: true,
+            reference_types: true,
+            multi_value: true,
+            bulk_memory: true,
+            simd: true,
+            ..WasmFeatures::wasm1()
+        }
+    }
+
+    /// Returns a set of features with every proposal enabled.
+    ///
+    /// This does not turn on [`WasmFeatures::deterministic_only`], which
+    /// restricts rather than extends what is accepted.
+    pub const fn all() -> WasmFeatures {
+        WasmFeatures {
+            mutable_global: true,
+            saturating_float_to_int: true,
+            sign_extension: true,
+            reference_types: true,
+            multi_value: true,
+            bulk_memory: true,
+            simd: true,
+            relaxed_simd: true,
+            threads: true,
+            tail_call: true,
+            deterministic_only: false,
+            floats: true,
+            multi_memory: true,
+            exceptions: true,
+            memory64: true,
+            extended_const: true,
+            component_model: true,
+            gc: true,
+        }
+    }
+
+    /// Returns a set of features with everything disabled, including the
+    /// features which are enabled by default such as floats.
+    pub const fn none() -> WasmFeatures {
+        WasmFeatures {
+            mutable_global: false,
+            saturating_float_to_int: false,
+            sign_extension: false,
+            reference_types: false,
+            multi_value: false,
+            bulk_memory: false,
+            simd: false,
+            relaxed_simd: false,
+            threads: false,
+            tail_call: false,
+            deterministic_only: false,
+            floats: false,
+            multi_memory: false,
+            exceptions: false,
+            memory64: false,
+            extended_const: false,
+            component_model: false,
+            gc: false,
+        }
+    }
+
+    /// Checks that `ty` may be used, returning the error message and the name
+    /// of the feature which must be enabled otherwise.
+    pub(crate) fn check_value_type(&self, ty: Type) -> Result<(), (&'static str, &'static str)> {
         match ty {
-            Type::I32 | Type::I64 | Type::F32 | Type::F64 => Ok(()),
+            Type::I32 | Type::I64 => Ok(()),
+            Type::F32 | Type::F64 => {
+                if self.floats {
+                    Ok(())
+                } else {
+                    Err(("floating point not supported on this target", "floats"))
+                }
+            }
             Type::FuncRef | Type::ExternRef => {
                 if self.reference_types {
                     Ok(())
                 } else {
-                    Err("reference types support is not enabled")
+                    Err(("reference types support is not enabled", "reference_types"))
                 }
             }
             Type::V128 => {
                 if self.simd {
                     Ok(())
                 } else {
-                    Err("SIMD support is not enabled")
+                    Err(("SIMD support is not enabled", "simd"))
                 }
             }
         }
     }
 }
 
+macro_rules! define_with_features {
+    ($($field:ident => $method:ident,)*) => {
+        impl WasmFeatures {
+            $(
+                #[doc = concat!(
+                    "Returns these features with [`WasmFeatures::",
+                    stringify!($field),
+                    "`] set to `enabled`.",
+                )]
+                pub const fn $method(mut self, enabled: bool) -> WasmFeatures {
+                    self.$field = enabled;
+                    self
+                }
+            )*
+        }
+    };
+}
+
+define_with_features! {
+    mutable_global => with_mutable_global,
+    saturating_float_to_int => with_saturating_float_to_int,
+    sign_extension => with_sign_extension,
+    reference_types => with_reference_types,
+    multi_value => with_multi_value,
+    bulk_memory => with_bulk_memory,
+    simd => with_simd,
+    relaxed_simd => with_relaxed_simd,
+    threads => with_threads,
+    tail_call => with_tail_call,
+    deterministic_only => with_deterministic_only,
+    floats => with_floats,
+    multi_memory => with_multi_memory,
+    exceptions => with_exceptions,
+    memory64 => with_memory64,
+    extended_const => with_extended_const,
+    component_model => with_component_model,
+    gc => with_gc,
+}
+
 impl Default for WasmFeatures {
     fn default() -> WasmFeatures {
         WasmFeatures {
@@ -271,6 +639,7 @@ impl Default for WasmFeatures {
             memory64: false,
             extended_const: false,
             component_model: false,
+            gc: false,
             deterministic_only: cfg!(feature = "deterministic"),
 
             // on-by-default features
@@ -281,6 +650,7 @@ impl Default for WasmFeatures {
             multi_value: true,
             reference_types: true,
             simd: true,
+            floats: true,
         }
     }
 }
@@ -331,6 +701,64 @@ impl Validator {
         &self.features
     }
 
+    /// Configures this validator to only accept core WebAssembly modules.
+    ///
+    /// A component version header is rejected immediately, regardless of
+    /// whether the component model feature is enabled.
+    pub fn module_only(&mut self) -> &mut Self {
+        self.accepted_encoding = Some(Encoding::Module);
+        self
+    }
+
+    /// Configures this validator to only accept components.
+    ///
+    /// A module version header is rejected immediately, although components
+    /// may still contain nested core modules. Note that components are only
+    /// valid when the component model feature is enabled.
+    pub fn component_only(&mut self) -> &mut Self {
+        self.accepted_encoding = Some(Encoding::Component);
+        self
+    }
+
+    /// Configures whether [`Validator::validate_all`] rejects modules that
+    /// define dead functions.
+    ///
+    /// When enabled, every defined function must be exported, referenced by
+    /// an element segment or constant expression, called from some function
+    /// body, or be the start function. This is useful for verifying the
+    /// output of tree-shaking tools.
+    ///
+    /// This is disabled by default. It only applies to
+    /// [`Validator::validate_all`] since that's the only place where all
+    /// function bodies are seen by the validator.
+    pub fn require_live_functions(&mut self, require: bool) -> &mut Self {
+        self.require_live_functions = require;
+        self
+    }
+
+    /// Configures whether [`Validator::validate_all`] records every load and
+    /// store in the function bodies it validates.
+    ///
+    /// The recorded accesses are available from [`Types::memory_accesses`].
+    /// This is disabled by default since it requires reading every body a
+    /// second time.
+    pub fn record_memory_accesses(&mut self, record: bool) -> &mut Self {
+        self.record_memory_accesses = record;
+        self
+    }
+
+    /// Registers a callback which [`Validator::validate_all`] invokes after
+    /// each payload it validates.
+    ///
+    /// The callback receives the number of bytes validated so far followed by
+    /// the total number of bytes being validated, which is enough to render a
+    /// progress bar for large modules. Offsets passed to the callback never
+    /// decrease.
+    pub fn on_progress(&mut self, f: impl FnMut(usize, usize) + Send + 'static) -> &mut Self {
+        self.on_progress = Some(Box::new(f));
+        self
+    }
+
     /// Validates an entire in-memory module or component with this validator.
     ///
     /// This function will internally create a [`Parser`] to parse the `bytes`
@@ -340,12 +768,46 @@ impl Validator {
     /// Upon success, the type information for the top-level module or component
     /// will be returned.
     pub fn validate_all(&mut self, bytes: &[u8]) -> Result<Types> {
+        self.validate_all_with(bytes, Parallelism::Serial)
+    }
+
+    /// Same as [`Validator::validate_all`], except that function bodies are
+    /// validated on up to `threads` threads.
+    ///
+    /// Function bodies are validated once the whole input has been parsed, so
+    /// the results are the same as with [`Validator::validate_all`]. If
+    /// several functions are invalid, the error of the function with the
+    /// lowest index is returned. Passing a `threads` of 0 or 1 validates
+    /// everything on the current thread, and an error is returned if the
+    /// threads can't be created.
+    ///
+    /// This requires the `rayon` feature of this crate.
+    #[cfg(feature = "rayon")]
+    pub fn validate_all_parallel(&mut self, bytes: &[u8], threads: usize) -> Result<Types> {
+        let parallelism = match threads {
+            0 | 1 => Parallelism::Serial,
+            _ => Parallelism::Threads(threads),
+        };
+        self.validate_all_with(bytes, parallelism)
+    }
+
+    /// Same as [`Validator::validate_all_parallel`], except that function
+    /// bodies are validated on the global [`rayon`] thread pool.
+    ///
+    /// This requires the `rayon` feature of this crate.
+    #[cfg(feature = "rayon")]
+    pub fn validate_all_rayon(&mut self, bytes: &[u8]) -> Result<Types> {
+        self.validate_all_with(bytes, Parallelism::Rayon)
+    }
+
+    fn validate_all_with(&mut self, bytes: &[u8], parallelism: Parallelism) -> Result<Types> {
         let mut functions_to_validate = Vec::new();
         let mut last_types = None;
         for payload in Parser::new(0).parse_all(bytes) {
-            match self.payload(&payload?)? {
-                ValidPayload::Func(a, b) => {
-                    functions_to_validate.push((a, b));
+            let payload = payload?;
+            match self.payload(&payload)? {
+                ValidPayload::Func(validator, body) => {
+                    functions_to_validate.push((validator, body));
                 }
                 ValidPayload::End(types) => {
                     // Only the last (top-level) type information will be returned
@@ -353,13 +815,199 @@ impl Validator {
                 }
                 _ => {}
             }
+            if let Some(on_progress) = &mut self.on_progress {
+                // Function bodies are only validated once everything else has
+                // been, so progress can't go past the first of them yet.
+                let offset = match functions_to_validate.first() {
+                    Some((_, body)) => body.range().start,
+                    None => validated_offset(&payload),
+                };
+                on_progress(offset, bytes.len());
+            }
         }
 
-        for (mut validator, body) in functions_to_validate {
-            validator.validate(&body)?;
+        let mut types = last_types.unwrap();
+        let mut function_bodies = Vec::new();
+        if !functions_to_validate.is_empty() {
+            self.validate_functions(
+                functions_to_validate,
+                parallelism,
+                &mut types,
+                &mut function_bodies,
+            )?;
+            if let Some(on_progress) = &mut self.on_progress {
+                on_progress(bytes.len(), bytes.len());
+            }
+        }
+
+        if self.require_live_functions {
+            // Bodies of the same module are always contiguous, so check each
+            // run of bodies sharing a module in turn.
+            let mut rest = &function_bodies[..];
+            while let Some((module, _, _)) = rest.first() {
+                let len = rest
+                    .iter()
+                    .position(|(m, _, _)| !Arc::ptr_eq(m, module))
+                    .unwrap_or(rest.len());
+                let bodies = rest[..len]
+                    .iter()
+                    .map(|(_, body, called)| (*body, called.clone()))
+                    .collect::<Vec<_>>();
+                module.check_live_functions(&bodies)?;
+                rest = &rest[len..];
+            }
+        }
+
+        Ok(types)
+    }
+
+    /// Validates the bodies of `funcs`, splitting them across threads as
+    /// specified by `parallelism`, and then records what `types` reports
+    /// about each of them in order.
+    fn validate_functions<'a>(
+        &mut self,
+        mut funcs: Vec<(FuncValidator<ValidatorResources>, FunctionBody<'a>)>,
+        parallelism: Parallelism,
+        types: &mut Types,
+        function_bodies: &mut Vec<(Arc<Module>, FunctionBody<'a>, Vec<u32>)>,
+    ) -> Result<()> {
+        let results = match parallelism {
+            Parallelism::Serial => funcs
+                .iter_mut()
+                .map(|(validator, body)| validator.validate(body))
+                .collect::<Vec<_>>(),
+            #[cfg(feature = "rayon")]
+            Parallelism::Threads(threads) => {
+                use rayon::prelude::*;
+                let pool = rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .map_err(|e| {
+                        BinaryReaderError::new(format!("failed to create a thread pool: {}", e), 0)
+                    })?;
+                pool.install(|| {
+                    funcs
+                        .par_iter_mut()
+                        .map(|(validator, body)| validator.validate(body))
+                        .collect::<Vec<_>>()
+                })
+            }
+            #[cfg(feature = "rayon")]
+            Parallelism::Rayon => {
+                use rayon::prelude::*;
+                funcs
+                    .par_iter_mut()
+                    .map(|(validator, body)| validator.validate(body))
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        for ((mut validator, body), result) in funcs.into_iter().zip(results) {
+            result?;
+            let (called, calls_indirectly) = validator.take_calls();
+            if self.require_live_functions {
+                function_bodies.push((validator.resources().0.clone(), body, called.clone()));
+            }
+            // Bodies of modules nested in a component don't contribute to the
+            // returned type information.
+            if types.is_module(&validator.resources().0) {
+                types.max_control_depth =
+                    types.max_control_depth.max(validator.max_control_depth());
+                types.instruction_count += validator.operator_count();
+                types
+                    .function_operator_counts
+                    .push(validator.operator_count() as usize);
+                types.function_calls.push((called, calls_indirectly));
+                if self.record_memory_accesses {
+                    let mut reader = body.get_operators_reader()?;
+                    reader.allow_memarg64(self.features.memory64);
+                    for op in reader.into_iter_with_offsets() {
+                        let (op, position) = op?;
+                        types
+                            .memory_accesses
+                            .extend(MemoryAccess::new(&op, position));
+                        types
+                            .atomic_accesses
+                            .extend(AtomicAccess::new(&op, position));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates `bytes` with the smallest set of proposals it needs.
+    ///
+    /// This first checks that `bytes` is valid with every proposal enabled,
+    /// failing otherwise, and then greedily turns off every proposal which the
+    /// module or component doesn't need. The input is then validated with this
+    /// validator using exactly that set of features, which are returned along
+    /// with the resulting type information. The features this validator was
+    /// configured with are restored afterwards.
+    ///
+    /// Only proposals are turned off. Since [`WasmFeatures::deterministic_only`]
+    /// and [`WasmFeatures::floats`] restrict rather than extend what's valid,
+    /// the former is always disabled and the latter always enabled in the
+    /// returned features.
+    ///
+    /// Note that this validates `bytes` many times over: once with every
+    /// proposal enabled, once for each attempt at turning a proposal off
+    /// (repeated until no more can be), and once more with the final set, so
+    /// it's considerably slower than [`Validator::validate_all`].
+    pub fn validate_all_minimal(&mut self, bytes: &[u8]) -> Result<(Types, WasmFeatures)> {
+        let proposals: [fn(&mut WasmFeatures) -> &mut bool; 16] = [
+            |f| &mut f.mutable_global,
+            |f| &mut f.saturating_float_to_int,
+            |f| &mut f.sign_extension,
+            |f| &mut f.reference_types,
+            |f| &mut f.multi_value,
+            |f| &mut f.bulk_memory,
+            |f| &mut f.simd,
+            |f| &mut f.relaxed_simd,
+            |f| &mut f.threads,
+            |f| &mut f.tail_call,
+            |f| &mut f.multi_memory,
+            |f| &mut f.exceptions,
+            |f| &mut f.memory64,
+            |f| &mut f.extended_const,
+            |f| &mut f.component_model,
+            |f| &mut f.gc,
+        ];
+        let mut features = WasmFeatures {
+            deterministic_only: false,
+            floats: true,
+            ..WasmFeatures::default()
+        };
+        for proposal in proposals.iter() {
+            *proposal(&mut features) = true;
+        }
+        Validator::new_with_features(features).validate_all(bytes)?;
+
+        // Some proposals are only required by others, so keep going until no
+        // more proposals can be turned off.
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for proposal in proposals.iter() {
+                if !*proposal(&mut features) {
+                    continue;
+                }
+                let mut candidate = features;
+                *proposal(&mut candidate) = false;
+                if Validator::new_with_features(candidate)
+                    .validate_all(bytes)
+                    .is_ok()
+                {
+                    features = candidate;
+                    changed = true;
+                }
+            }
         }
 
-        Ok(last_types.unwrap())
+        let configured = mem::replace(&mut self.features, features);
+        let types = self.validate_all(bytes);
+        self.features = configured;
+        Ok((types?, features))
     }
 
     /// Convenience function to validate a single [`Payload`].
@@ -432,10 +1080,103 @@ impl Validator {
         Ok(ValidPayload::Ok)
     }
 
+    /// Returns whether the validator has seen the end of the top-level module
+    /// or component, meaning that no more payloads are expected.
+    pub fn is_complete(&self) -> bool {
+        matches!(self.state, State::End)
+    }
+
+    /// Returns the exports of the component currently being validated which
+    /// have been seen so far, in the order they appear.
+    ///
+    /// This is empty when no component is being validated.
+    pub fn current_component_exports(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (&str, &crate::ComponentExportKind)> + '_ {
+        let exports = match self.components.last() {
+            Some(component) => &component.export_kinds[..],
+            None => &[],
+        };
+        exports.iter().map(|(name, kind)| (name.as_str(), kind))
+    }
+
+    /// Saves how far this validator has gotten through its input, so that
+    /// validation can later be resumed from this point with
+    /// [`Validator::restore_state`].
+    ///
+    /// Only the progress through the input is saved, not the configuration
+    /// of this validator such as its [`WasmFeatures`]. Function bodies handed
+    /// out as [`ValidPayload::Func`] before the checkpoint was taken are not
+    /// part of it either and must still be validated separately.
+    pub fn save_state(&self) -> ValidatorCheckpoint {
+        ValidatorCheckpoint {
+            state: self.state.clone(),
+            types: self.types.clone(),
+            module: self.module.clone(),
+            components: self.components.clone(),
+        }
+    }
+
+    /// Resumes validation from `checkpoint`, discarding the progress of this
+    /// validator.
+    ///
+    /// The configuration of this validator is kept, so it should match that
+    /// of the validator the checkpoint was saved from.
+    pub fn restore_state(&mut self, checkpoint: ValidatorCheckpoint) {
+        self.state = checkpoint.state;
+        self.types = checkpoint.types;
+        self.module = checkpoint.module;
+        self.components = checkpoint.components;
+    }
+
+    /// Returns whether a section with the given `id` may come next in the
+    /// module or component currently being validated.
+    ///
+    /// Custom sections are accepted anywhere after the header. Module
+    /// sections must follow the sections seen so far in the order mandated by
+    /// the spec, and sections for proposals which aren't enabled are never
+    /// accepted. This only checks the position of the section, not its
+    /// contents.
+    pub fn can_accept(&self, id: u8) -> bool {
+        match self.state {
+            State::Unparsed(_) | State::End => false,
+            State::Component => id == 0 || (self.features.component_model && id <= 9),
+            State::Module => {
+                if id == 0 {
+                    return true;
+                }
+                let order = match Order::from_section_id(id) {
+                    Some(order) => order,
+                    None => return false,
+                };
+                let enabled = match order {
+                    Order::Tag => self.features.exceptions,
+                    Order::DataCount => self.features.bulk_memory,
+                    _ => true,
+                };
+                enabled && self.module.as_ref().unwrap().order() < order
+            }
+        }
+    }
+
     /// Validates [`Payload::Version`](crate::Payload).
     pub fn version(&mut self, num: u32, encoding: Encoding, range: &Range) -> Result<()> {
         match &self.state {
             State::Unparsed(expected) => {
+                if expected.is_none() {
+                    match self.accepted_encoding {
+                        Some(accepted) if accepted != encoding => {
+                            return Err(BinaryReaderError::new(
+                                match encoding {
+                                    Encoding::Module => "modules are not accepted",
+                                    Encoding::Component => "components are not accepted",
+                                },
+                                range.start,
+                            ));
+                        }
+                        _ => {}
+                    }
+                }
                 if let Some(expected) = expected {
                     if *expected != encoding {
                         return Err(BinaryReaderError::new(
@@ -467,8 +1208,9 @@ impl Validator {
             }
             (Encoding::Component, WASM_COMPONENT_VERSION) => {
                 if !self.features.component_model {
-                    return Err(BinaryReaderError::new(
+                    return Err(BinaryReaderError::feature_disabled(
                         "WebAssembly component model feature not enabled",
+                        "component_model",
                         range.start,
                     ));
                 }
@@ -569,11 +1311,12 @@ impl Validator {
             section,
             "table",
             |state, _, _, count, offset| {
-                check_max(
+                check_max_or_proposal(
                     state.module.tables.len(),
                     count,
                     state.module.max_tables(&features),
                     "tables",
+                    "reference_types",
                     offset,
                 )?;
                 state.module.assert_mut().tables.reserve(count as usize);
@@ -594,11 +1337,12 @@ impl Validator {
             section,
             "memory",
             |state, features, _, count, offset| {
-                check_max(
+                check_max_or_proposal(
                     state.module.memories.len(),
                     count,
                     state.module.max_memories(features),
                     "memories",
+                    "multi_memory",
                     offset,
                 )?;
                 state.module.assert_mut().memories.reserve(count as usize);
@@ -615,8 +1359,9 @@ impl Validator {
     /// This method should only be called when parsing a module.
     pub fn tag_section(&mut self, section: &crate::TagSectionReader<'_>) -> Result<()> {
         if !self.features.exceptions {
-            return Err(BinaryReaderError::new(
+            return Err(BinaryReaderError::feature_disabled(
                 "exceptions proposal not enabled",
+                "exceptions",
                 section.range().start,
             ));
         }
@@ -692,7 +1437,15 @@ impl Validator {
             |state, features, _, e, offset| {
                 let module = state.module.assert_mut();
                 let ty = module.export_to_entity_type(&e, offset)?;
-                module.add_export(e.name, ty, features, offset)
+                module.add_export(e.name, ty, features, offset)?;
+                match e.kind {
+                    ExternalKind::Func => module.exported_functions.insert(e.index),
+                    ExternalKind::Table => module.exported_tables.insert(e.index),
+                    ExternalKind::Memory => module.exported_memories.insert(e.index),
+                    ExternalKind::Global => module.exported_globals.insert(e.index),
+                    ExternalKind::Tag => false,
+                };
+                Ok(())
             },
         )
     }
@@ -713,6 +1466,7 @@ impl Validator {
                 offset,
             ));
         }
+        state.module.assert_mut().start_function = Some(func);
 
         Ok(())
     }
@@ -822,6 +1576,8 @@ impl Validator {
         let offset = body.range().start;
         self.state.ensure_module_state("code", offset)?;
         let state = self.module.as_mut().unwrap();
+        let range = body.range();
+        state.function_sizes.push(range.end - range.start);
 
         Ok(FuncValidator::new(
             state.next_code_entry_type(offset)?,
@@ -1043,7 +1799,11 @@ impl Validator {
             |components, types, _, export, offset| {
                 let current = components.last_mut().unwrap();
                 let ty = current.export_to_entity_type(&export, types, offset)?;
-                current.add_export(export.name, ty, offset)
+                current.add_export(export.name, ty, offset)?;
+                current
+                    .export_kinds
+                    .push((export.name.to_string(), export.kind));
+                Ok(())
             },
         )
     }
@@ -1105,7 +1865,7 @@ impl Validator {
                 offset,
             )),
             State::Module => {
-                let mut state = self.module.take().unwrap();
+                let state = self.module.take().unwrap();
                 state.validate_end(offset)?;
 
                 // If there's a parent component, we'll add a module to the parent state
@@ -1115,18 +1875,14 @@ impl Validator {
                     self.state = State::Component;
                 }
 
-                Ok(Types::from_module(
-                    self.types.commit(),
-                    state.module.arc().clone(),
-                ))
+                Ok(Types::from_module(self.types.commit(), state))
             }
             State::Component => {
                 let mut component = self.components.pop().unwrap();
 
                 // If there's a parent component, pop the stack, add it to the parent,
                 // and continue to validate the component
-                if self.components.len() > 1 {
-                    let current = self.components.last_mut().unwrap();
+                if let Some(current) = self.components.last_mut() {
                     current.add_component(&mut component, &mut self.types);
                     self.state = State::Component;
                 }
@@ -1204,8 +1960,9 @@ impl Validator {
         let offset = section.range().start;
 
         if !self.features.component_model {
-            return Err(BinaryReaderError::new(
+            return Err(BinaryReaderError::feature_disabled(
                 "component model feature is not enabled",
+                "component_model",
                 offset,
             ));
         }
@@ -1239,8 +1996,12 @@ impl Validator {
 
 #[cfg(test)]
 mod tests {
-    use crate::{GlobalType, MemoryType, TableType, Type, Validator, WasmFeatures};
+    use crate::types::{EntityType, TypeDef, TypeDiff};
+    use crate::{
+        FuncType, GlobalType, MemoryType, Parser, Payload, TableType, Type, Validator, WasmFeatures,
+    };
     use anyhow::Result;
+    use std::collections::HashSet;
 
     #[test]
     fn test_module_type_information() -> Result<()> {
@@ -1254,6 +2015,8 @@ mod tests {
                 (func (type 0) (i32.const 0))
                 (tag (param i64 i32))
                 (elem funcref (ref.func 0))
+                (export "f" (func 0))
+                (export "m" (memory 0))
             )
         "#,
         )?;
@@ -1338,6 +2101,1383 @@ mod tests {
 
         assert_eq!(types.element_at(0), Some(Type::FuncRef));
 
+        match types.export("f") {
+            Some(EntityType::Func(id)) => {
+                assert_eq!(types.id_from_type_index(0), Some(id));
+                match types.type_from_id(id) {
+                    Some(TypeDef::Func(ty)) => assert_eq!(ty, types.func_type_at(0).unwrap()),
+                    _ => unreachable!(),
+                }
+            }
+            _ => unreachable!(),
+        }
+        assert!(matches!(
+            types.export("m"),
+            Some(EntityType::Memory(MemoryType { initial: 1, .. }))
+        ));
+        assert!(types.export("g").is_none());
+        let mut names = types.exports().map(|(name, _)| name).collect::<Vec<_>>();
+        names.sort_unstable();
+        assert_eq!(names, ["f", "m"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_require_live_functions() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (table 1 funcref)
+                (func $start call $called)
+                (func $called)
+                (func $exported (export "f"))
+                (func $referenced)
+                (func $dead)
+                (elem (i32.const 0) $referenced)
+                (start $start)
+            )
+        "#,
+        )?;
+
+        assert!(Validator::new().validate_all(&bytes).is_ok());
+
+        match Validator::new()
+            .require_live_functions(true)
+            .validate_all(&bytes)
+        {
+            Err(e) => assert!(e.message().starts_with("function 4 is never exported")),
+            Ok(_) => panic!("dead function should be rejected"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_require_live_functions_memory64() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (memory i64 1)
+                (func (export "f")
+                    i64.const 0
+                    i64.load offset=0x100000000
+                    drop
+                    call $called)
+                (func $called)
+            )
+        "#,
+        )?;
+
+        Validator::new_with_features(WasmFeatures {
+            memory64: true,
+            ..Default::default()
+        })
+        .require_live_functions(true)
+        .validate_all(&bytes)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_all_checks_bodies_last() -> Result<()> {
+        // Both the function body and the data segment are invalid, but the
+        // bodies are only validated after everything else.
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (func i32.const 0)
+                (data (i32.const 0) "x")
+            )
+        "#,
+        )?;
+        let err = match Validator::new().validate_all(&bytes) {
+            Ok(_) => panic!("expected an error"),
+            Err(e) => e,
+        };
+        assert!(err.message().contains("unknown memory"), "{}", err);
+        Ok(())
+    }
+
+    #[test]
+    fn test_validator_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Validator>();
+    }
+
+    #[test]
+    fn test_on_progress() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (memory 1)
+                (func (export "a") (result i32) i32.const 1)
+                (func (export "b") (result i32) i32.const 2)
+                (data (i32.const 0) "hello")
+            )
+        "#,
+        )?;
+
+        let offsets = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = offsets.clone();
+        Validator::new()
+            .on_progress(move |validated, total| recorded.lock().unwrap().push((validated, total)))
+            .validate_all(&bytes)?;
+
+        let offsets = offsets.lock().unwrap();
+        assert!(offsets.len() > 1);
+        assert!(offsets.windows(2).all(|w| w[0].0 <= w[1].0));
+        assert!(offsets.iter().all(|(_, total)| *total == bytes.len()));
+        assert_eq!(offsets.last().unwrap().0, bytes.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_control_depth() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (func
+                    block
+                        loop
+                            block
+                            end
+                        end
+                    end
+                    block
+                    end
+                )
+                (func (param i32)
+                    local.get 0
+                    if
+                    end
+                )
+            )
+        "#,
+        )?;
+
+        let types = Validator::new().validate_all(&bytes)?;
+        assert_eq!(types.max_control_depth(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_components() -> Result<()> {
+        let header = [0x00, 0x61, 0x73, 0x6d, 0x0a, 0x00, 0x01, 0x00];
+        let mut bytes = header.to_vec();
+        // A component section containing an empty component.
+        bytes.extend([0x05, header.len() as u8]);
+        bytes.extend(header);
+
+        let features = WasmFeatures {
+            component_model: true,
+            ..Default::default()
+        };
+
+        let types = Validator::new_with_features(features).validate_all(&header)?;
+        assert!(!types.has_nested_components());
+        assert_eq!(types.nested_component_count(), 0);
+
+        let types = Validator::new_with_features(features).validate_all(&bytes)?;
+        assert!(types.has_nested_components());
+        assert_eq!(types.nested_component_count(), 1);
+        assert_eq!(types.component_count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_floats_disabled() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (func (param i32) (result i32)
+                    local.get 0
+                    f32.convert_i32_s
+                    f32.const 1
+                    f32.add
+                    i32.trunc_f32_s
+                )
+            )
+        "#,
+        )?;
+
+        Validator::new().validate_all(&bytes)?;
+
+        let mut validator = Validator::new_with_features(WasmFeatures {
+            floats: false,
+            ..Default::default()
+        });
+        match validator.validate_all(&bytes) {
+            Ok(_) => panic!("floats should be rejected"),
+            Err(e) => assert!(e
+                .message()
+                .contains("floating point not supported on this target")),
+        }
+
+        let bytes = wat::parse_str("(module (func unreachable f32.add drop))")?;
+        let mut validator = Validator::new_with_features(WasmFeatures {
+            floats: false,
+            ..Default::default()
+        });
+        assert!(validator.validate_all(&bytes).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unreachable_is_stack_polymorphic() -> Result<()> {
+        // Operands missing after `unreachable` are taken from the polymorphic
+        // bottom of the stack, whatever their type.
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (func (result i32)
+                    unreachable
+                    f64.add
+                    i64.trunc_f64_s
+                    drop
+                    select
+                )
+                (func (param i32)
+                    block
+                        local.get 0
+                        br_if 0
+                        unreachable
+                        i32.add
+                        drop
+                    end
+                    unreachable
+                    br 0
+                    f32.neg
+                    drop
+                )
+            )
+        "#,
+        )?;
+        Validator::new().validate_all(&bytes)?;
+
+        // Values pushed after `unreachable` are still typed though, so this is
+        // a type mismatch per the spec.
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (func (result f64)
+                    unreachable
+                    i32.const 0
+                    f64.add
+                )
+            )
+        "#,
+        )?;
+        match Validator::new().validate_all(&bytes) {
+            Ok(_) => panic!("mismatched operand after unreachable should be rejected"),
+            Err(e) => assert!(e.message().contains("type mismatch")),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_complete_and_can_accept() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (type (func))
+                (func (type 0))
+                (export "f" (func 0))
+            )
+        "#,
+        )?;
+
+        let mut validator = Validator::new();
+        assert!(!validator.can_accept(1));
+        for payload in Parser::new(0).parse_all(&bytes) {
+            let payload = payload?;
+            assert!(!validator.is_complete());
+            match &payload {
+                Payload::TypeSection(_) => {
+                    assert!(validator.can_accept(1));
+                    validator.payload(&payload)?;
+                    assert!(!validator.can_accept(1));
+                    assert!(validator.can_accept(3));
+                    assert!(validator.can_accept(0));
+                }
+                Payload::ExportSection(_) => {
+                    validator.payload(&payload)?;
+                    assert!(!validator.can_accept(3));
+                    assert!(validator.can_accept(10));
+                }
+                _ => {
+                    validator.payload(&payload)?;
+                }
+            }
+        }
+        assert!(validator.is_complete());
+        assert!(!validator.can_accept(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_item_exported() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (import "env" "g" (global i32))
+                (func (export "f"))
+                (func)
+                (memory (export "m") 1)
+                (table 1 funcref)
+                (export "g" (global 0))
+            )
+        "#,
+        )?;
+        let types = Validator::new().validate_all(&bytes)?;
+
+        assert!(types.is_function_exported(0));
+        assert!(!types.is_function_exported(1));
+        assert!(types.is_memory_exported(0));
+        assert!(!types.is_table_exported(0));
+        assert!(types.is_global_exported(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tag_attribute() -> Result<()> {
+        let module = |attribute: u8| {
+            let mut bytes = b"\0asm\x01\0\0\0".to_vec();
+            // A type section with a single `[] -> []` function type.
+            bytes.extend_from_slice(&[0x01, 0x04, 0x01, 0x60, 0x00, 0x00]);
+            // A tag section with a single tag of that type.
+            bytes.extend_from_slice(&[0x0d, 0x03, 0x01, attribute, 0x00]);
+            bytes
+        };
+        let features = WasmFeatures {
+            exceptions: true,
+            ..Default::default()
+        };
+
+        Validator::new_with_features(features).validate_all(&module(0))?;
+
+        match Validator::new_with_features(features).validate_all(&module(1)) {
+            Ok(_) => panic!("a nonzero tag attribute should be rejected"),
+            Err(e) => assert!(e.message().contains("invalid tag attribute")),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_init_expr_global_deps() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (import "env" "g" (global i32))
+                (import "env" "h" (global i32))
+                (global i32 (global.get 0))
+                (global i32 (i32.const 1))
+                (table 1 funcref)
+                (elem (i32.const 0) funcref)
+                (elem (global.get 1) funcref)
+            )
+        "#,
+        )?;
+        let types = Validator::new().validate_all(&bytes)?;
+
+        let deps = types.init_expr_global_deps();
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[&2], [0]);
+
+        let deps = types.element_init_expr_global_deps();
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[&1], [1]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_data_segment_lengths() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (memory 1)
+                (data (i32.const 0) "hello")
+                (data "")
+                (data (i32.const 8) "\00\01\02")
+            )
+        "#,
+        )?;
+        let types = Validator::new().validate_all(&bytes)?;
+        assert_eq!(types.data_segment_lengths(), [5, 0, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_memory_accesses() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (memory 1)
+                (func (param i32)
+                    local.get 0
+                    i32.load offset=4 align=2
+                    drop
+                    local.get 0
+                    i64.const 0
+                    i64.store8
+                )
+            )
+        "#,
+        )?;
+        let types = Validator::new().validate_all(&bytes)?;
+        assert!(types.memory_accesses().is_empty());
+
+        let types = Validator::new()
+            .record_memory_accesses(true)
+            .validate_all(&bytes)?;
+        let accesses = types.memory_accesses();
+        assert_eq!(accesses.len(), 2);
+        assert_eq!(accesses[0].memory, 0);
+        assert_eq!(accesses[0].offset, 4);
+        // `align=2` is encoded as its exponent.
+        assert_eq!(accesses[0].align, 1);
+        assert_eq!(accesses[0].ty, Type::I32);
+        assert!(!accesses[0].store);
+        assert_eq!(accesses[1].offset, 0);
+        assert_eq!(accesses[1].align, 0);
+        assert_eq!(accesses[1].ty, Type::I64);
+        assert!(accesses[1].store);
+        Ok(())
+    }
+
+    #[test]
+    fn test_atomic_accesses() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (memory 1 1 shared)
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.load
+                    drop
+                    local.get 0
+                    i32.atomic.load offset=8
+                )
+            )
+        "#,
+        )?;
+        let features = WasmFeatures {
+            threads: true,
+            ..Default::default()
+        };
+        let types = Validator::new_with_features(features)
+            .record_memory_accesses(true)
+            .validate_all(&bytes)?;
+        let accesses = types.atomic_accesses();
+        assert_eq!(accesses.len(), 1);
+        assert_eq!(accesses[0].memory, 0);
+        assert_eq!(accesses[0].offset, 8);
+        // 4-byte alignment is encoded as its exponent.
+        assert_eq!(accesses[0].align, 2);
+        assert_eq!(types.memory_accesses().len(), 1);
+
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (memory 1 1 shared)
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.atomic.load align=2
+                )
+            )
+        "#,
+        )?;
+        match Validator::new_with_features(features).validate_all(&bytes) {
+            Ok(_) => panic!("underaligned atomic access should be rejected"),
+            Err(e) => assert!(e.message().contains("natural alignment")),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_atomic_operators() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (memory 1 1 shared)
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.const 1
+                    i32.atomic.rmw.add
+                    drop
+                    local.get 0
+                    i64.const 1
+                    i64.const 2
+                    i64.atomic.rmw.cmpxchg
+                    drop
+                    local.get 0
+                    i32.const 0
+                    i64.const -1
+                    memory.atomic.wait32
+                    drop
+                    atomic.fence
+                    local.get 0
+                    i32.const 1
+                    memory.atomic.notify
+                )
+            )
+        "#,
+        )?;
+        let features = WasmFeatures {
+            threads: true,
+            ..Default::default()
+        };
+        Validator::new_with_features(features).validate_all(&bytes)?;
+
+        let disabled = WasmFeatures {
+            threads: false,
+            ..Default::default()
+        };
+        match Validator::new_with_features(disabled).validate_all(&bytes) {
+            Ok(_) => panic!("atomics should require the threads feature"),
+            Err(e) => assert_eq!(e.missing_feature(), Some("threads")),
+        }
+
+        // The threads proposal allows atomic operators on unshared memories,
+        // where `memory.atomic.wait32` traps at runtime instead.
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (memory 1)
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.atomic.load
+                    local.get 0
+                    i32.const 0
+                    i64.const 0
+                    memory.atomic.wait32
+                    i32.add
+                )
+            )
+        "#,
+        )?;
+        Validator::new_with_features(features).validate_all(&bytes)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_tail_calls() -> Result<()> {
+        let features = WasmFeatures {
+            tail_call: true,
+            ..Default::default()
+        };
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (type $t (func (param i32) (result i32)))
+                (table 1 funcref)
+                (func $f (type $t)
+                    local.get 0
+                    i32.eqz
+                    if
+                        i32.const 0
+                        return
+                    end
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $f
+                )
+                (func (type $t)
+                    local.get 0
+                    i32.const 0
+                    return_call_indirect (type $t)
+                )
+            )
+        "#,
+        )?;
+        Validator::new_with_features(features).validate_all(&bytes)?;
+        match Validator::new().validate_all(&bytes) {
+            Ok(_) => panic!("tail calls should require the tail_call feature"),
+            Err(e) => assert_eq!(e.missing_feature(), Some("tail_call")),
+        }
+
+        for wat in [
+            "(module (func $f (result i64) i64.const 0) (func (result i32) return_call $f))",
+            // The caller's result is on the stack, but isn't produced by the
+            // callee.
+            "(module (func $f) (func (result i32) i32.const 0 return_call $f))",
+            r#"(module
+                (type $t (func (result i64)))
+                (table 1 funcref)
+                (func (result i32) i32.const 0 return_call_indirect (type $t))
+            )"#,
+        ] {
+            let bytes = wat::parse_str(wat)?;
+            match Validator::new_with_features(features).validate_all(&bytes) {
+                Ok(_) => panic!("`{}` should not validate", wat),
+                Err(e) => assert!(e.message().starts_with("type mismatch"), "{}", e),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_has_only_zero_offsets() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (import "env" "base" (global i32))
+                (memory 1)
+                (table 1 funcref)
+                (func $f)
+                (data (i32.const 0) "a")
+                (data (global.get 0) "b")
+                (data "passive")
+                (elem (i32.const 0) $f)
+            )
+        "#,
+        )?;
+        let types = Validator::new().validate_all(&bytes)?;
+        assert!(types.has_only_zero_offsets());
+
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (memory 1)
+                (data (i32.const 0) "a")
+                (data (i32.const 1000) "b")
+            )
+        "#,
+        )?;
+        let types = Validator::new().validate_all(&bytes)?;
+        assert!(!types.has_only_zero_offsets());
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_all_minimal() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (func (param v128) (result v128)
+                    local.get 0
+                    local.get 0
+                    i32x4.add
+                )
+            )
+        "#,
+        )?;
+        let mut validator = Validator::new_with_features(WasmFeatures {
+            threads: true,
+            ..WasmFeatures::default()
+        });
+        let (types, features) = validator.validate_all_minimal(&bytes)?;
+        assert_eq!(types.function_count(), 1);
+        assert!(features.simd);
+        assert!(!features.threads);
+        assert!(!features.multi_value);
+        assert!(features.floats);
+        assert!(validator.features().threads);
+
+        // Restrictions aren't proposals, so they're never turned off.
+        let bytes = wat::parse_str("(module (func (result i32) i32.const 0))")?;
+        let (_, features) = Validator::new().validate_all_minimal(&bytes)?;
+        assert!(features.floats);
+        assert!(!features.deterministic_only);
+
+        // Not even valid with every feature enabled.
+        let bytes = wat::parse_str("(module (func (result i32)))")?;
+        assert!(Validator::new().validate_all_minimal(&bytes).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_global_init_bytes() -> Result<()> {
+        use crate::{Operator, OperatorsReader};
+
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (import "" "g" (global i32))
+                (global i32 (i32.const 42))
+                (global i32 (global.get 0))
+            )
+        "#,
+        )?;
+        let types = Validator::new().validate_all(&bytes)?;
+        assert_eq!(types.global_init_bytes(0), None);
+        assert_eq!(types.global_init_bytes(3), None);
+
+        let mut reader = OperatorsReader::new(types.global_init_bytes(1).unwrap(), 0);
+        assert!(matches!(reader.read()?, Operator::I32Const { value: 42 }));
+        assert!(matches!(reader.read()?, Operator::End));
+        assert!(reader.eof());
+
+        let mut reader = OperatorsReader::new(types.global_init_bytes(2).unwrap(), 0);
+        assert!(matches!(
+            reader.read()?,
+            Operator::GlobalGet { global_index: 0 }
+        ));
+        assert!(matches!(reader.read()?, Operator::End));
+        assert!(reader.eof());
+        Ok(())
+    }
+
+    #[test]
+    fn test_function_sizes() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (import "env" "f" (func))
+                (func)
+                (func (local i32) i32.const 1 local.set 0)
+            )
+        "#,
+        )?;
+        let types = Validator::new().validate_all(&bytes)?;
+        // An empty body is just a local declaration count and an `end`, while
+        // the second body also declares a local and has two 2-byte
+        // instructions.
+        assert_eq!(types.function_sizes(), [2, 8]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_alias_unknown_instance_export() {
+        let bytes = [
+            0x00, 0x61, 0x73, 0x6d, 0x0a, 0x00, 0x01, 0x00, // component header
+            0x01, 0x03, 0x01, 0x4d, 0x00, // type 0: an instance without exports
+            0x02, 0x04, 0x01, 0x01, b'i', 0x00, // import "i" of type 0
+            // alias the function "foo" of instance 0
+            0x09, 0x08, 0x01, 0x00, 0x03, 0x00, 0x03, b'f', b'o', b'o',
+        ];
+        let err = match Validator::new_with_features(WasmFeatures {
+            component_model: true,
+            ..Default::default()
+        })
+        .validate_all(&bytes)
+        {
+            Ok(_) => panic!("expected an invalid alias"),
+            Err(err) => err,
+        };
+        assert_eq!(
+            err.message(),
+            "alias refers to unknown instance export `foo` of instance 0"
+        );
+        assert_eq!(err.offset(), 22);
+    }
+
+    #[test]
+    fn test_imported_counts() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (import "env" "f" (func))
+                (import "env" "g" (func (param i32)))
+                (import "env" "m" (memory 1))
+                (import "env" "x" (global i32))
+                (table 1 funcref)
+                (global i32 (i32.const 0))
+                (func)
+            )
+        "#,
+        )?;
+        let types = Validator::new().validate_all(&bytes)?;
+        assert_eq!(types.imported_function_count(), 2);
+        assert_eq!(types.function_count(), 3);
+        assert_eq!(types.imported_table_count(), 0);
+        assert_eq!(types.imported_memory_count(), 1);
+        assert_eq!(types.imported_global_count(), 1);
+        assert_eq!(types.global_count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_imported_function_signatures() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (import "env" "log" (func (param i32)))
+                (import "env" "mem" (memory 1))
+                (func)
+            )
+        "#,
+        )?;
+        let types = Validator::new().validate_all(&bytes)?;
+        let signatures = types.imported_function_signatures();
+        assert_eq!(signatures.len(), 1);
+        let (module, field, ty) = &signatures[0];
+        assert_eq!(module, "env");
+        assert_eq!(field, "log");
+        assert_eq!(&*ty.params, [Type::I32]);
+        assert_eq!(&*ty.returns, []);
+        Ok(())
+    }
+
+    #[test]
+    fn test_multiple_memories_and_tables_messages() -> Result<()> {
+        let bytes = wat::parse_str("(module (memory 1) (memory 1))")?;
+        let err = Validator::new_with_features(WasmFeatures {
+            multi_memory: false,
+            ..Default::default()
+        })
+        .validate_all(&bytes)
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.message(),
+            "multiple memories require the multi-memory proposal"
+        );
+
+        let bytes = wat::parse_str(r#"(module (import "env" "m" (memory 1)) (memory 1))"#)?;
+        let err = Validator::new().validate_all(&bytes).err().unwrap();
+        assert_eq!(
+            err.message(),
+            "multiple memories require the multi-memory proposal"
+        );
+
+        let bytes = wat::parse_str("(module (table 1 funcref) (table 1 funcref))")?;
+        let err = Validator::new_with_features(WasmFeatures {
+            reference_types: false,
+            ..Default::default()
+        })
+        .validate_all(&bytes)
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.message(),
+            "multiple tables require the reference-types proposal"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_multiple_component_start_sections() {
+        let features = WasmFeatures {
+            component_model: true,
+            ..Default::default()
+        };
+        let mut bytes = vec![
+            0x00, 0x61, 0x73, 0x6d, 0x0a, 0x00, 0x01, 0x00, // component header
+            0x01, 0x04, 0x01, 0x4c, 0x00, 0x7f, // type 0: a function returning unit
+            0x02, 0x04, 0x01, 0x01, b'f', 0x00, // import "f" of type 0
+            0x08, 0x02, 0x00, 0x00, // start function 0
+        ];
+        assert!(Validator::new_with_features(features)
+            .validate_all(&bytes)
+            .is_ok());
+
+        bytes.extend([0x08, 0x02, 0x00, 0x00]);
+        let err = match Validator::new_with_features(features).validate_all(&bytes) {
+            Ok(_) => panic!("expected multiple start sections to be rejected"),
+            Err(err) => err,
+        };
+        assert_eq!(
+            err.message(),
+            "component cannot have more than one start function"
+        );
+        assert_eq!(err.offset(), 26);
+    }
+
+    #[test]
+    fn test_current_component_exports() -> Result<()> {
+        use crate::{ComponentExportKind, Parser};
+
+        let bytes = [
+            0x00, 0x61, 0x73, 0x6d, 0x0a, 0x00, 0x01, 0x00, // component header
+            0x01, 0x04, 0x01, 0x4c, 0x00, 0x7f, // type 0: a function returning unit
+            0x02, 0x04, 0x01, 0x01, b'f', 0x00, // import "f" of type 0
+            0x07, 0x05, 0x01, 0x01, b'a', 0x03, 0x00, // export "a" of function 0
+            0x07, 0x05, 0x01, 0x01, b'b', 0x05, 0x00, // export "b" of type 0
+        ];
+        let mut validator = Validator::new_with_features(WasmFeatures {
+            component_model: true,
+            ..Default::default()
+        });
+        assert_eq!(validator.current_component_exports().len(), 0);
+
+        let mut seen = Vec::new();
+        for payload in Parser::new(0).parse_all(&bytes) {
+            let payload = payload?;
+            let is_export = matches!(payload, Payload::ComponentExportSection(_));
+            if let Payload::End(_) = payload {
+                break;
+            }
+            validator.payload(&payload)?;
+            if is_export {
+                seen.push(validator.current_component_exports().len());
+            }
+        }
+        assert_eq!(seen, [1, 2]);
+
+        let exports = validator.current_component_exports().collect::<Vec<_>>();
+        assert_eq!(exports[0].0, "a");
+        assert!(matches!(exports[0].1, ComponentExportKind::Function(0)));
+        assert_eq!(exports[1].0, "b");
+        assert!(matches!(exports[1].1, ComponentExportKind::Type(0)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_and_restore_state() -> Result<()> {
+        use crate::ValidPayload;
+
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (import "env" "f" (func (param i32)))
+                (memory 1)
+                (global (mut i32) (i32.const 0))
+                (func (export "g") (result i32)
+                    i32.const 1
+                    call 0
+                    global.get 0
+                )
+                (func (param i64))
+            )
+        "#,
+        )?;
+        let expected = Validator::new().validate_all(&bytes)?;
+
+        let mut payloads = Parser::new(0).parse_all(&bytes);
+        let mut validator = Validator::new();
+        for payload in payloads.by_ref() {
+            let payload = payload?;
+            let is_memory_section = matches!(payload, Payload::MemorySection(_));
+            validator.payload(&payload)?;
+            if is_memory_section {
+                break;
+            }
+        }
+        let checkpoint = validator.save_state();
+
+        let mut validator = Validator::new();
+        validator.restore_state(checkpoint);
+        let mut types = None;
+        for payload in payloads {
+            match validator.payload(&payload?)? {
+                ValidPayload::Func(mut func, body) => func.validate(&body)?,
+                ValidPayload::End(t) => types = Some(t),
+                _ => {}
+            }
+        }
+        let types = types.unwrap();
+        assert!(types.diff(&expected).is_empty());
+        assert_eq!(types.type_count(), expected.type_count());
+        assert_eq!(types.function_count(), expected.function_count());
+        assert_eq!(types.global_at(0), expected.global_at(0));
+        assert!(types.is_function_exported(1));
+        assert!(types.export("g").is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_total_instruction_count() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (func (result i32)
+                    i32.const 1
+                    i32.const 2
+                    i32.add
+                )
+                (func
+                    nop
+                )
+            )
+        "#,
+        )?;
+        let types = Validator::new().validate_all(&bytes)?;
+        // Each body also counts its final `end`.
+        assert_eq!(types.total_instruction_count(), 4 + 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_function_operator_count() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (import "" "" (func))
+                (func (result i32)
+                    i32.const 1
+                    i32.const 2
+                    i32.add
+                    drop
+                    i32.const 0
+                )
+                (func
+                    nop
+                    nop
+                )
+            )
+        "#,
+        )?;
+        let types = Validator::new().validate_all(&bytes)?;
+        assert_eq!(types.function_operator_count(0), None);
+        // The second body holds three operators: two `nop`s and its `end`.
+        assert_eq!(types.function_operator_count(1), Some(6));
+        assert_eq!(types.function_operator_count(2), Some(3));
+        assert_eq!(types.function_operator_count(3), None);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_validate_all_parallel() -> Result<()> {
+        let mut wat = String::from("(module (memory 1)");
+        for i in 0..50 {
+            wat.push_str(&format!(
+                "(func i32.const {} i32.load offset={} drop call {})",
+                i,
+                i * 4,
+                (i + 1) % 50
+            ));
+        }
+        wat.push_str("(export \"f\" (func 0)) (start 0))");
+        let bytes = wat::parse_str(&wat)?;
+        let serial = Validator::new()
+            .record_memory_accesses(true)
+            .validate_all(&bytes)?;
+        assert_eq!(serial.memory_accesses().len(), 50);
+        for threads in [0, 1, 4, 64] {
+            let parallel = Validator::new()
+                .record_memory_accesses(true)
+                .validate_all_parallel(&bytes, threads)?;
+            for i in 0..51 {
+                assert_eq!(
+                    parallel.function_operator_count(i),
+                    serial.function_operator_count(i)
+                );
+            }
+            assert_eq!(parallel.memory_accesses(), serial.memory_accesses());
+            assert_eq!(
+                parallel.reachable_from_start(),
+                serial.reachable_from_start()
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_validate_all_parallel_reports_first_error() -> Result<()> {
+        let mut wat = String::from("(module");
+        for i in 0..20 {
+            if i == 7 || i == 15 {
+                wat.push_str("(func (result i32) i64.const 0)");
+            } else {
+                wat.push_str("(func (result i32) i32.const 0)");
+            }
+        }
+        wat.push(')');
+        let bytes = wat::parse_str(&wat)?;
+        let expected = match Validator::new().validate_all(&bytes) {
+            Ok(_) => panic!("expected the module to be invalid"),
+            Err(e) => e.offset(),
+        };
+        for threads in [2, 4, 20] {
+            match Validator::new().validate_all_parallel(&bytes, threads) {
+                Ok(_) => panic!("expected the module to be invalid"),
+                Err(e) => assert_eq!(e.offset(), expected),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_validate_all_rayon() -> Result<()> {
+        let mut wat = String::from("(module (memory 1)");
+        for i in 0..500 {
+            wat.push_str(&format!(
+                "(func (result i32) i32.const {} i32.load offset={} drop call {})",
+                i,
+                i * 4,
+                (i + 1) % 500
+            ));
+        }
+        wat.push(')');
+        let bytes = wat::parse_str(&wat)?;
+        let serial = Validator::new()
+            .record_memory_accesses(true)
+            .validate_all(&bytes)?;
+        let parallel = Validator::new()
+            .record_memory_accesses(true)
+            .validate_all_rayon(&bytes)?;
+        for i in 0..500 {
+            assert_eq!(
+                parallel.function_operator_count(i),
+                serial.function_operator_count(i)
+            );
+        }
+        assert_eq!(parallel.memory_accesses(), serial.memory_accesses());
+
+        // Give two of the functions the wrong result type.
+        let bytes = wat::parse_str(
+            wat.replace("i32.const 123 ", "i64.const 123 ")
+                .replace("i32.const 456 ", "i64.const 456 "),
+        )?;
+        let expected = match Validator::new().validate_all(&bytes) {
+            Ok(_) => panic!("expected the module to be invalid"),
+            Err(e) => e.offset(),
+        };
+        match Validator::new().validate_all_rayon(&bytes) {
+            Ok(_) => panic!("expected the module to be invalid"),
+            Err(e) => assert_eq!(e.offset(), expected),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_module_only() -> Result<()> {
+        let module = wat::parse_str("(module)")?;
+        let component = [0x00, 0x61, 0x73, 0x6d, 0x0a, 0x00, 0x01, 0x00];
+
+        Validator::new().module_only().validate_all(&module)?;
+        let features = WasmFeatures {
+            component_model: true,
+            ..WasmFeatures::default()
+        };
+        match Validator::new_with_features(features)
+            .module_only()
+            .validate_all(&component)
+        {
+            Ok(_) => panic!("component should be rejected"),
+            Err(e) => assert!(e.message().contains("components are not accepted")),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_component_only() -> Result<()> {
+        let module = wat::parse_str("(module)")?;
+        let component = [0x00, 0x61, 0x73, 0x6d, 0x0a, 0x00, 0x01, 0x00];
+
+        let features = WasmFeatures {
+            component_model: true,
+            ..WasmFeatures::default()
+        };
+        Validator::new_with_features(features)
+            .component_only()
+            .validate_all(&component)?;
+        match Validator::new_with_features(features)
+            .component_only()
+            .validate_all(&module)
+        {
+            Ok(_) => panic!("module should be rejected"),
+            Err(e) => assert!(e.message().contains("modules are not accepted")),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_reachable_from_start() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (import "" "" (func))
+                (func
+                    call 2
+                )
+                (func
+                    call 0
+                )
+                (func
+                    call 1
+                )
+                (start 1)
+            )
+        "#,
+        )?;
+        let types = Validator::new().validate_all(&bytes)?;
+        assert_eq!(types.reachable_from_start(), HashSet::from([0, 1, 2]));
+
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (type (func))
+                (table 1 funcref)
+                (func
+                    i32.const 0
+                    call_indirect (type 0)
+                )
+                (func)
+                (start 0)
+            )
+        "#,
+        )?;
+        let types = Validator::new().validate_all(&bytes)?;
+        assert_eq!(types.reachable_from_start(), HashSet::from([0, 1]));
+
+        let types = Validator::new().validate_all(&wat::parse_str("(module (func))")?)?;
+        assert!(types.reachable_from_start().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_wasm_release_features() -> Result<()> {
+        let wasm1 = wat::parse_str(
+            r#"
+            (module
+                (import "" "" (global (mut i32)))
+                (memory 1)
+                (func (export "f") (param i32) (result i32)
+                    local.get 0
+                    global.get 0
+                    i32.add
+                )
+            )
+        "#,
+        )?;
+        Validator::new_with_features(WasmFeatures::wasm1()).validate_all(&wasm1)?;
+        Validator::new_with_features(WasmFeatures::wasm2()).validate_all(&wasm1)?;
+
+        for wasm2 in [
+            "(module (func (result i32 i32) i32.const 0 i32.const 1))",
+            "(module (func (param externref)))",
+            "(module (func (param i32) (result i32) local.get 0 i32.extend8_s))",
+        ] {
+            let bytes = wat::parse_str(wasm2)?;
+            Validator::new_with_features(WasmFeatures::wasm2()).validate_all(&bytes)?;
+            assert!(Validator::new_with_features(WasmFeatures::wasm1())
+                .validate_all(&bytes)
+                .is_err());
+        }
+
+        let threads = wat::parse_str("(module (memory 1 1 shared))")?;
+        assert!(Validator::new_with_features(WasmFeatures::wasm2())
+            .validate_all(&threads)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_all_and_no_features() -> Result<()> {
+        let simd = wat::parse_str(
+            r#"
+            (module
+                (func (param v128) (result v128)
+                    local.get 0
+                    local.get 0
+                    i32x4.add
+                )
+            )
+        "#,
+        )?;
+        Validator::new_with_features(WasmFeatures::all()).validate_all(&simd)?;
+        assert!(Validator::new_with_features(WasmFeatures::none())
+            .validate_all(&simd)
+            .is_err());
+
+        let empty = wat::parse_str("(module)")?;
+        Validator::new_with_features(WasmFeatures::none()).validate_all(&empty)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_feature() -> Result<()> {
+        let features = WasmFeatures {
+            simd: false,
+            ..Default::default()
+        };
+        for wat in [
+            "(module (func (param v128)))",
+            "(module (func (result i32) i32.const 0 i32x4.splat i32x4.extract_lane 0))",
+        ] {
+            let bytes = wat::parse_str(wat)?;
+            match Validator::new_with_features(features).validate_all(&bytes) {
+                Ok(_) => panic!("`{}` should not validate", wat),
+                Err(e) => assert_eq!(e.missing_feature(), Some("simd")),
+            }
+        }
+
+        let bytes = wat::parse_str("(module (memory 1) (memory 1))")?;
+        match Validator::new().validate_all(&bytes) {
+            Ok(_) => panic!("multiple memories should require multi-memory"),
+            Err(e) => assert_eq!(e.missing_feature(), Some("multi_memory")),
+        }
+
+        let bytes = [
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // module header
+            0x01, 0x04, 0x01, 0x5e, 0x7f, 0x00, // type 0: an array of i32
+        ];
+        match Validator::new().validate_all(&bytes) {
+            Ok(_) => panic!("array types should require gc"),
+            Err(e) => assert_eq!(e.missing_feature(), Some("gc")),
+        }
+
+        let bytes = wat::parse_str("(module (func (result i32) i64.const 0))")?;
+        match Validator::new().validate_all(&bytes) {
+            Ok(_) => panic!("module should not validate"),
+            Err(e) => assert_eq!(e.missing_feature(), None),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_types_diff() -> Result<()> {
+        let original = wat::parse_str(
+            r#"
+            (module
+                (memory 1)
+                (func (param i32))
+            )
+        "#,
+        )?;
+        let extended = wat::parse_str(
+            r#"
+            (module
+                (memory 1)
+                (func (param i32))
+                (func (result i64) i64.const 0)
+            )
+        "#,
+        )?;
+
+        let original = Validator::new().validate_all(&original)?;
+        let extended = Validator::new().validate_all(&extended)?;
+
+        assert!(original.diff(&original).is_empty());
+        assert_eq!(
+            original.diff(&extended),
+            [TypeDiff::FunctionAdded {
+                index: 1,
+                ty: FuncType {
+                    params: Box::new([]),
+                    returns: Box::new([Type::I64]),
+                },
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_total_initial_memory_pages() -> Result<()> {
+        let bytes = wat::parse_str(
+            r#"
+            (module
+                (import "env" "m" (memory 4))
+                (memory 1)
+                (memory 2 10)
+            )
+        "#,
+        )?;
+
+        let mut validator = Validator::new_with_features(WasmFeatures {
+            multi_memory: true,
+            ..Default::default()
+        });
+        let types = validator.validate_all(&bytes)?;
+        assert_eq!(types.total_initial_memory_pages(false), 3);
+        assert_eq!(types.total_initial_memory_pages(true), 7);
+
         Ok(())
     }
 }
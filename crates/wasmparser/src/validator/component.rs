@@ -14,7 +14,7 @@ use crate::{
 };
 use std::{collections::HashMap, mem};
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct ComponentState {
     pub types: Vec<TypeId>,
     pub modules: Vec<TypeId>,
@@ -28,6 +28,10 @@ pub struct ComponentState {
     pub tags: Vec<TypeId>,
     pub imports: HashMap<String, ComponentEntityType>,
     pub exports: HashMap<String, ComponentEntityType>,
+    // The names and kinds of `exports`, in the order they were exported.
+    pub export_kinds: Vec<(String, crate::ComponentExportKind)>,
+    // The number of components defined directly inside of this one.
+    pub nested_components: usize,
     has_start: bool,
 }
 
@@ -239,6 +243,7 @@ impl ComponentState {
     }
 
     pub(super) fn add_component(&mut self, component: &mut Self, types: &mut TypeList) {
+        self.nested_components += 1;
         self.components.push(TypeId(types.len()));
         types.push(TypeDef::Component(ComponentType {
             imports: mem::take(&mut component.imports),
@@ -546,6 +551,12 @@ impl ComponentState {
                     offset,
                 ))
             }
+            TypeDef::Struct(_) | TypeDef::Array(_) => {
+                return Err(BinaryReaderError::new(
+                    format!("core WebAssembly GC types cannot be {}", desc),
+                    offset,
+                ))
+            }
         })
     }
 
@@ -1483,7 +1494,10 @@ impl ComponentState {
             Some(export) => Ok(export),
             None => {
                 return Err(BinaryReaderError::new(
-                    format!("instance {} has no export named `{}`", idx, name),
+                    format!(
+                        "alias refers to unknown instance export `{}` of instance {}",
+                        name, idx
+                    ),
                     offset,
                 ))
             }
@@ -1505,7 +1519,10 @@ impl ComponentState {
             Some(export) => Ok(export),
             None => {
                 return Err(BinaryReaderError::new(
-                    format!("instance {} has no export named `{}`", idx, name),
+                    format!(
+                        "alias refers to unknown instance export `{}` of instance {}",
+                        name, idx
+                    ),
                     offset,
                 ))
             }
@@ -1,14 +1,14 @@
 //! State relating to validating a WebAssembly module.
 //!
 use super::{
-    check_max,
+    check_max, check_max_or_proposal,
     operators::OperatorValidator,
     types::{EntityType, TypeDef, TypeId, TypeList},
 };
 use crate::{
     limits::*, BinaryReaderError, Data, DataKind, Element, ElementItem, ElementKind, ExternalKind,
-    FuncType, Global, GlobalType, InitExpr, MemoryType, Operator, Result, TableType, TagType, Type,
-    TypeRef, WasmFeatures, WasmModuleResources,
+    FieldType, FuncType, FunctionBody, Global, GlobalType, InitExpr, MemoryType, Operator, Result,
+    StorageType, TableType, TagType, Type, TypeRef, WasmFeatures, WasmModuleResources,
 };
 use std::{
     collections::{HashMap, HashSet},
@@ -18,7 +18,14 @@ use std::{
 fn check_value_type(ty: Type, features: &WasmFeatures, offset: usize) -> Result<()> {
     match features.check_value_type(ty) {
         Ok(()) => Ok(()),
-        Err(e) => Err(BinaryReaderError::new(e, offset)),
+        Err((e, feature)) => Err(BinaryReaderError::feature_disabled(e, feature, offset)),
+    }
+}
+
+fn check_field_type(ty: &FieldType, features: &WasmFeatures, offset: usize) -> Result<()> {
+    match ty.element_type {
+        StorageType::I8 | StorageType::I16 => Ok(()),
+        StorageType::Val(ty) => check_value_type(ty, features, offset),
     }
 }
 
@@ -44,13 +51,36 @@ pub enum Order {
     Data,
 }
 
+impl Order {
+    /// Returns the position of the module section with the given `id`, or
+    /// `None` for custom and unknown sections.
+    pub fn from_section_id(id: u8) -> Option<Order> {
+        Some(match id {
+            1 => Order::Type,
+            2 => Order::Import,
+            3 => Order::Function,
+            4 => Order::Table,
+            5 => Order::Memory,
+            13 => Order::Tag,
+            6 => Order::Global,
+            7 => Order::Export,
+            8 => Order::Start,
+            9 => Order::Element,
+            12 => Order::DataCount,
+            10 => Order::Code,
+            11 => Order::Data,
+            _ => return None,
+        })
+    }
+}
+
 impl Default for Order {
     fn default() -> Order {
         Order::Initial
     }
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct ModuleState {
     /// Internal state that is incrementally built-up for the module being
     /// validated. This houses type information for all wasm items, like
@@ -74,9 +104,27 @@ pub struct ModuleState {
 
     /// When parsing the code section, represents the current index in the section.
     code_section_index: Option<usize>,
+
+    /// The length in bytes of each data segment seen so far.
+    pub(crate) data_segment_lengths: Vec<usize>,
+
+    /// Whether any active data or element segment has an offset which is
+    /// neither zero nor computed from a global.
+    pub(crate) has_absolute_offsets: bool,
+
+    /// The length in bytes of each function body seen so far.
+    pub(crate) function_sizes: Vec<usize>,
+
+    /// The encoded initializer of each defined global seen so far, including
+    /// its trailing `end`.
+    pub(crate) global_init_bytes: Vec<Vec<u8>>,
 }
 
 impl ModuleState {
+    pub fn order(&self) -> Order {
+        self.order
+    }
+
     pub fn update_order(&mut self, order: Order, offset: usize) -> Result<()> {
         if self.order >= order {
             return Err(BinaryReaderError::new("section out of order", offset));
@@ -138,14 +186,23 @@ impl ModuleState {
     ) -> Result<()> {
         self.module
             .check_global_type(&global.ty, features, offset)?;
-        self.check_init_expr(
+        let deps = self.check_init_expr(
             &global.init_expr,
             global.ty.content_type,
             features,
             types,
             offset,
         )?;
-        self.module.assert_mut().globals.push(global.ty);
+        let mut reader = global.init_expr.get_binary_reader();
+        self.global_init_bytes
+            .push(reader.read_bytes(reader.bytes_remaining())?.to_vec());
+        let module = self.module.assert_mut();
+        if !deps.is_empty() {
+            module
+                .global_init_deps
+                .insert(module.globals.len() as u32, deps);
+        }
+        module.globals.push(global.ty);
         Ok(())
     }
 
@@ -156,6 +213,7 @@ impl ModuleState {
         types: &TypeList,
         offset: usize,
     ) -> Result<()> {
+        self.data_segment_lengths.push(data.data.len());
         match data.kind {
             DataKind::Passive => Ok(()),
             DataKind::Active {
@@ -163,7 +221,9 @@ impl ModuleState {
                 init_expr,
             } => {
                 let ty = self.module.memory_at(memory_index, offset)?.index_type();
-                self.check_init_expr(&init_expr, ty, features, types, offset)
+                let deps = self.check_init_expr(&init_expr, ty, features, types, offset)?;
+                self.check_offset_is_relocatable(&init_expr, &deps)?;
+                Ok(())
             }
         }
     }
@@ -179,13 +239,15 @@ impl ModuleState {
             Type::FuncRef => {}
             Type::ExternRef if features.reference_types => {}
             Type::ExternRef => {
-                return Err(BinaryReaderError::new(
+                return Err(BinaryReaderError::feature_disabled(
                     "reference types must be enabled for externref elem segment",
+                    "reference_types",
                     offset,
                 ))
             }
             _ => return Err(BinaryReaderError::new("malformed reference type", offset)),
         }
+        let mut deps = Vec::new();
         match e.kind {
             ElementKind::Active {
                 table_index,
@@ -199,12 +261,14 @@ impl ModuleState {
                     ));
                 }
 
-                self.check_init_expr(&init_expr, Type::I32, features, types, offset)?;
+                deps = self.check_init_expr(&init_expr, Type::I32, features, types, offset)?;
+                self.check_offset_is_relocatable(&init_expr, &deps)?;
             }
             ElementKind::Passive | ElementKind::Declared => {
                 if !features.bulk_memory {
-                    return Err(BinaryReaderError::new(
+                    return Err(BinaryReaderError::feature_disabled(
                         "bulk memory must be enabled",
+                        "bulk_memory",
                         offset,
                     ));
                 }
@@ -221,7 +285,11 @@ impl ModuleState {
             let offset = items.original_position();
             match items.read()? {
                 ElementItem::Expr(expr) => {
-                    self.check_init_expr(&expr, e.ty, features, types, offset)?;
+                    for global in self.check_init_expr(&expr, e.ty, features, types, offset)? {
+                        if !deps.contains(&global) {
+                            deps.push(global);
+                        }
+                    }
                 }
                 ElementItem::Func(f) => {
                     if e.ty != Type::FuncRef {
@@ -236,10 +304,35 @@ impl ModuleState {
             }
         }
 
-        self.module.assert_mut().element_types.push(e.ty);
+        let module = self.module.assert_mut();
+        if !deps.is_empty() {
+            module
+                .element_init_deps
+                .insert(module.element_types.len() as u32, deps);
+        }
+        module.element_types.push(e.ty);
         Ok(())
     }
 
+    /// Records whether a segment offset reading the globals `deps` is an
+    /// absolute address rather than zero or one computed from a global.
+    fn check_offset_is_relocatable(&mut self, init_expr: &InitExpr, deps: &[u32]) -> Result<()> {
+        if !deps.is_empty() {
+            return Ok(());
+        }
+        let mut ops = init_expr.get_operators_reader();
+        let is_zero = matches!(
+            ops.read()?,
+            Operator::I32Const { value: 0 } | Operator::I64Const { value: 0 }
+        ) && matches!(ops.read()?, Operator::End);
+        if !is_zero {
+            self.has_absolute_offsets = true;
+        }
+        Ok(())
+    }
+
+    /// Validates a constant expression, returning the indices of the globals
+    /// it reads.
     fn check_init_expr(
         &mut self,
         expr: &InitExpr<'_>,
@@ -247,7 +340,8 @@ impl ModuleState {
         features: &WasmFeatures,
         types: &TypeList,
         offset: usize,
-    ) -> Result<()> {
+    ) -> Result<Vec<u32>> {
+        let mut deps = Vec::new();
         let mut ops = expr.get_operators_reader();
         let mut validator = OperatorValidator::new_init_expr(features, expected_ty);
         let mut uninserted_funcref = false;
@@ -289,6 +383,9 @@ impl ModuleState {
                             offset,
                         ));
                     }
+                    if !deps.contains(global_index) {
+                        deps.push(*global_index);
+                    }
                 }
 
                 // Functions in initialization expressions are only valid in
@@ -342,11 +439,11 @@ impl ModuleState {
         // See comment in `RefFunc` above for why this is an assert.
         assert!(!uninserted_funcref);
 
-        Ok(())
+        Ok(deps)
     }
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct Module {
     // This is set once the code section starts.
     // `WasmModuleResources` implementations use the snapshot to
@@ -364,12 +461,41 @@ pub struct Module {
     pub tags: Vec<TypeId>,
     pub function_references: HashSet<u32>,
     pub imports: HashMap<(String, String), Vec<EntityType>>,
+    // The module and field names of each imported function, in order.
+    pub function_imports: Vec<(String, String)>,
     pub exports: HashMap<String, EntityType>,
+    pub exported_functions: HashSet<u32>,
+    pub exported_tables: HashSet<u32>,
+    pub exported_memories: HashSet<u32>,
+    pub exported_globals: HashSet<u32>,
+    pub start_function: Option<u32>,
+    // Maps globals and element segments to the globals read by their
+    // initializers, for those which read any.
+    pub global_init_deps: HashMap<u32, Vec<u32>>,
+    pub element_init_deps: HashMap<u32, Vec<u32>>,
     num_imported_globals: u32,
     num_imported_functions: u32,
+    num_imported_tables: u32,
+    num_imported_memories: u32,
 }
 
 impl Module {
+    pub(super) fn num_imported_functions(&self) -> u32 {
+        self.num_imported_functions
+    }
+
+    pub(super) fn num_imported_tables(&self) -> u32 {
+        self.num_imported_tables
+    }
+
+    pub(super) fn num_imported_memories(&self) -> u32 {
+        self.num_imported_memories
+    }
+
+    pub(super) fn num_imported_globals(&self) -> u32 {
+        self.num_imported_globals
+    }
+
     pub(super) fn add_type(
         &mut self,
         def: crate::TypeDef,
@@ -383,13 +509,38 @@ impl Module {
                     check_value_type(*ty, features, offset)?;
                 }
                 if t.returns.len() > 1 && !features.multi_value {
-                    return Err(BinaryReaderError::new(
+                    return Err(BinaryReaderError::feature_disabled(
                         "func type returns multiple values but the multi-value feature is not enabled",
+                        "multi_value",
                         offset,
                     ));
                 }
                 TypeDef::Func(t)
             }
+            crate::TypeDef::Struct(t) => {
+                if !features.gc {
+                    return Err(BinaryReaderError::feature_disabled(
+                        "struct types require the GC proposal to be enabled",
+                        "gc",
+                        offset,
+                    ));
+                }
+                for field in t.fields.iter() {
+                    check_field_type(field, features, offset)?;
+                }
+                TypeDef::Struct(t)
+            }
+            crate::TypeDef::Array(t) => {
+                if !features.gc {
+                    return Err(BinaryReaderError::feature_disabled(
+                        "array types require the GC proposal to be enabled",
+                        "gc",
+                        offset,
+                    ));
+                }
+                check_field_type(&t.field, features, offset)?;
+                TypeDef::Array(t)
+            }
         };
 
         self.types.push(TypeId(types.len()));
@@ -409,15 +560,35 @@ impl Module {
         let (len, max, desc) = match import.ty {
             TypeRef::Func(type_index) => {
                 self.functions.push(type_index);
+                self.function_imports
+                    .push((import.module.to_string(), import.name.to_string()));
                 self.num_imported_functions += 1;
                 (self.functions.len(), MAX_WASM_FUNCTIONS, "functions")
             }
             TypeRef::Table(ty) => {
                 self.tables.push(ty);
+                self.num_imported_tables += 1;
+                check_max_or_proposal(
+                    self.tables.len(),
+                    0,
+                    self.max_tables(features),
+                    "tables",
+                    "reference_types",
+                    offset,
+                )?;
                 (self.tables.len(), self.max_tables(features), "tables")
             }
             TypeRef::Memory(ty) => {
                 self.memories.push(ty);
+                self.num_imported_memories += 1;
+                check_max_or_proposal(
+                    self.memories.len(),
+                    0,
+                    self.max_memories(features),
+                    "memories",
+                    "multi_memory",
+                    offset,
+                )?;
                 (self.memories.len(), self.max_memories(features), "memories")
             }
             TypeRef::Tag(ty) => {
@@ -426,8 +597,9 @@ impl Module {
             }
             TypeRef::Global(ty) => {
                 if !features.mutable_global && ty.mutable {
-                    return Err(BinaryReaderError::new(
+                    return Err(BinaryReaderError::feature_disabled(
                         "mutable global support is not enabled",
+                        "mutable_global",
                         offset,
                     ));
                 }
@@ -457,8 +629,9 @@ impl Module {
         if !features.mutable_global {
             if let EntityType::Global(global_type) = ty {
                 if global_type.mutable {
-                    return Err(BinaryReaderError::new(
+                    return Err(BinaryReaderError::feature_disabled(
                         "mutable global support is not enabled",
+                        "mutable_global",
                         offset,
                     ));
                 }
@@ -473,6 +646,35 @@ impl Module {
         }
     }
 
+    /// Checks that every defined function is exported, referenced by an
+    /// element segment or constant expression, called from one of `bodies`,
+    /// or used as the start function.
+    ///
+    /// `bodies` must be all of this module's function bodies, in order, each
+    /// paired with the functions it calls as recorded while validating it.
+    pub(super) fn check_live_functions(&self, bodies: &[(FunctionBody, Vec<u32>)]) -> Result<()> {
+        let mut live = self.function_references.clone();
+        live.extend(self.start_function);
+        for (_, called) in bodies {
+            live.extend(called.iter().copied());
+        }
+
+        for (i, (body, _)) in bodies.iter().enumerate() {
+            let index = self.num_imported_functions + i as u32;
+            if !live.contains(&index) {
+                return Err(BinaryReaderError::new(
+                    format!(
+                        "function {} is never exported, referenced, called, or used as the start function",
+                        index
+                    ),
+                    body.range().start,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
     pub(super) fn add_function(
         &mut self,
         type_index: u32,
@@ -534,7 +736,13 @@ impl Module {
         types: &'a TypeList,
         offset: usize,
     ) -> Result<&'a FuncType> {
-        Ok(self.type_at(type_index, types, offset)?.unwrap_func_type())
+        match self.type_at(type_index, types, offset)?.as_func_type() {
+            Some(ty) => Ok(ty),
+            None => Err(BinaryReaderError::new(
+                format!("type index {} is not a function type", type_index),
+                offset,
+            )),
+        }
     }
 
     pub(super) fn check_type_ref(
@@ -607,8 +815,9 @@ impl Module {
         self.check_limits(ty.initial, ty.maximum, offset)?;
         let (true_maximum, err) = if ty.memory64 {
             if !features.memory64 {
-                return Err(BinaryReaderError::new(
+                return Err(BinaryReaderError::feature_disabled(
                     "memory64 must be enabled for 64-bit memories",
+                    "memory64",
                     offset,
                 ));
             }
@@ -632,8 +841,9 @@ impl Module {
         }
         if ty.shared {
             if !features.threads {
-                return Err(BinaryReaderError::new(
+                return Err(BinaryReaderError::feature_disabled(
                     "threads must be enabled for shared memories",
+                    "threads",
                     offset,
                 ));
             }
@@ -674,8 +884,9 @@ impl Module {
         offset: usize,
     ) -> Result<()> {
         if !features.exceptions {
-            return Err(BinaryReaderError::new(
+            return Err(BinaryReaderError::feature_disabled(
                 "exceptions proposal not enabled",
+                "exceptions",
                 offset,
             ));
         }
@@ -845,7 +1056,7 @@ impl WasmModuleResources for OperatorValidatorResources<'_> {
     }
 
     fn func_type_at(&self, at: u32) -> Option<&Self::FuncType> {
-        Some(self.types[*self.module.types.get(at as usize)?].unwrap_func_type())
+        self.types[*self.module.types.get(at as usize)?].as_func_type()
     }
 
     fn type_of_function(&self, at: u32) -> Option<&Self::FuncType> {
@@ -892,7 +1103,7 @@ impl WasmModuleResources for ValidatorResources {
     }
 
     fn func_type_at(&self, at: u32) -> Option<&Self::FuncType> {
-        Some(self.0.snapshot.as_ref().unwrap()[*self.0.types.get(at as usize)?].unwrap_func_type())
+        self.0.snapshot.as_ref().unwrap()[*self.0.types.get(at as usize)?].as_func_type()
     }
 
     fn type_of_function(&self, at: u32) -> Option<&Self::FuncType> {
@@ -955,6 +1166,21 @@ mod arc {
         }
     }
 
+    impl<T: Clone> Clone for MaybeOwned<T> {
+        fn clone(&self) -> MaybeOwned<T> {
+            // An owned value may still be mutated, so it can't be shared.
+            let arc = if self.owned {
+                Arc::new((*self.arc).clone())
+            } else {
+                self.arc.clone()
+            };
+            MaybeOwned {
+                owned: self.owned,
+                arc,
+            }
+        }
+    }
+
     impl<T: Default> Default for MaybeOwned<T> {
         fn default() -> MaybeOwned<T> {
             MaybeOwned {
@@ -1,6 +1,7 @@
 use super::operators::OperatorValidator;
 use crate::{BinaryReader, Result, Type};
 use crate::{FunctionBody, Operator, WasmFeatures, WasmModuleResources};
+use std::mem;
 
 /// Validation context for a WebAssembly function.
 ///
@@ -14,6 +15,17 @@ pub struct FuncValidator<T> {
     resources: T,
 }
 
+/// The state of a [`FuncValidator`] part way through a function body.
+///
+/// This is created by [`FuncValidator::snapshot`] and captures the operand and
+/// control stacks, so that the operators following that point can be
+/// revalidated with [`FuncValidator::revalidate_from`] without validating the
+/// whole body again.
+#[derive(Clone)]
+pub struct FuncSnapshot {
+    validator: OperatorValidator,
+}
+
 impl<T: WasmModuleResources> FuncValidator<T> {
     /// Creates a new `FuncValidator`.
     ///
@@ -44,6 +56,29 @@ impl<T: WasmModuleResources> FuncValidator<T> {
         self.validator.operands.len() as u32
     }
 
+    /// Get the deepest nesting of `block`, `loop`, `if` and `try` frames seen
+    /// so far in this function.
+    ///
+    /// The implicit frame of the function body itself isn't counted.
+    pub fn max_control_depth(&self) -> u32 {
+        self.validator.max_control_depth as u32
+    }
+
+    /// Get the number of operators validated so far in this function,
+    /// including the final `end`.
+    pub fn operator_count(&self) -> u64 {
+        self.validator.operator_count
+    }
+
+    /// Takes the functions called directly so far in this function, along
+    /// with whether it also makes indirect calls.
+    pub(crate) fn take_calls(&mut self) -> (Vec<u32>, bool) {
+        (
+            mem::take(&mut self.validator.called_functions),
+            self.validator.calls_indirectly,
+        )
+    }
+
     /// Convenience function to validate an entire function's body.
     ///
     /// You may not end up using this in final implementations because you'll
@@ -96,6 +131,35 @@ impl<T: WasmModuleResources> FuncValidator<T> {
         Ok(())
     }
 
+    /// Captures the current state of this validator, which can later be
+    /// restored with [`FuncValidator::revalidate_from`].
+    pub fn snapshot(&self) -> FuncSnapshot {
+        FuncSnapshot {
+            validator: self.validator.clone(),
+        }
+    }
+
+    /// Restores the state captured by `snapshot` and validates `ops` from
+    /// there.
+    ///
+    /// This is useful to only revalidate the operators following an edit in
+    /// the middle of a function body. The `snapshot` must have been taken
+    /// from a validator for the same function, and
+    /// [`FuncValidator::finish`] still needs to be called after the last
+    /// operator.
+    pub fn revalidate_from<'a>(
+        &mut self,
+        snapshot: FuncSnapshot,
+        ops: impl IntoIterator<Item = Result<(Operator<'a>, usize)>>,
+    ) -> Result<()> {
+        self.validator = snapshot.validator;
+        for op in ops {
+            let (op, offset) = op?;
+            self.op(offset, &op)?;
+        }
+        Ok(())
+    }
+
     /// Function that must be called after the last opcode has been processed.
     ///
     /// This will validate that the function was properly terminated with the
@@ -200,4 +264,46 @@ mod tests {
         assert!(v.op(2, &Operator::I32Const { value: 99 }).is_ok());
         assert_eq!(v.operand_stack_height(), 2);
     }
+
+    #[test]
+    fn revalidate_from_snapshot() {
+        let ops = [
+            Operator::I32Const { value: 1 },
+            Operator::Block {
+                ty: crate::BlockType::Empty,
+            },
+            Operator::I32Const { value: 2 },
+            Operator::Drop,
+            Operator::End,
+            Operator::Drop,
+            Operator::End,
+        ];
+
+        let mut whole = FuncValidator::new(0, 0, &EmptyResources, &Default::default()).unwrap();
+        for (offset, op) in ops.iter().enumerate() {
+            whole.op(offset, op).unwrap();
+        }
+        assert!(whole.finish(ops.len()).is_ok());
+
+        let mut v = FuncValidator::new(0, 0, &EmptyResources, &Default::default()).unwrap();
+        for (offset, op) in ops[..2].iter().enumerate() {
+            v.op(offset, op).unwrap();
+        }
+        let snapshot = v.snapshot();
+        let suffix = ops.iter().cloned().zip(0..).skip(2).map(Ok);
+        assert!(v.revalidate_from(snapshot.clone(), suffix).is_ok());
+        assert!(v.finish(ops.len()).is_ok());
+        assert_eq!(v.operand_stack_height(), whole.operand_stack_height());
+
+        // An edited suffix which leaves a value in the block is rejected.
+        let edited = [
+            Operator::I32Const { value: 2 },
+            Operator::End,
+            Operator::Drop,
+            Operator::End,
+        ];
+        let edited = edited.iter().cloned().zip(2..).map(Ok);
+        let err = v.revalidate_from(snapshot, edited).unwrap_err();
+        assert_eq!(err.offset(), 3);
+    }
 }
@@ -22,6 +22,7 @@
 // confusing it's recommended to read over that section to see how it maps to
 // the various methods here.
 
+use super::types::atomic_memarg;
 use crate::{
     limits::MAX_WASM_FUNCTION_LOCALS, BinaryReaderError, BlockType, MemoryImmediate, Operator,
     Result, SIMDLaneIndex, Type, WasmFeatures, WasmFuncType, WasmModuleResources,
@@ -55,6 +56,14 @@ impl OperatorValidatorError {
         OperatorValidatorError(e)
     }
 
+    /// Create a new `OperatorValidatorError`, with a placeholder offset, for
+    /// an operator which requires the disabled `feature`.
+    pub(crate) fn feature_disabled(message: impl Into<String>, feature: &'static str) -> Self {
+        let offset = std::usize::MAX;
+        let e = BinaryReaderError::feature_disabled(message, feature, offset);
+        OperatorValidatorError(e)
+    }
+
     /// Convert this `OperatorValidatorError` into a `BinaryReaderError` by
     /// supplying an actual offset to replace the internal placeholder offset.
     pub(crate) fn set_offset(mut self, offset: usize) -> BinaryReaderError {
@@ -66,6 +75,7 @@ impl OperatorValidatorError {
 
 type OperatorValidatorResult<T> = std::result::Result<T, OperatorValidatorError>;
 
+#[derive(Clone)]
 pub(crate) struct OperatorValidator {
     // The total number of locals that this function contains
     num_locals: u32,
@@ -85,6 +95,15 @@ pub(crate) struct OperatorValidator {
     // list of blocks that we're currently in.
     pub(crate) operands: Vec<Option<Type>>,
     control: Vec<Frame>,
+    // The deepest nesting of control frames seen so far, not counting the
+    // outermost frame of the function itself.
+    pub(crate) max_control_depth: usize,
+    // The number of operators validated so far.
+    pub(crate) operator_count: u64,
+    // The functions called directly so far, including by `return_call`.
+    pub(crate) called_functions: Vec<u32>,
+    // Whether a `call_indirect` or `return_call_indirect` has been seen.
+    pub(crate) calls_indirectly: bool,
 
     // This is a list of flags for wasm features which are used to gate various
     // instructions.
@@ -96,6 +115,7 @@ pub(crate) struct OperatorValidator {
 
 // This structure corresponds to `ctrl_frame` as specified at in the validation
 // appendix of the wasm spec
+#[derive(Clone)]
 struct Frame {
     // Indicator for what kind of instruction pushed this frame.
     kind: FrameKind,
@@ -147,6 +167,10 @@ impl OperatorValidator {
                 height: 0,
                 unreachable: false,
             }],
+            max_control_depth: 0,
+            operator_count: 0,
+            called_functions: Vec::new(),
+            calls_indirectly: false,
             features: *features,
             br_table_tmp: Vec::new(),
         })
@@ -166,6 +190,10 @@ impl OperatorValidator {
                 height: 0,
                 unreachable: false,
             }],
+            max_control_depth: 0,
+            operator_count: 0,
+            called_functions: Vec::new(),
+            calls_indirectly: false,
             features: *features,
             br_table_tmp: Vec::new(),
         }
@@ -174,7 +202,7 @@ impl OperatorValidator {
     pub fn define_locals(&mut self, offset: usize, count: u32, ty: Type) -> Result<()> {
         self.features
             .check_value_type(ty)
-            .map_err(|e| BinaryReaderError::new(e, offset))?;
+            .map_err(|(e, feature)| BinaryReaderError::feature_disabled(e, feature, offset))?;
         if count == 0 {
             return Ok(());
         }
@@ -218,7 +246,7 @@ impl OperatorValidator {
     fn push_operand(&mut self, ty: Type) -> OperatorValidatorResult<()> {
         self.features
             .check_value_type(ty)
-            .map_err(OperatorValidatorError::new)?;
+            .map_err(|(e, feature)| OperatorValidatorError::feature_disabled(e, feature))?;
         self.operands.push(Some(ty));
         Ok(())
     }
@@ -242,6 +270,11 @@ impl OperatorValidator {
     /// expected and a type was successfully popped, but its exact type is
     /// indeterminate because the current block is unreachable.
     fn pop_operand(&mut self, expected: Option<Type>) -> OperatorValidatorResult<Option<Type>> {
+        // Float operands can only be pushed when floats are enabled, but
+        // unreachable code may still pop them out of thin air.
+        if let Some(Type::F32 | Type::F64) = expected {
+            self.check_floats_enabled()?;
+        }
         let control = self.control.last().unwrap();
         let actual = if self.operands.len() == control.height {
             if control.unreachable {
@@ -296,6 +329,7 @@ impl OperatorValidator {
             height: self.operands.len(),
             unreachable: false,
         });
+        self.max_control_depth = self.max_control_depth.max(self.control.len() - 1);
         // All of the parameters are now also available in this control frame,
         // so we push them here in order.
         for ty in params(ty, resources)? {
@@ -353,8 +387,9 @@ impl OperatorValidator {
         resources: impl WasmModuleResources,
     ) -> OperatorValidatorResult<Type> {
         if memory_index > 0 && !self.features.multi_memory {
-            return Err(OperatorValidatorError::new(
+            return Err(OperatorValidatorError::feature_disabled(
                 "multi-memory support is not enabled",
+                "multi_memory",
             ));
         }
         match resources.memory_at(memory_index) {
@@ -389,8 +424,9 @@ impl OperatorValidator {
     #[cfg(feature = "deterministic")]
     fn check_non_deterministic_enabled(&self) -> OperatorValidatorResult<()> {
         if !self.features.deterministic_only {
-            return Err(OperatorValidatorError::new(
+            return Err(OperatorValidatorError::feature_disabled(
                 "deterministic_only support is not enabled",
+                "deterministic_only",
             ));
         }
         Ok(())
@@ -404,8 +440,9 @@ impl OperatorValidator {
 
     fn check_threads_enabled(&self) -> OperatorValidatorResult<()> {
         if !self.features.threads {
-            return Err(OperatorValidatorError::new(
+            return Err(OperatorValidatorError::feature_disabled(
                 "threads support is not enabled",
+                "threads",
             ));
         }
         Ok(())
@@ -413,8 +450,19 @@ impl OperatorValidator {
 
     fn check_reference_types_enabled(&self) -> OperatorValidatorResult<()> {
         if !self.features.reference_types {
-            return Err(OperatorValidatorError::new(
+            return Err(OperatorValidatorError::feature_disabled(
                 "reference types support is not enabled",
+                "reference_types",
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_floats_enabled(&self) -> OperatorValidatorResult<()> {
+        if !self.features.floats {
+            return Err(OperatorValidatorError::feature_disabled(
+                "floating point not supported on this target",
+                "floats",
             ));
         }
         Ok(())
@@ -422,7 +470,10 @@ impl OperatorValidator {
 
     fn check_simd_enabled(&self) -> OperatorValidatorResult<()> {
         if !self.features.simd {
-            return Err(OperatorValidatorError::new("SIMD support is not enabled"));
+            return Err(OperatorValidatorError::feature_disabled(
+                "SIMD support is not enabled",
+                "simd",
+            ));
         }
         Ok(())
     }
@@ -432,8 +483,9 @@ impl OperatorValidator {
         self.check_non_deterministic_enabled()?;
         self.check_simd_enabled()?;
         if !self.features.relaxed_simd {
-            return Err(OperatorValidatorError::new(
+            return Err(OperatorValidatorError::feature_disabled(
                 "Relaxed SIMD support is not enabled",
+                "relaxed_simd",
             ));
         }
         Ok(())
@@ -441,8 +493,9 @@ impl OperatorValidator {
 
     fn check_exceptions_enabled(&self) -> OperatorValidatorResult<()> {
         if !self.features.exceptions {
-            return Err(OperatorValidatorError::new(
+            return Err(OperatorValidatorError::feature_disabled(
                 "Exceptions support is not enabled",
+                "exceptions",
             ));
         }
         Ok(())
@@ -450,18 +503,27 @@ impl OperatorValidator {
 
     fn check_bulk_memory_enabled(&self) -> OperatorValidatorResult<()> {
         if !self.features.bulk_memory {
-            return Err(OperatorValidatorError::new(
+            return Err(OperatorValidatorError::feature_disabled(
                 "bulk memory support is not enabled",
+                "bulk_memory",
             ));
         }
         Ok(())
     }
 
-    fn check_shared_memarg_wo_align(
+    /// Validates the memory immediate of the atomic memory operator `op`,
+    /// which must specify the natural alignment of the access.
+    fn check_shared_memarg(
         &self,
-        memarg: MemoryImmediate,
+        op: &Operator,
         resources: impl WasmModuleResources,
     ) -> OperatorValidatorResult<Type> {
+        let (memarg, align) = atomic_memarg(op).unwrap();
+        if memarg.align != align {
+            return Err(OperatorValidatorError::new(
+                "atomic accesses must specify their natural alignment",
+            ));
+        }
         self.check_memory_index(memarg.memory, resources)
     }
 
@@ -479,20 +541,18 @@ impl OperatorValidator {
         resources: impl WasmModuleResources,
     ) -> OperatorValidatorResult<()> {
         match ty {
-            BlockType::Empty
-            | BlockType::Type(Type::I32)
-            | BlockType::Type(Type::I64)
-            | BlockType::Type(Type::F32)
-            | BlockType::Type(Type::F64) => Ok(()),
+            BlockType::Empty | BlockType::Type(Type::I32) | BlockType::Type(Type::I64) => Ok(()),
+            BlockType::Type(Type::F32) | BlockType::Type(Type::F64) => self.check_floats_enabled(),
             BlockType::Type(Type::ExternRef) | BlockType::Type(Type::FuncRef) => {
                 self.check_reference_types_enabled()
             }
             BlockType::Type(Type::V128) => self.check_simd_enabled(),
             BlockType::FuncType(idx) => {
                 if !self.features.multi_value {
-                    return Err(OperatorValidatorError::new(
+                    return Err(OperatorValidatorError::feature_disabled(
                         "blocks, loops, and ifs may only produce a resulttype \
                          when multi-value is not enabled",
+                        "multi_value",
                     ));
                 }
                 func_type_at(&resources, idx)?;
@@ -523,6 +583,7 @@ impl OperatorValidator {
         for ty in ty.outputs() {
             self.push_operand(ty)?;
         }
+        self.called_functions.push(function_index);
         Ok(())
     }
 
@@ -555,6 +616,7 @@ impl OperatorValidator {
         for ty in ty.outputs() {
             self.push_operand(ty)?;
         }
+        self.calls_indirectly = true;
         Ok(())
     }
 
@@ -571,6 +633,25 @@ impl OperatorValidator {
         Ok(())
     }
 
+    /// Validates that a tail call to a function of type `ty` produces exactly
+    /// the results of the current function, since `check_return` alone would
+    /// also accept a callee producing only some of them.
+    fn check_tail_call_results(
+        &self,
+        ty: &impl WasmFuncType,
+        resources: &impl WasmModuleResources,
+    ) -> OperatorValidatorResult<()> {
+        if !ty
+            .outputs()
+            .eq(results(self.control[0].block_type, resources)?)
+        {
+            bail_op_err!(
+                "type mismatch: callee results differ from the current function's results"
+            );
+        }
+        Ok(())
+    }
+
     pub fn process_operator(
         &mut self,
         operator: &Operator,
@@ -579,6 +660,7 @@ impl OperatorValidator {
         if self.control.is_empty() {
             bail_op_err!("operators remaining after end of function");
         }
+        self.operator_count += 1;
         match *operator {
             Operator::Nop => {}
 
@@ -756,11 +838,15 @@ impl OperatorValidator {
             Operator::Call { function_index } => self.check_call(function_index, resources)?,
             Operator::ReturnCall { function_index } => {
                 if !self.features.tail_call {
-                    return Err(OperatorValidatorError::new(
+                    return Err(OperatorValidatorError::feature_disabled(
                         "tail calls support is not enabled",
+                        "tail_call",
                     ));
                 }
                 self.check_call(function_index, resources)?;
+                // `check_call` has already verified that the function exists.
+                let ty = resources.type_of_function(function_index).unwrap();
+                self.check_tail_call_results(ty, resources)?;
                 self.check_return(resources)?;
             }
             Operator::CallIndirect {
@@ -769,19 +855,23 @@ impl OperatorValidator {
                 table_byte,
             } => {
                 if table_byte != 0 && !self.features.reference_types {
-                    return Err(OperatorValidatorError::new(
+                    return Err(OperatorValidatorError::feature_disabled(
                         "reference-types not enabled: zero byte expected",
+                        "reference_types",
                     ));
                 }
                 self.check_call_indirect(index, table_index, resources)?
             }
             Operator::ReturnCallIndirect { index, table_index } => {
                 if !self.features.tail_call {
-                    return Err(OperatorValidatorError::new(
+                    return Err(OperatorValidatorError::feature_disabled(
                         "tail calls support is not enabled",
+                        "tail_call",
                     ));
                 }
                 self.check_call_indirect(index, table_index, resources)?;
+                let ty = func_type_at(&resources, index)?;
+                self.check_tail_call_results(ty, resources)?;
                 self.check_return(resources)?;
             }
             Operator::Drop => {
@@ -948,8 +1038,9 @@ impl OperatorValidator {
             }
             Operator::MemorySize { mem, mem_byte } => {
                 if mem_byte != 0 && !self.features.multi_memory {
-                    return Err(OperatorValidatorError::new(
+                    return Err(OperatorValidatorError::feature_disabled(
                         "multi-memory not enabled: zero byte expected",
+                        "multi_memory",
                     ));
                 }
                 let index_ty = self.check_memory_index(mem, resources)?;
@@ -957,8 +1048,9 @@ impl OperatorValidator {
             }
             Operator::MemoryGrow { mem, mem_byte } => {
                 if mem_byte != 0 && !self.features.multi_memory {
-                    return Err(OperatorValidatorError::new(
+                    return Err(OperatorValidatorError::feature_disabled(
                         "multi-memory not enabled: zero byte expected",
+                        "multi_memory",
                     ));
                 }
                 let index_ty = self.check_memory_index(mem, resources)?;
@@ -1199,8 +1291,9 @@ impl OperatorValidator {
             }
             Operator::I32TruncSatF32S | Operator::I32TruncSatF32U => {
                 if !self.features.saturating_float_to_int {
-                    return Err(OperatorValidatorError::new(
+                    return Err(OperatorValidatorError::feature_disabled(
                         "saturating float to int conversions support is not enabled",
+                        "saturating_float_to_int",
                     ));
                 }
                 self.pop_operand(Some(Type::F32))?;
@@ -1208,8 +1301,9 @@ impl OperatorValidator {
             }
             Operator::I32TruncSatF64S | Operator::I32TruncSatF64U => {
                 if !self.features.saturating_float_to_int {
-                    return Err(OperatorValidatorError::new(
+                    return Err(OperatorValidatorError::feature_disabled(
                         "saturating float to int conversions support is not enabled",
+                        "saturating_float_to_int",
                     ));
                 }
                 self.pop_operand(Some(Type::F64))?;
@@ -1217,8 +1311,9 @@ impl OperatorValidator {
             }
             Operator::I64TruncSatF32S | Operator::I64TruncSatF32U => {
                 if !self.features.saturating_float_to_int {
-                    return Err(OperatorValidatorError::new(
+                    return Err(OperatorValidatorError::feature_disabled(
                         "saturating float to int conversions support is not enabled",
+                        "saturating_float_to_int",
                     ));
                 }
                 self.pop_operand(Some(Type::F32))?;
@@ -1226,8 +1321,9 @@ impl OperatorValidator {
             }
             Operator::I64TruncSatF64S | Operator::I64TruncSatF64U => {
                 if !self.features.saturating_float_to_int {
-                    return Err(OperatorValidatorError::new(
+                    return Err(OperatorValidatorError::feature_disabled(
                         "saturating float to int conversions support is not enabled",
+                        "saturating_float_to_int",
                     ));
                 }
                 self.pop_operand(Some(Type::F64))?;
@@ -1235,8 +1331,9 @@ impl OperatorValidator {
             }
             Operator::I32Extend16S | Operator::I32Extend8S => {
                 if !self.features.sign_extension {
-                    return Err(OperatorValidatorError::new(
+                    return Err(OperatorValidatorError::feature_disabled(
                         "sign extension operations support is not enabled",
+                        "sign_extension",
                     ));
                 }
                 self.pop_operand(Some(Type::I32))?;
@@ -1245,153 +1342,154 @@ impl OperatorValidator {
 
             Operator::I64Extend32S | Operator::I64Extend16S | Operator::I64Extend8S => {
                 if !self.features.sign_extension {
-                    return Err(OperatorValidatorError::new(
+                    return Err(OperatorValidatorError::feature_disabled(
                         "sign extension operations support is not enabled",
+                        "sign_extension",
                     ));
                 }
                 self.pop_operand(Some(Type::I64))?;
                 self.push_operand(Type::I64)?;
             }
 
-            Operator::I32AtomicLoad { memarg }
-            | Operator::I32AtomicLoad16U { memarg }
-            | Operator::I32AtomicLoad8U { memarg } => {
+            Operator::I32AtomicLoad { .. }
+            | Operator::I32AtomicLoad16U { .. }
+            | Operator::I32AtomicLoad8U { .. } => {
                 self.check_threads_enabled()?;
-                let ty = self.check_shared_memarg_wo_align(memarg, resources)?;
+                let ty = self.check_shared_memarg(operator, resources)?;
                 self.pop_operand(Some(ty))?;
                 self.push_operand(Type::I32)?;
             }
-            Operator::I64AtomicLoad { memarg }
-            | Operator::I64AtomicLoad32U { memarg }
-            | Operator::I64AtomicLoad16U { memarg }
-            | Operator::I64AtomicLoad8U { memarg } => {
+            Operator::I64AtomicLoad { .. }
+            | Operator::I64AtomicLoad32U { .. }
+            | Operator::I64AtomicLoad16U { .. }
+            | Operator::I64AtomicLoad8U { .. } => {
                 self.check_threads_enabled()?;
-                let ty = self.check_shared_memarg_wo_align(memarg, resources)?;
+                let ty = self.check_shared_memarg(operator, resources)?;
                 self.pop_operand(Some(ty))?;
                 self.push_operand(Type::I64)?;
             }
-            Operator::I32AtomicStore { memarg }
-            | Operator::I32AtomicStore16 { memarg }
-            | Operator::I32AtomicStore8 { memarg } => {
+            Operator::I32AtomicStore { .. }
+            | Operator::I32AtomicStore16 { .. }
+            | Operator::I32AtomicStore8 { .. } => {
                 self.check_threads_enabled()?;
-                let ty = self.check_shared_memarg_wo_align(memarg, resources)?;
+                let ty = self.check_shared_memarg(operator, resources)?;
                 self.pop_operand(Some(Type::I32))?;
                 self.pop_operand(Some(ty))?;
             }
-            Operator::I64AtomicStore { memarg }
-            | Operator::I64AtomicStore32 { memarg }
-            | Operator::I64AtomicStore16 { memarg }
-            | Operator::I64AtomicStore8 { memarg } => {
+            Operator::I64AtomicStore { .. }
+            | Operator::I64AtomicStore32 { .. }
+            | Operator::I64AtomicStore16 { .. }
+            | Operator::I64AtomicStore8 { .. } => {
                 self.check_threads_enabled()?;
-                let ty = self.check_shared_memarg_wo_align(memarg, resources)?;
+                let ty = self.check_shared_memarg(operator, resources)?;
                 self.pop_operand(Some(Type::I64))?;
                 self.pop_operand(Some(ty))?;
             }
-            Operator::I32AtomicRmwAdd { memarg }
-            | Operator::I32AtomicRmwSub { memarg }
-            | Operator::I32AtomicRmwAnd { memarg }
-            | Operator::I32AtomicRmwOr { memarg }
-            | Operator::I32AtomicRmwXor { memarg }
-            | Operator::I32AtomicRmw16AddU { memarg }
-            | Operator::I32AtomicRmw16SubU { memarg }
-            | Operator::I32AtomicRmw16AndU { memarg }
-            | Operator::I32AtomicRmw16OrU { memarg }
-            | Operator::I32AtomicRmw16XorU { memarg }
-            | Operator::I32AtomicRmw8AddU { memarg }
-            | Operator::I32AtomicRmw8SubU { memarg }
-            | Operator::I32AtomicRmw8AndU { memarg }
-            | Operator::I32AtomicRmw8OrU { memarg }
-            | Operator::I32AtomicRmw8XorU { memarg } => {
+            Operator::I32AtomicRmwAdd { .. }
+            | Operator::I32AtomicRmwSub { .. }
+            | Operator::I32AtomicRmwAnd { .. }
+            | Operator::I32AtomicRmwOr { .. }
+            | Operator::I32AtomicRmwXor { .. }
+            | Operator::I32AtomicRmw16AddU { .. }
+            | Operator::I32AtomicRmw16SubU { .. }
+            | Operator::I32AtomicRmw16AndU { .. }
+            | Operator::I32AtomicRmw16OrU { .. }
+            | Operator::I32AtomicRmw16XorU { .. }
+            | Operator::I32AtomicRmw8AddU { .. }
+            | Operator::I32AtomicRmw8SubU { .. }
+            | Operator::I32AtomicRmw8AndU { .. }
+            | Operator::I32AtomicRmw8OrU { .. }
+            | Operator::I32AtomicRmw8XorU { .. } => {
                 self.check_threads_enabled()?;
-                let ty = self.check_shared_memarg_wo_align(memarg, resources)?;
+                let ty = self.check_shared_memarg(operator, resources)?;
                 self.pop_operand(Some(Type::I32))?;
                 self.pop_operand(Some(ty))?;
                 self.push_operand(Type::I32)?;
             }
-            Operator::I64AtomicRmwAdd { memarg }
-            | Operator::I64AtomicRmwSub { memarg }
-            | Operator::I64AtomicRmwAnd { memarg }
-            | Operator::I64AtomicRmwOr { memarg }
-            | Operator::I64AtomicRmwXor { memarg }
-            | Operator::I64AtomicRmw32AddU { memarg }
-            | Operator::I64AtomicRmw32SubU { memarg }
-            | Operator::I64AtomicRmw32AndU { memarg }
-            | Operator::I64AtomicRmw32OrU { memarg }
-            | Operator::I64AtomicRmw32XorU { memarg }
-            | Operator::I64AtomicRmw16AddU { memarg }
-            | Operator::I64AtomicRmw16SubU { memarg }
-            | Operator::I64AtomicRmw16AndU { memarg }
-            | Operator::I64AtomicRmw16OrU { memarg }
-            | Operator::I64AtomicRmw16XorU { memarg }
-            | Operator::I64AtomicRmw8AddU { memarg }
-            | Operator::I64AtomicRmw8SubU { memarg }
-            | Operator::I64AtomicRmw8AndU { memarg }
-            | Operator::I64AtomicRmw8OrU { memarg }
-            | Operator::I64AtomicRmw8XorU { memarg } => {
+            Operator::I64AtomicRmwAdd { .. }
+            | Operator::I64AtomicRmwSub { .. }
+            | Operator::I64AtomicRmwAnd { .. }
+            | Operator::I64AtomicRmwOr { .. }
+            | Operator::I64AtomicRmwXor { .. }
+            | Operator::I64AtomicRmw32AddU { .. }
+            | Operator::I64AtomicRmw32SubU { .. }
+            | Operator::I64AtomicRmw32AndU { .. }
+            | Operator::I64AtomicRmw32OrU { .. }
+            | Operator::I64AtomicRmw32XorU { .. }
+            | Operator::I64AtomicRmw16AddU { .. }
+            | Operator::I64AtomicRmw16SubU { .. }
+            | Operator::I64AtomicRmw16AndU { .. }
+            | Operator::I64AtomicRmw16OrU { .. }
+            | Operator::I64AtomicRmw16XorU { .. }
+            | Operator::I64AtomicRmw8AddU { .. }
+            | Operator::I64AtomicRmw8SubU { .. }
+            | Operator::I64AtomicRmw8AndU { .. }
+            | Operator::I64AtomicRmw8OrU { .. }
+            | Operator::I64AtomicRmw8XorU { .. } => {
                 self.check_threads_enabled()?;
-                let ty = self.check_shared_memarg_wo_align(memarg, resources)?;
+                let ty = self.check_shared_memarg(operator, resources)?;
                 self.pop_operand(Some(Type::I64))?;
                 self.pop_operand(Some(ty))?;
                 self.push_operand(Type::I64)?;
             }
-            Operator::I32AtomicRmwXchg { memarg }
-            | Operator::I32AtomicRmw16XchgU { memarg }
-            | Operator::I32AtomicRmw8XchgU { memarg } => {
+            Operator::I32AtomicRmwXchg { .. }
+            | Operator::I32AtomicRmw16XchgU { .. }
+            | Operator::I32AtomicRmw8XchgU { .. } => {
                 self.check_threads_enabled()?;
-                let ty = self.check_shared_memarg_wo_align(memarg, resources)?;
+                let ty = self.check_shared_memarg(operator, resources)?;
                 self.pop_operand(Some(Type::I32))?;
                 self.pop_operand(Some(ty))?;
                 self.push_operand(Type::I32)?;
             }
-            Operator::I32AtomicRmwCmpxchg { memarg }
-            | Operator::I32AtomicRmw16CmpxchgU { memarg }
-            | Operator::I32AtomicRmw8CmpxchgU { memarg } => {
+            Operator::I32AtomicRmwCmpxchg { .. }
+            | Operator::I32AtomicRmw16CmpxchgU { .. }
+            | Operator::I32AtomicRmw8CmpxchgU { .. } => {
                 self.check_threads_enabled()?;
-                let ty = self.check_shared_memarg_wo_align(memarg, resources)?;
+                let ty = self.check_shared_memarg(operator, resources)?;
                 self.pop_operand(Some(Type::I32))?;
                 self.pop_operand(Some(Type::I32))?;
                 self.pop_operand(Some(ty))?;
                 self.push_operand(Type::I32)?;
             }
-            Operator::I64AtomicRmwXchg { memarg }
-            | Operator::I64AtomicRmw32XchgU { memarg }
-            | Operator::I64AtomicRmw16XchgU { memarg }
-            | Operator::I64AtomicRmw8XchgU { memarg } => {
+            Operator::I64AtomicRmwXchg { .. }
+            | Operator::I64AtomicRmw32XchgU { .. }
+            | Operator::I64AtomicRmw16XchgU { .. }
+            | Operator::I64AtomicRmw8XchgU { .. } => {
                 self.check_threads_enabled()?;
-                let ty = self.check_shared_memarg_wo_align(memarg, resources)?;
+                let ty = self.check_shared_memarg(operator, resources)?;
                 self.pop_operand(Some(Type::I64))?;
                 self.pop_operand(Some(ty))?;
                 self.push_operand(Type::I64)?;
             }
-            Operator::I64AtomicRmwCmpxchg { memarg }
-            | Operator::I64AtomicRmw32CmpxchgU { memarg }
-            | Operator::I64AtomicRmw16CmpxchgU { memarg }
-            | Operator::I64AtomicRmw8CmpxchgU { memarg } => {
+            Operator::I64AtomicRmwCmpxchg { .. }
+            | Operator::I64AtomicRmw32CmpxchgU { .. }
+            | Operator::I64AtomicRmw16CmpxchgU { .. }
+            | Operator::I64AtomicRmw8CmpxchgU { .. } => {
                 self.check_threads_enabled()?;
-                let ty = self.check_shared_memarg_wo_align(memarg, resources)?;
+                let ty = self.check_shared_memarg(operator, resources)?;
                 self.pop_operand(Some(Type::I64))?;
                 self.pop_operand(Some(Type::I64))?;
                 self.pop_operand(Some(ty))?;
                 self.push_operand(Type::I64)?;
             }
-            Operator::MemoryAtomicNotify { memarg } => {
+            Operator::MemoryAtomicNotify { .. } => {
                 self.check_threads_enabled()?;
-                let ty = self.check_shared_memarg_wo_align(memarg, resources)?;
+                let ty = self.check_shared_memarg(operator, resources)?;
                 self.pop_operand(Some(Type::I32))?;
                 self.pop_operand(Some(ty))?;
                 self.push_operand(Type::I32)?;
             }
-            Operator::MemoryAtomicWait32 { memarg } => {
+            Operator::MemoryAtomicWait32 { .. } => {
                 self.check_threads_enabled()?;
-                let ty = self.check_shared_memarg_wo_align(memarg, resources)?;
+                let ty = self.check_shared_memarg(operator, resources)?;
                 self.pop_operand(Some(Type::I64))?;
                 self.pop_operand(Some(Type::I32))?;
                 self.pop_operand(Some(ty))?;
                 self.push_operand(Type::I32)?;
             }
-            Operator::MemoryAtomicWait64 { memarg } => {
+            Operator::MemoryAtomicWait64 { .. } => {
                 self.check_threads_enabled()?;
-                let ty = self.check_shared_memarg_wo_align(memarg, resources)?;
+                let ty = self.check_shared_memarg(operator, resources)?;
                 self.pop_operand(Some(Type::I64))?;
                 self.pop_operand(Some(Type::I64))?;
                 self.pop_operand(Some(ty))?;
@@ -2,16 +2,22 @@
 
 use indexmap::{IndexMap, IndexSet};
 
-use crate::{FuncType, GlobalType, MemoryType, PrimitiveInterfaceType, Result, TableType, Type};
+use crate::{
+    ArrayType, FuncType, GlobalType, MemoryImmediate, MemoryType, Operator, PrimitiveInterfaceType,
+    Result, StructType, TableType, Type,
+};
 use std::{
     borrow::Borrow,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     hash::{Hash, Hasher},
     mem,
     sync::Arc,
 };
 
-use super::{component::ComponentState, core::Module};
+use super::{
+    component::ComponentState,
+    core::{Module, ModuleState},
+};
 
 fn push_primitive_wasm_types(ty: &PrimitiveInterfaceType, wasm_types: &mut Vec<Type>) {
     match ty {
@@ -40,9 +46,18 @@ fn push_primitive_wasm_types(ty: &PrimitiveInterfaceType, wasm_types: &mut Vec<T
 pub struct TypeId(pub(crate) usize);
 
 /// A unified type definition for inspecting WebAssembly modules and components.
+#[derive(Clone)]
 pub enum TypeDef {
     /// The definition is for a core function type.
     Func(FuncType),
+    /// The definition is for a core struct type.
+    ///
+    /// This variant is only supported with the GC proposal.
+    Struct(StructType),
+    /// The definition is for a core array type.
+    ///
+    /// This variant is only supported with the GC proposal.
+    Array(ArrayType),
     /// The definition is for a module type.
     ///
     /// This variant is only supported when parsing a component.
@@ -70,6 +85,13 @@ pub enum TypeDef {
 }
 
 impl TypeDef {
+    pub(crate) fn as_func_type(&self) -> Option<&FuncType> {
+        match self {
+            Self::Func(ty) => Some(ty),
+            _ => None,
+        }
+    }
+
     pub(crate) fn unwrap_func_type(&self) -> &FuncType {
         match self {
             Self::Func(ty) => ty,
@@ -706,26 +728,306 @@ enum TypesKind {
     Component(ComponentState),
 }
 
+/// A structural difference between two sets of [`Types`], as reported by
+/// [`Types::diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeDiff {
+    /// A core function is only present in the other types.
+    FunctionAdded {
+        /// The index of the function.
+        index: u32,
+        /// The type of the added function.
+        ty: FuncType,
+    },
+    /// A core function is only present in the original types.
+    FunctionRemoved {
+        /// The index of the function.
+        index: u32,
+        /// The type of the removed function.
+        ty: FuncType,
+    },
+    /// A core function has a different signature in the other types.
+    FunctionChanged {
+        /// The index of the function.
+        index: u32,
+        /// The original type of the function.
+        old: FuncType,
+        /// The type of the function in the other types.
+        new: FuncType,
+    },
+    /// A memory is only present in the other types.
+    MemoryAdded {
+        /// The index of the memory.
+        index: u32,
+        /// The type of the added memory.
+        ty: MemoryType,
+    },
+    /// A memory is only present in the original types.
+    MemoryRemoved {
+        /// The index of the memory.
+        index: u32,
+        /// The type of the removed memory.
+        ty: MemoryType,
+    },
+    /// A memory has a different type, such as different limits, in the other
+    /// types.
+    MemoryChanged {
+        /// The index of the memory.
+        index: u32,
+        /// The original type of the memory.
+        old: MemoryType,
+        /// The type of the memory in the other types.
+        new: MemoryType,
+    },
+}
+
+/// A load or store found in a function body, as reported by
+/// [`Types::memory_accesses`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryAccess {
+    /// The byte offset of the load or store instruction.
+    pub position: usize,
+    /// The index of the accessed memory.
+    pub memory: u32,
+    /// The static offset immediate of the access.
+    pub offset: u64,
+    /// The alignment immediate of the access, as the exponent of a power of
+    /// two like [`MemoryImmediate::align`].
+    pub align: u8,
+    /// The type of the value which is loaded or stored.
+    pub ty: Type,
+    /// Whether the access is a store rather than a load.
+    pub store: bool,
+}
+
+impl MemoryAccess {
+    /// Returns the access performed by `op`, found at `position`, if it is a
+    /// non-atomic load or store.
+    pub(crate) fn new(op: &Operator, position: usize) -> Option<MemoryAccess> {
+        use Operator::*;
+        let (memarg, ty, store): (&MemoryImmediate, _, _) = match op {
+            I32Load { memarg }
+            | I32Load8S { memarg }
+            | I32Load8U { memarg }
+            | I32Load16S { memarg }
+            | I32Load16U { memarg } => (memarg, Type::I32, false),
+            I64Load { memarg }
+            | I64Load8S { memarg }
+            | I64Load8U { memarg }
+            | I64Load16S { memarg }
+            | I64Load16U { memarg }
+            | I64Load32S { memarg }
+            | I64Load32U { memarg } => (memarg, Type::I64, false),
+            F32Load { memarg } => (memarg, Type::F32, false),
+            F64Load { memarg } => (memarg, Type::F64, false),
+            V128Load { memarg }
+            | V128Load8x8S { memarg }
+            | V128Load8x8U { memarg }
+            | V128Load16x4S { memarg }
+            | V128Load16x4U { memarg }
+            | V128Load32x2S { memarg }
+            | V128Load32x2U { memarg }
+            | V128Load8Splat { memarg }
+            | V128Load16Splat { memarg }
+            | V128Load32Splat { memarg }
+            | V128Load64Splat { memarg }
+            | V128Load32Zero { memarg }
+            | V128Load64Zero { memarg }
+            | V128Load8Lane { memarg, .. }
+            | V128Load16Lane { memarg, .. }
+            | V128Load32Lane { memarg, .. }
+            | V128Load64Lane { memarg, .. } => (memarg, Type::V128, false),
+            I32Store { memarg } | I32Store8 { memarg } | I32Store16 { memarg } => {
+                (memarg, Type::I32, true)
+            }
+            I64Store { memarg }
+            | I64Store8 { memarg }
+            | I64Store16 { memarg }
+            | I64Store32 { memarg } => (memarg, Type::I64, true),
+            F32Store { memarg } => (memarg, Type::F32, true),
+            F64Store { memarg } => (memarg, Type::F64, true),
+            V128Store { memarg }
+            | V128Store8Lane { memarg, .. }
+            | V128Store16Lane { memarg, .. }
+            | V128Store32Lane { memarg, .. }
+            | V128Store64Lane { memarg, .. } => (memarg, Type::V128, true),
+            _ => return None,
+        };
+        Some(MemoryAccess {
+            position,
+            memory: memarg.memory,
+            offset: memarg.offset,
+            align: memarg.align,
+            ty,
+            store,
+        })
+    }
+}
+
+/// Returns the memory immediate of `op` along with the natural alignment of
+/// the access, as an exponent, if it is an atomic memory operator.
+pub(crate) fn atomic_memarg<'a>(op: &'a Operator) -> Option<(&'a MemoryImmediate, u8)> {
+    use Operator::*;
+    Some(match op {
+        I32AtomicLoad8U { memarg }
+        | I64AtomicLoad8U { memarg }
+        | I32AtomicStore8 { memarg }
+        | I64AtomicStore8 { memarg }
+        | I32AtomicRmw8AddU { memarg }
+        | I64AtomicRmw8AddU { memarg }
+        | I32AtomicRmw8SubU { memarg }
+        | I64AtomicRmw8SubU { memarg }
+        | I32AtomicRmw8AndU { memarg }
+        | I64AtomicRmw8AndU { memarg }
+        | I32AtomicRmw8OrU { memarg }
+        | I64AtomicRmw8OrU { memarg }
+        | I32AtomicRmw8XorU { memarg }
+        | I64AtomicRmw8XorU { memarg }
+        | I32AtomicRmw8XchgU { memarg }
+        | I64AtomicRmw8XchgU { memarg }
+        | I32AtomicRmw8CmpxchgU { memarg }
+        | I64AtomicRmw8CmpxchgU { memarg } => (memarg, 0),
+        I32AtomicLoad16U { memarg }
+        | I64AtomicLoad16U { memarg }
+        | I32AtomicStore16 { memarg }
+        | I64AtomicStore16 { memarg }
+        | I32AtomicRmw16AddU { memarg }
+        | I64AtomicRmw16AddU { memarg }
+        | I32AtomicRmw16SubU { memarg }
+        | I64AtomicRmw16SubU { memarg }
+        | I32AtomicRmw16AndU { memarg }
+        | I64AtomicRmw16AndU { memarg }
+        | I32AtomicRmw16OrU { memarg }
+        | I64AtomicRmw16OrU { memarg }
+        | I32AtomicRmw16XorU { memarg }
+        | I64AtomicRmw16XorU { memarg }
+        | I32AtomicRmw16XchgU { memarg }
+        | I64AtomicRmw16XchgU { memarg }
+        | I32AtomicRmw16CmpxchgU { memarg }
+        | I64AtomicRmw16CmpxchgU { memarg } => (memarg, 1),
+        I32AtomicLoad { memarg }
+        | I64AtomicLoad32U { memarg }
+        | I32AtomicStore { memarg }
+        | I64AtomicStore32 { memarg }
+        | I32AtomicRmwAdd { memarg }
+        | I64AtomicRmw32AddU { memarg }
+        | I32AtomicRmwSub { memarg }
+        | I64AtomicRmw32SubU { memarg }
+        | I32AtomicRmwAnd { memarg }
+        | I64AtomicRmw32AndU { memarg }
+        | I32AtomicRmwOr { memarg }
+        | I64AtomicRmw32OrU { memarg }
+        | I32AtomicRmwXor { memarg }
+        | I64AtomicRmw32XorU { memarg }
+        | I32AtomicRmwXchg { memarg }
+        | I64AtomicRmw32XchgU { memarg }
+        | I32AtomicRmwCmpxchg { memarg }
+        | I64AtomicRmw32CmpxchgU { memarg }
+        | MemoryAtomicNotify { memarg }
+        | MemoryAtomicWait32 { memarg } => (memarg, 2),
+        I64AtomicLoad { memarg }
+        | I64AtomicStore { memarg }
+        | I64AtomicRmwAdd { memarg }
+        | I64AtomicRmwSub { memarg }
+        | I64AtomicRmwAnd { memarg }
+        | I64AtomicRmwOr { memarg }
+        | I64AtomicRmwXor { memarg }
+        | I64AtomicRmwXchg { memarg }
+        | I64AtomicRmwCmpxchg { memarg }
+        | MemoryAtomicWait64 { memarg } => (memarg, 3),
+        _ => return None,
+    })
+}
+
+/// An atomic memory operator found in a function body, as reported by
+/// [`Types::atomic_accesses`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtomicAccess {
+    /// The byte offset of the atomic instruction.
+    pub position: usize,
+    /// The index of the accessed memory.
+    pub memory: u32,
+    /// The static offset immediate of the access.
+    pub offset: u64,
+    /// The alignment required by the access, as the exponent of a power of
+    /// two like [`MemoryImmediate::align`].
+    ///
+    /// Atomic accesses must be naturally aligned, so this is the size of the
+    /// accessed value.
+    pub align: u8,
+}
+
+impl AtomicAccess {
+    /// Returns the access performed by `op`, found at `position`, if it is an
+    /// atomic memory operator.
+    pub(crate) fn new(op: &Operator, position: usize) -> Option<AtomicAccess> {
+        let (memarg, align) = atomic_memarg(op)?;
+        Some(AtomicAccess {
+            position,
+            memory: memarg.memory,
+            offset: memarg.offset,
+            align,
+        })
+    }
+}
+
 /// Represents the types known to a [`crate::Validator`] once validation has completed.
 ///
 /// The type information is returned via the [`crate::Validator::end`] method.
 pub struct Types {
     types: TypeList,
     kind: TypesKind,
+    // The statistics below about function bodies are filled in by
+    // `Validator::validate_all` once it has validated all of them.
+    pub(crate) max_control_depth: u32,
+    pub(crate) instruction_count: u64,
+    pub(crate) function_operator_counts: Vec<usize>,
+    pub(crate) function_calls: Vec<(Vec<u32>, bool)>,
+    data_segment_lengths: Vec<usize>,
+    pub(crate) memory_accesses: Vec<MemoryAccess>,
+    pub(crate) atomic_accesses: Vec<AtomicAccess>,
+    has_absolute_offsets: bool,
+    function_sizes: Vec<usize>,
+    global_init_bytes: Vec<Vec<u8>>,
 }
 
 impl Types {
-    pub(crate) fn from_module(types: TypeList, module: Arc<Module>) -> Self {
+    pub(crate) fn from_module(types: TypeList, mut state: ModuleState) -> Self {
         Self {
             types,
-            kind: TypesKind::Module(module),
+            kind: TypesKind::Module(state.module.arc().clone()),
+            max_control_depth: 0,
+            instruction_count: 0,
+            function_operator_counts: Vec::new(),
+            function_calls: Vec::new(),
+            data_segment_lengths: state.data_segment_lengths,
+            memory_accesses: Vec::new(),
+            atomic_accesses: Vec::new(),
+            has_absolute_offsets: state.has_absolute_offsets,
+            function_sizes: state.function_sizes,
+            global_init_bytes: state.global_init_bytes,
         }
     }
 
+    /// Returns whether these are the types of the module `module`.
+    pub(crate) fn is_module(&self, module: &Arc<Module>) -> bool {
+        matches!(&self.kind, TypesKind::Module(m) if Arc::ptr_eq(m, module))
+    }
+
     pub(crate) fn from_component(types: TypeList, component: ComponentState) -> Self {
         Self {
             types,
             kind: TypesKind::Component(component),
+            max_control_depth: 0,
+            instruction_count: 0,
+            function_operator_counts: Vec::new(),
+            function_calls: Vec::new(),
+            data_segment_lengths: Vec::new(),
+            memory_accesses: Vec::new(),
+            atomic_accesses: Vec::new(),
+            has_absolute_offsets: false,
+            function_sizes: Vec::new(),
+            global_init_bytes: Vec::new(),
         }
     }
 
@@ -797,6 +1099,17 @@ impl Types {
         }
     }
 
+    /// Gets the count of imported tables, which is also the index of the
+    /// first defined table.
+    ///
+    /// This is always zero for components.
+    pub fn imported_table_count(&self) -> u32 {
+        match &self.kind {
+            TypesKind::Module(module) => module.num_imported_tables(),
+            TypesKind::Component(_) => 0,
+        }
+    }
+
     /// Gets the type of a memory at the given memory index.
     ///
     /// Returns `None` if the index is out of bounds.
@@ -817,6 +1130,34 @@ impl Types {
         }
     }
 
+    /// Gets the count of imported memories, which is also the index of the
+    /// first defined memory.
+    ///
+    /// This is always zero for components.
+    pub fn imported_memory_count(&self) -> u32 {
+        match &self.kind {
+            TypesKind::Module(module) => module.num_imported_memories(),
+            TypesKind::Component(_) => 0,
+        }
+    }
+
+    /// Gets the sum of the initial number of pages of every memory.
+    ///
+    /// Imported memories are only accounted for if `include_imported` is
+    /// set. All memories of a component are counted regardless of the flag
+    /// since they are always aliased from instances.
+    pub fn total_initial_memory_pages(&self, include_imported: bool) -> u64 {
+        let memories = match &self.kind {
+            TypesKind::Module(module) if !include_imported => {
+                &module.memories[module.num_imported_memories() as usize..]
+            }
+            TypesKind::Module(module) => &module.memories[..],
+            TypesKind::Component(component) => &component.memories[..],
+        };
+
+        memories.iter().map(|memory| memory.initial).sum()
+    }
+
     /// Gets the type of a global at the given global index.
     ///
     /// Returns `None` if the index is out of bounds.
@@ -837,6 +1178,202 @@ impl Types {
         }
     }
 
+    /// Gets the count of imported globals, which is also the index of the
+    /// first defined global.
+    ///
+    /// This is always zero for components.
+    pub fn imported_global_count(&self) -> u32 {
+        match &self.kind {
+            TypesKind::Module(module) => module.num_imported_globals(),
+            TypesKind::Component(_) => 0,
+        }
+    }
+
+    /// Gets the globals read by the initializer of each global.
+    ///
+    /// The map is keyed by global index and only contains the globals whose
+    /// initializer reads another global, which is always an imported one. It
+    /// is always empty for components.
+    pub fn init_expr_global_deps(&self) -> HashMap<u32, Vec<u32>> {
+        match &self.kind {
+            TypesKind::Module(module) => module.global_init_deps.clone(),
+            TypesKind::Component(_) => HashMap::new(),
+        }
+    }
+
+    /// Gets the globals read by the offset and item expressions of each
+    /// element segment.
+    ///
+    /// The map is keyed by element segment index and, like
+    /// [`Types::init_expr_global_deps`], only contains segments reading any
+    /// global.
+    pub fn element_init_expr_global_deps(&self) -> HashMap<u32, Vec<u32>> {
+        match &self.kind {
+            TypesKind::Module(module) => module.element_init_deps.clone(),
+            TypesKind::Component(_) => HashMap::new(),
+        }
+    }
+
+    /// Gets the length in bytes of each data segment, in order.
+    ///
+    /// This is always empty for components.
+    pub fn data_segment_lengths(&self) -> Vec<usize> {
+        self.data_segment_lengths.clone()
+    }
+
+    /// Gets the length in bytes of each defined function's body, in order.
+    ///
+    /// The lengths don't include the size prefix of each body. This is always
+    /// empty for components.
+    pub fn function_sizes(&self) -> Vec<usize> {
+        self.function_sizes.clone()
+    }
+
+    /// Gets the encoded initializer expression of the global at the given
+    /// index, including its trailing `end`, so that it can be copied
+    /// verbatim.
+    ///
+    /// Returns `None` for imported globals and for components.
+    pub fn global_init_bytes(&self, index: u32) -> Option<&[u8]> {
+        let module = match &self.kind {
+            TypesKind::Module(module) => module,
+            TypesKind::Component(_) => return None,
+        };
+        let defined = index.checked_sub(module.num_imported_globals())?;
+        self.global_init_bytes
+            .get(defined as usize)
+            .map(|bytes| bytes.as_slice())
+    }
+
+    /// Returns whether every active data and element segment offset is either
+    /// zero or computed from a global, as is the case for modules which a
+    /// linker can relocate.
+    ///
+    /// This is always `true` for components.
+    pub fn has_only_zero_offsets(&self) -> bool {
+        !self.has_absolute_offsets
+    }
+
+    /// Gets every non-atomic load and store in the module's function bodies,
+    /// in the order they appear.
+    ///
+    /// This is only recorded by [`crate::Validator::validate_all`] when
+    /// enabled with [`crate::Validator::record_memory_accesses`], and is
+    /// otherwise empty.
+    pub fn memory_accesses(&self) -> Vec<MemoryAccess> {
+        self.memory_accesses.clone()
+    }
+
+    /// Gets every atomic memory operator in the module's function bodies, in
+    /// the order they appear, along with the alignment they require.
+    ///
+    /// Like [`Types::memory_accesses`], this is only recorded by
+    /// [`crate::Validator::validate_all`] when enabled with
+    /// [`crate::Validator::record_memory_accesses`], and is otherwise empty.
+    pub fn atomic_accesses(&self) -> Vec<AtomicAccess> {
+        self.atomic_accesses.clone()
+    }
+
+    /// Gets the indices of the functions which may run when the module is
+    /// instantiated, following direct calls from the start function.
+    ///
+    /// Since the target of an indirect call isn't known, every function of the
+    /// module is considered reachable as soon as a reachable function makes
+    /// one. Only functions validated through
+    /// [`Validator::validate_all`](crate::Validator::validate_all) are
+    /// followed, and this is always empty for components and modules without
+    /// a start function.
+    pub fn reachable_from_start(&self) -> HashSet<u32> {
+        let module = match &self.kind {
+            TypesKind::Module(module) => module,
+            TypesKind::Component(_) => return HashSet::new(),
+        };
+        let mut reachable = HashSet::new();
+        let mut worklist = module.start_function.into_iter().collect::<Vec<_>>();
+        while let Some(function) = worklist.pop() {
+            if !reachable.insert(function) {
+                continue;
+            }
+            let calls = function
+                .checked_sub(module.num_imported_functions())
+                .and_then(|defined| self.function_calls.get(defined as usize));
+            if let Some((callees, indirect)) = calls {
+                if *indirect {
+                    return (0..module.functions.len() as u32).collect();
+                }
+                worklist.extend(callees);
+            }
+        }
+        reachable
+    }
+
+    /// Returns whether the function at the given function index is exported.
+    ///
+    /// This is always `false` for components.
+    pub fn is_function_exported(&self, index: u32) -> bool {
+        match &self.kind {
+            TypesKind::Module(module) => module.exported_functions.contains(&index),
+            TypesKind::Component(_) => false,
+        }
+    }
+
+    /// Returns whether the table at the given table index is exported.
+    ///
+    /// This is always `false` for components.
+    pub fn is_table_exported(&self, index: u32) -> bool {
+        match &self.kind {
+            TypesKind::Module(module) => module.exported_tables.contains(&index),
+            TypesKind::Component(_) => false,
+        }
+    }
+
+    /// Returns whether the memory at the given memory index is exported.
+    ///
+    /// This is always `false` for components.
+    pub fn is_memory_exported(&self, index: u32) -> bool {
+        match &self.kind {
+            TypesKind::Module(module) => module.exported_memories.contains(&index),
+            TypesKind::Component(_) => false,
+        }
+    }
+
+    /// Returns whether the global at the given global index is exported.
+    ///
+    /// This is always `false` for components.
+    pub fn is_global_exported(&self, index: u32) -> bool {
+        match &self.kind {
+            TypesKind::Module(module) => module.exported_globals.contains(&index),
+            TypesKind::Component(_) => false,
+        }
+    }
+
+    /// Gets the type of the export with the given name.
+    ///
+    /// The type of an exported function is given as a [`TypeId`], which
+    /// [`Types::type_from_id`] resolves to its [`FuncType`].
+    ///
+    /// This always returns `None` for components.
+    pub fn export(&self, name: &str) -> Option<EntityType> {
+        match &self.kind {
+            TypesKind::Module(module) => module.exports.get(name).copied(),
+            TypesKind::Component(_) => None,
+        }
+    }
+
+    /// Returns the names and types of all exports, in no particular order.
+    ///
+    /// This is always empty for components.
+    pub fn exports(&self) -> impl Iterator<Item = (&str, EntityType)> + '_ {
+        let exports = match &self.kind {
+            TypesKind::Module(module) => Some(&module.exports),
+            TypesKind::Component(_) => None,
+        };
+        exports
+            .into_iter()
+            .flatten()
+            .map(|(name, ty)| (name.as_str(), *ty))
+    }
+
     /// Gets the type of a tag at the given tag index.
     ///
     /// Returns `None` if the index is out of bounds.
@@ -903,6 +1440,37 @@ impl Types {
         }
     }
 
+    /// Gets the count of imported functions, which is also the index of the
+    /// first defined function.
+    ///
+    /// This is always zero for components.
+    pub fn imported_function_count(&self) -> u32 {
+        match &self.kind {
+            TypesKind::Module(module) => module.num_imported_functions(),
+            TypesKind::Component(_) => 0,
+        }
+    }
+
+    /// Gets the module name, field name and signature of each imported
+    /// function, in the order they are imported.
+    ///
+    /// This is always empty for components.
+    pub fn imported_function_signatures(&self) -> Vec<(String, String, FuncType)> {
+        let module = match &self.kind {
+            TypesKind::Module(module) => module,
+            TypesKind::Component(_) => return Vec::new(),
+        };
+        module
+            .function_imports
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (module_name, field))| {
+                let ty = self.function_at(i as u32)?;
+                Some((module_name.clone(), field.clone(), ty.clone()))
+            })
+            .collect()
+    }
+
     /// Gets the type of an element segment at the given element segment index.
     ///
     /// Returns `None` if the index is out of bounds.
@@ -961,6 +1529,24 @@ impl Types {
         }
     }
 
+    /// Returns whether any components are defined inside of this component.
+    ///
+    /// This is always `false` for modules.
+    pub fn has_nested_components(&self) -> bool {
+        self.nested_component_count() > 0
+    }
+
+    /// Gets the count of components defined inside of this component.
+    ///
+    /// Unlike [`Types::component_count`] this doesn't include imported,
+    /// exported, or aliased components.
+    pub fn nested_component_count(&self) -> usize {
+        match &self.kind {
+            TypesKind::Module(_) => 0,
+            TypesKind::Component(component) => component.nested_components,
+        }
+    }
+
     /// Gets the type of an instance at the given instance index.
     ///
     /// Returns `None` if the index is out of bounds or if the instance is not
@@ -1022,6 +1608,85 @@ impl Types {
             TypesKind::Component(component) => component.values.len(),
         }
     }
+
+    /// Gets the deepest nesting of `block`, `loop`, `if` and `try` frames
+    /// across all of the module's functions.
+    ///
+    /// Only functions validated through
+    /// [`Validator::validate_all`](crate::Validator::validate_all) are
+    /// accounted for, and this is always 0 for components.
+    pub fn max_control_depth(&self) -> u32 {
+        self.max_control_depth
+    }
+
+    /// Gets the total number of operators across all of the module's
+    /// functions, including the `end` of each body.
+    ///
+    /// Only functions validated through
+    /// [`Validator::validate_all`](crate::Validator::validate_all) are
+    /// accounted for, and this is always 0 for components.
+    pub fn total_instruction_count(&self) -> u64 {
+        self.instruction_count
+    }
+
+    /// Gets the number of operators in the body of the function at the given
+    /// function index, including the final `end`.
+    ///
+    /// Returns `None` for imported functions, for functions whose body wasn't
+    /// validated through
+    /// [`Validator::validate_all`](crate::Validator::validate_all), and for
+    /// components.
+    pub fn function_operator_count(&self, func_index: u32) -> Option<usize> {
+        let module = match &self.kind {
+            TypesKind::Module(module) => module,
+            TypesKind::Component(_) => return None,
+        };
+        let defined = func_index.checked_sub(module.num_imported_functions())?;
+        self.function_operator_counts.get(defined as usize).copied()
+    }
+
+    /// Reports the structural differences between these types and `other`.
+    ///
+    /// Functions and memories are matched up by index, and functions are
+    /// compared by their core function type, so component functions are
+    /// ignored. The differences are reported in index order, functions first.
+    pub fn diff(&self, other: &Types) -> Vec<TypeDiff> {
+        let mut diffs = Vec::new();
+
+        let count = self.function_count().max(other.function_count());
+        for index in 0..count as u32 {
+            match (self.function_at(index), other.function_at(index)) {
+                (Some(old), Some(new)) if old != new => diffs.push(TypeDiff::FunctionChanged {
+                    index,
+                    old: old.clone(),
+                    new: new.clone(),
+                }),
+                (Some(ty), None) => diffs.push(TypeDiff::FunctionRemoved {
+                    index,
+                    ty: ty.clone(),
+                }),
+                (None, Some(ty)) => diffs.push(TypeDiff::FunctionAdded {
+                    index,
+                    ty: ty.clone(),
+                }),
+                _ => {}
+            }
+        }
+
+        let count = self.memory_count().max(other.memory_count());
+        for index in 0..count as u32 {
+            match (self.memory_at(index), other.memory_at(index)) {
+                (Some(old), Some(new)) if old != new => {
+                    diffs.push(TypeDiff::MemoryChanged { index, old, new })
+                }
+                (Some(ty), None) => diffs.push(TypeDiff::MemoryRemoved { index, ty }),
+                (None, Some(ty)) => diffs.push(TypeDiff::MemoryAdded { index, ty }),
+                _ => {}
+            }
+        }
+
+        diffs
+    }
 }
 
 /// This is a type which mirrors a subset of the `Vec<T>` API, but is intended
@@ -1035,6 +1700,7 @@ impl Types {
 ///
 /// Otherwise, though, this type behaves as if it were a large `Vec<T>`, but
 /// it's represented by lists of contiguous chunks.
+#[derive(Clone)]
 pub(crate) struct SnapshotList<T> {
     // All previous snapshots, the "head" of the list that this type represents.
     // The first entry in this pair is the starting index for all elements
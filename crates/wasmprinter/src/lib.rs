@@ -51,6 +51,8 @@ enum TypeDef<'a> {
     // The inner type is `None` for core functions in a component's function
     // index space.
     Func(Option<FuncType>),
+    // A core GC struct or array type.
+    Gc,
     Module,
     // Stores a map between export name and index into the global type list.
     // The exports are a subset of those exports relevant to the printer's state.
@@ -625,6 +627,19 @@ impl Printer {
                 self.start_group("func");
                 self.print_func_type(state, ty, None)?;
             }
+            wasmparser::TypeDef::Struct(ty) => {
+                self.start_group("struct");
+                for field in ty.fields.iter() {
+                    self.result.push(' ');
+                    self.start_group("field ");
+                    self.print_field_type(field)?;
+                    self.end_group();
+                }
+            }
+            wasmparser::TypeDef::Array(ty) => {
+                self.start_group("array ");
+                self.print_field_type(&ty.field)?;
+            }
         }
         self.end_group(); // inner type
         self.end_group(); // `type` itself
@@ -646,6 +661,10 @@ impl Printer {
                     state.types.push(types.len());
                     types.push(TypeDef::Func(Some(ty)));
                 }
+                wasmparser::TypeDef::Struct(_) | wasmparser::TypeDef::Array(_) => {
+                    state.types.push(types.len());
+                    types.push(TypeDef::Gc);
+                }
             }
         }
 
@@ -717,6 +736,21 @@ impl Printer {
         Ok(())
     }
 
+    fn print_field_type(&mut self, ty: &FieldType) -> Result<()> {
+        if ty.mutable {
+            self.result.push_str("(mut ");
+        }
+        match ty.element_type {
+            StorageType::I8 => self.result.push_str("i8"),
+            StorageType::I16 => self.result.push_str("i16"),
+            StorageType::Val(ty) => self.print_valtype(ty)?,
+        }
+        if ty.mutable {
+            self.result.push(')');
+        }
+        Ok(())
+    }
+
     fn print_reftype(&mut self, ty: Type) -> Result<()> {
         match ty {
             Type::FuncRef => self.result.push_str("func"),
@@ -2402,6 +2436,10 @@ impl Printer {
                             state.types.push(types.len());
                             types.push(TypeDef::Func(Some(ty)));
                         }
+                        wasmparser::TypeDef::Struct(_) | wasmparser::TypeDef::Array(_) => {
+                            state.types.push(types.len());
+                            types.push(TypeDef::Gc);
+                        }
                     }
                 }
                 ModuleType::Export { name, ty } => {
@@ -112,6 +112,8 @@ struct Config {
     min_data_segments: Option<usize>,
     #[clap(long = "max-data-segments")]
     max_data_segments: Option<usize>,
+    #[clap(long = "max-data-bytes")]
+    max_data_bytes: Option<usize>,
     #[clap(long = "max-instructions")]
     max_instructions: Option<usize>,
     #[clap(long = "min-memories")]
@@ -160,6 +162,8 @@ struct Config {
     memory64_enabled: Option<bool>,
     #[clap(long = "canonicalize-nans")]
     canonicalize_nans: Option<bool>,
+    #[clap(long = "deterministic-only")]
+    deterministic_only: Option<bool>,
     /// Limit what kinds of instructions are allowed.
     ///
     /// By default, all kinds are allowed; available kinds: numeric, vector,
@@ -284,6 +288,7 @@ impl wasm_smith::Config for CliAndJsonConfig {
         (max_element_segments, usize, 100),
         (min_data_segments, usize, 0),
         (max_data_segments, usize, 100),
+        (max_data_bytes, usize, usize::MAX),
         (max_instructions, usize, 100),
         (min_memories, u32, 0),
         (max_memories, usize, 1),
@@ -304,6 +309,7 @@ impl wasm_smith::Config for CliAndJsonConfig {
         (max_nesting_depth, usize, 1000),
         (max_type_size, u32, 1000),
         (canonicalize_nans, bool, false),
+        (deterministic_only, bool, false),
     }
 
     fn max_memory_pages(&self, _is_64: bool) -> u64 {
@@ -90,11 +90,13 @@ fn parse_features(arg: &str) -> Result<WasmFeatures> {
         ("multi-value", |f| &mut f.multi_value),
         ("tail-call", |f| &mut f.tail_call),
         ("component-model", |f| &mut f.component_model),
+        ("gc", |f| &mut f.gc),
         ("multi-memory", |f| &mut f.multi_memory),
         ("exception-handling", |f| &mut f.exceptions),
         ("memory64", |f| &mut f.memory64),
         ("extended-const", |f| &mut f.extended_const),
         ("deterministic", |f| &mut f.deterministic_only),
+        ("floats", |f| &mut f.floats),
         ("saturating-float-to-int", |f| {
             &mut f.saturating_float_to_int
         }),
@@ -478,6 +478,8 @@ impl TestState {
             tail_call: true,
             component_model: false,
             deterministic_only: false,
+            floats: true,
+            gc: false,
             multi_value: true,
             multi_memory: true,
             memory64: true,